@@ -0,0 +1,433 @@
+//! Atmospheres around bodies, drawn as a pass over the finished scene.
+//!
+//! Each pixel's view ray is marched through every atmosphere it crosses,
+//! adding the sunlight scattered towards the camera by air molecules
+//! (Rayleigh) and haze (Mie), and dimming whatever is behind. Surfaces inside
+//! an atmosphere are also dimmed by the air between them and the sun, which
+//! reddens them towards the terminator.
+//!
+//! Distances in the shader are in units of the body's radius, relative to
+//! its center, so that thin atmospheres around huge bodies stay precise.
+
+use bytemuck::{Pod, Zeroable};
+use glam::{DVec3, Vec3};
+use wgpu::{include_wgsl, util::DeviceExt};
+
+use crate::{lighting::Lighting, viewport::Viewport, world::World, GraphicsContext};
+
+/// Most atmospheres drawn at once. The nearest ones to the camera are kept.
+const MAX_ATMOSPHERES: usize = 4;
+
+/// Parameters of a body's atmosphere. Heights and lengths are fractions of
+/// the body's radius.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Atmosphere {
+    /// Height of the top of the atmosphere above the surface.
+    pub height: f64,
+    /// Rayleigh scattering coefficients at the surface for red, green and
+    /// blue light, per radius.
+    pub rayleigh: DVec3,
+    /// Height over which the density of air falls by a factor of e.
+    pub rayleigh_scale_height: f64,
+    /// Mie scattering coefficient at the surface, the same for every color,
+    /// per radius.
+    pub mie: f64,
+    pub mie_scale_height: f64,
+    /// How much haze scatters light forward rather than back, from -1 to 1.
+    pub mie_asymmetry: f64,
+    /// Brightness of sunlight entering the atmosphere.
+    pub sun_intensity: f64,
+}
+
+impl Atmosphere {
+    /// Earth's atmosphere, scaled to a body of any size.
+    pub fn earth_like() -> Self {
+        // Earth's radius, which the coefficients are measured against (m).
+        const RADIUS: f64 = 6.371e6;
+        Self {
+            height: 100.0e3 / RADIUS,
+            rayleigh: DVec3::new(5.802e-6, 13.558e-6, 33.1e-6) * RADIUS,
+            rayleigh_scale_height: 8.0e3 / RADIUS,
+            mie: 3.996e-6 * RADIUS,
+            mie_scale_height: 1.2e3 / RADIUS,
+            mie_asymmetry: 0.8,
+            sun_intensity: 15.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Pod, Zeroable)]
+#[repr(C)]
+struct AtmosphereUniforms {
+    // vec4<f32>
+    /// Camera position relative to the center, in radii.
+    eye: [f32; 3],
+    /// Radius of the top of the atmosphere, in radii.
+    top: f32,
+
+    // vec4<f32>
+    sun_direction: [f32; 3],
+    /// Radius of the body in world units, to scale scene depths by.
+    radius: f32,
+
+    // vec4<f32>
+    rayleigh: [f32; 3],
+    rayleigh_scale_height: f32,
+
+    // vec4<f32>
+    sun_color: [f32; 3],
+    mie: f32,
+
+    // vec4<f32>
+    mie_scale_height: f32,
+    mie_asymmetry: f32,
+    _padding: [u8; 8],
+}
+
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct Uniforms {
+    count: u32,
+    z_near: f32,
+    z_far: f32,
+    _padding: [u8; 4],
+    /// Ordered from the farthest to the nearest, in the order they're
+    /// composited.
+    atmospheres: [AtmosphereUniforms; MAX_ATMOSPHERES],
+}
+
+impl Uniforms {
+    /// The atmospheres nearest to `camera`, lit by a light at
+    /// `light_position`, with the viewport's clip planes `z_range`.
+    fn new(
+        world: &World,
+        camera: DVec3,
+        z_range: (f32, f32),
+        light_position: DVec3,
+        light_color: Vec3,
+    ) -> Self {
+        let mut bodies: Vec<_> = world
+            .body_tags
+            .iter()
+            .map(|tag| world.body(tag))
+            .filter_map(|body| Some((body, body.atmosphere()?)))
+            .map(|(body, atmosphere)| (body.position().distance(camera), body, atmosphere))
+            .collect();
+        bodies.sort_by(|a, b| a.0.total_cmp(&b.0));
+        bodies.truncate(MAX_ATMOSPHERES);
+        bodies.reverse();
+
+        let mut uniforms = Self {
+            count: bodies.len() as u32,
+            z_near: z_range.0,
+            z_far: z_range.1,
+            _padding: Default::default(),
+            atmospheres: Default::default(),
+        };
+        for (uniform, &(_, body, atmosphere)) in uniforms.atmospheres.iter_mut().zip(&bodies) {
+            let sun_direction = (light_position - body.position()).normalize_or_zero();
+            *uniform = AtmosphereUniforms {
+                eye: ((camera - body.position()) / body.radius())
+                    .as_vec3()
+                    .into(),
+                top: (1.0 + atmosphere.height) as f32,
+                sun_direction: sun_direction.as_vec3().into(),
+                radius: body.radius() as f32,
+                rayleigh: atmosphere.rayleigh.as_vec3().into(),
+                rayleigh_scale_height: atmosphere.rayleigh_scale_height as f32,
+                sun_color: (light_color * atmosphere.sun_intensity as f32).into(),
+                mie: atmosphere.mie as f32,
+                mie_scale_height: atmosphere.mie_scale_height as f32,
+                mie_asymmetry: atmosphere.mie_asymmetry as f32,
+                _padding: Default::default(),
+            };
+        }
+        uniforms
+    }
+}
+
+pub struct AtmosphereRenderer {
+    gfx: GraphicsContext,
+    bind_group_layout: wgpu::BindGroupLayout,
+    uniform_buffer: wgpu::Buffer,
+    /// Multiplies the scene by the transmittance of the atmospheres.
+    transmittance_pipeline: wgpu::RenderPipeline,
+    /// Adds the light scattered towards the camera.
+    inscatter_pipeline: wgpu::RenderPipeline,
+    count: usize,
+}
+
+impl AtmosphereRenderer {
+    pub fn new(gfx: &GraphicsContext, viewport: &Viewport) -> Self {
+        let uniform_buffer = gfx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("AtmosphereRenderer::uniform_buffer"),
+                contents: bytemuck::bytes_of(&Uniforms::zeroed()),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+
+        let bind_group_layout =
+            gfx.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("AtmosphereRenderer::bind_group_layout"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Texture {
+                                sample_type: wgpu::TextureSampleType::Depth,
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                                multisampled: false,
+                            },
+                            count: None,
+                        },
+                    ],
+                });
+
+        let pipeline_layout = gfx
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("AtmosphereRenderer::pipeline_layout"),
+                bind_group_layouts: &[viewport.bind_group_layout(), &bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let shader_module = gfx
+            .device
+            .create_shader_module(&include_wgsl!("atmosphere.wgsl"));
+
+        let create_pipeline = |label, entry_point, blend| {
+            gfx.device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some(label),
+                    layout: Some(&pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader_module,
+                        entry_point: "vs_main",
+                        buffers: &[],
+                    },
+                    primitive: Default::default(),
+                    depth_stencil: None,
+                    multisample: Default::default(),
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader_module,
+                        entry_point,
+                        targets: &[wgpu::ColorTargetState {
+                            format: gfx.render_format,
+                            blend: Some(blend),
+                            write_mask: wgpu::ColorWrites::COLOR,
+                        }],
+                    }),
+                    multiview: None,
+                })
+        };
+        let transmittance_pipeline = create_pipeline(
+            "AtmosphereRenderer::transmittance_pipeline",
+            "fs_transmittance",
+            wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Zero,
+                    dst_factor: wgpu::BlendFactor::Src,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent::REPLACE,
+            },
+        );
+        let inscatter_pipeline = create_pipeline(
+            "AtmosphereRenderer::inscatter_pipeline",
+            "fs_inscatter",
+            wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent::REPLACE,
+            },
+        );
+
+        Self {
+            gfx: gfx.clone(),
+            bind_group_layout,
+            uniform_buffer,
+            transmittance_pipeline,
+            inscatter_pipeline,
+            count: 0,
+        }
+    }
+
+    /// Uploads the atmospheres of the world's bodies as seen from the
+    /// viewport, lit from the light's position.
+    pub fn update(&mut self, world: &World, viewport: &Viewport, lighting: &Lighting) {
+        let uniforms = Uniforms::new(
+            world,
+            viewport.camera_pos().as_dvec3(),
+            (viewport.z_near, viewport.z_far),
+            lighting.position.as_dvec3(),
+            lighting.color,
+        );
+        self.count = uniforms.count as usize;
+        self.gfx
+            .queue
+            .write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+    }
+
+    /// Draws over the scene in `frame_view`, using the scene's depth to find
+    /// where view rays end.
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        frame_view: &wgpu::TextureView,
+        depth_view: &wgpu::TextureView,
+        viewport: &Viewport,
+    ) {
+        if self.count == 0 {
+            return;
+        }
+        let bind_group = self
+            .gfx
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("AtmosphereRenderer::bind_group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: self.uniform_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(depth_view),
+                    },
+                ],
+            });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("AtmosphereRenderer::render_pass"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: frame_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_bind_group(0, viewport.bind_group(), &[]);
+        render_pass.set_bind_group(1, &bind_group, &[]);
+        render_pass.set_pipeline(&self.transmittance_pipeline);
+        render_pass.draw(0..3, 0..1);
+        render_pass.set_pipeline(&self.inscatter_pipeline);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= 1e-6 * expected.abs(),
+            "{} isn't {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn earth_like_is_relative_to_the_radius() {
+        let atmosphere = Atmosphere::earth_like();
+        let radius = 6.371e6;
+        assert_close(atmosphere.height * radius, 100.0e3);
+        assert_close(atmosphere.rayleigh_scale_height * radius, 8.0e3);
+        assert_close(atmosphere.mie_scale_height * radius, 1.2e3);
+        assert_close(atmosphere.rayleigh.z / radius, 33.1e-6);
+        // Blue is scattered the most, which makes the sky blue.
+        let rayleigh = atmosphere.rayleigh;
+        assert!(rayleigh.x < rayleigh.y && rayleigh.y < rayleigh.z);
+        // Haze is thinner than the air, and scatters mostly forward.
+        assert!(atmosphere.mie_scale_height < atmosphere.rayleigh_scale_height);
+        assert!((0.0..1.0).contains(&atmosphere.mie_asymmetry));
+    }
+
+    #[test]
+    fn uniforms_are_in_units_of_the_body_radius() {
+        let world = World::new();
+        let earth = world
+            .body_tags
+            .iter()
+            .map(|tag| world.body(tag))
+            .find(|body| body.name() == "Earth")
+            .unwrap();
+        let camera = earth.position() + DVec3::new(2.0, 0.0, 0.0) * earth.radius();
+        let color = Vec3::new(1.0, 0.5, 0.25);
+        let uniforms = Uniforms::new(&world, camera, (0.5, 500.0), DVec3::ZERO, color);
+
+        assert_eq!(uniforms.count, 1);
+        assert_eq!((uniforms.z_near, uniforms.z_far), (0.5, 500.0));
+        let atmosphere = earth.atmosphere().unwrap();
+        let uniform = &uniforms.atmospheres[0];
+        assert_eq!(uniform.eye, [2.0, 0.0, 0.0]);
+        assert_eq!(uniform.top, (1.0 + atmosphere.height) as f32);
+        assert_eq!(uniform.radius, earth.radius() as f32);
+        let sun_direction = -earth.position().normalize().as_vec3();
+        assert!((Vec3::from(uniform.sun_direction) - sun_direction).length() < 1e-6);
+        let sun_color = color * atmosphere.sun_intensity as f32;
+        assert_eq!(uniform.sun_color, <[f32; 3]>::from(sun_color));
+        assert_eq!(
+            uniform.rayleigh,
+            <[f32; 3]>::from(atmosphere.rayleigh.as_vec3())
+        );
+        assert_eq!(uniform.mie, atmosphere.mie as f32);
+        // The other slots are left empty.
+        assert_eq!(uniforms.atmospheres[1].top, 0.0);
+    }
+
+    #[test]
+    fn uniforms_are_packed_for_the_shader() {
+        let atmosphere = AtmosphereUniforms {
+            eye: [1.0, 2.0, 3.0],
+            top: 4.0,
+            sun_direction: [5.0, 6.0, 7.0],
+            radius: 8.0,
+            rayleigh: [9.0, 10.0, 11.0],
+            rayleigh_scale_height: 12.0,
+            sun_color: [13.0, 14.0, 15.0],
+            mie: 16.0,
+            mie_scale_height: 17.0,
+            mie_asymmetry: 18.0,
+            _padding: Default::default(),
+        };
+        let floats: &[f32] = bytemuck::cast_slice(bytemuck::bytes_of(&atmosphere));
+        assert_eq!(floats.len(), 20);
+        let expected: Vec<f32> = (1..=18).map(|i| i as f32).collect();
+        assert_eq!(floats[..18], expected[..]);
+
+        let uniforms = Uniforms {
+            count: 2,
+            z_near: 0.5,
+            z_far: 2.0,
+            _padding: Default::default(),
+            atmospheres: [atmosphere; MAX_ATMOSPHERES],
+        };
+        let floats: &[f32] = bytemuck::cast_slice(bytemuck::bytes_of(&uniforms));
+        assert_eq!(floats.len(), 4 + 20 * MAX_ATMOSPHERES);
+        assert_eq!(floats[0].to_bits(), 2);
+        assert_eq!(floats[1..3], [0.5, 2.0]);
+        assert_eq!(floats[4..24], floats[24..44]);
+        assert_eq!(floats[4], 1.0);
+    }
+}
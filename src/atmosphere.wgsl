@@ -0,0 +1,217 @@
+struct Viewport {
+    view_proj: mat4x4<f32>;
+    eye: vec3<f32>;
+    forward_xfov: vec4<f32>;
+    up_yfov: vec4<f32>;
+};
+
+struct Atmosphere {
+    eye_top: vec4<f32>;
+    sun_direction_radius: vec4<f32>;
+    rayleigh: vec4<f32>;
+    sun_color_mie: vec4<f32>;
+    mie_scale_height_asymmetry: vec4<f32>;
+};
+
+struct Atmospheres {
+    count: u32;
+    z_near: f32;
+    z_far: f32;
+    atmospheres: array<Atmosphere, 4>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+};
+
+// Light passing through an atmosphere: how much of what's behind gets
+// through, and how much sunlight is scattered into the ray.
+struct Scattering {
+    transmittance: vec3<f32>;
+    inscatter: vec3<f32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> viewport: Viewport;
+
+[[group(1), binding(0)]]
+var<uniform> uniforms: Atmospheres;
+[[group(1), binding(1)]]
+var depth_texture: texture_depth_2d;
+
+let PI: f32 = 3.14159265;
+let VIEW_SAMPLES: i32 = 16;
+let LIGHT_SAMPLES: i32 = 8;
+// Haze absorbs some light as well as scattering it.
+let MIE_EXTINCTION: f32 = 1.1;
+
+// A triangle covering the screen.
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+    return out;
+}
+
+// Distances along the ray to where it enters and leaves a sphere around the
+// origin, with the first greater than the second if it misses.
+fn intersect_sphere(origin: vec3<f32>, direction: vec3<f32>, radius: f32) -> vec2<f32> {
+    let b = dot(origin, direction);
+    let c = dot(origin, origin) - radius * radius;
+    let discriminant = b * b - c;
+    if (discriminant < 0.0) {
+        return vec2<f32>(1.0, -1.0);
+    }
+    let root = sqrt(discriminant);
+    return vec2<f32>(-b - root, -b + root);
+}
+
+// Rayleigh and Mie densities relative to the surface at a point.
+fn density(atmosphere: Atmosphere, point: vec3<f32>) -> vec2<f32> {
+    let height = max(length(point) - 1.0, 0.0);
+    return exp(-height / vec2<f32>(
+        atmosphere.rayleigh.w,
+        atmosphere.mie_scale_height_asymmetry.x,
+    ));
+}
+
+fn extinction(atmosphere: Atmosphere, optical_depth: vec2<f32>) -> vec3<f32> {
+    let rayleigh = atmosphere.rayleigh.xyz * optical_depth.x;
+    let mie = atmosphere.sun_color_mie.w * MIE_EXTINCTION * optical_depth.y;
+    return exp(-(rayleigh + mie));
+}
+
+// Transmittance from a point to the sun, which is zero in the body's shadow.
+fn sun_transmittance(atmosphere: Atmosphere, point: vec3<f32>) -> vec3<f32> {
+    let sun = atmosphere.sun_direction_radius.xyz;
+    let ground = intersect_sphere(point, sun, 1.0);
+    if (ground.x <= ground.y && ground.x > 0.0) {
+        return vec3<f32>(0.0);
+    }
+    let exit = intersect_sphere(point, sun, atmosphere.eye_top.w).y;
+    let step = exit / f32(LIGHT_SAMPLES);
+    var optical_depth = vec2<f32>(0.0);
+    for (var i = 0; i < LIGHT_SAMPLES; i = i + 1) {
+        let position = point + sun * (f32(i) + 0.5) * step;
+        optical_depth = optical_depth + density(atmosphere, position) * step;
+    }
+    return extinction(atmosphere, optical_depth);
+}
+
+// Marches a view ray from `origin` through the atmosphere, up to
+// `scene_distance` in radii where the scene is hit. Inscattering is only
+// computed if asked for, since it takes most of the work.
+fn scatter(
+    atmosphere: Atmosphere,
+    direction: vec3<f32>,
+    scene_distance: f32,
+    with_inscatter: bool,
+) -> Scattering {
+    var out: Scattering;
+    out.transmittance = vec3<f32>(1.0);
+    out.inscatter = vec3<f32>(0.0);
+
+    let origin = atmosphere.eye_top.xyz;
+    let shell = intersect_sphere(origin, direction, atmosphere.eye_top.w);
+    if (shell.x > shell.y || shell.y < 0.0) {
+        return out;
+    }
+    let start = max(shell.x, 0.0);
+    var end = min(shell.y, scene_distance);
+    let ground = intersect_sphere(origin, direction, 1.0);
+    if (ground.x <= ground.y && ground.x > 0.0) {
+        end = min(end, ground.x);
+    }
+    if (end <= start) {
+        return out;
+    }
+
+    let step = (end - start) / f32(VIEW_SAMPLES);
+    var optical_depth = vec2<f32>(0.0);
+    var rayleigh = vec3<f32>(0.0);
+    var mie = vec3<f32>(0.0);
+    for (var i = 0; i < VIEW_SAMPLES; i = i + 1) {
+        let position = origin + direction * (start + (f32(i) + 0.5) * step);
+        let step_depth = density(atmosphere, position) * step;
+        optical_depth = optical_depth + step_depth;
+        if (with_inscatter) {
+            let light = extinction(atmosphere, optical_depth)
+                * sun_transmittance(atmosphere, position);
+            rayleigh = rayleigh + light * step_depth.x;
+            mie = mie + light * step_depth.y;
+        }
+    }
+    out.transmittance = extinction(atmosphere, optical_depth);
+
+    // Surfaces inside the atmosphere were lit by the sun through the air
+    // above them. Towards the terminator, where the sun sets, the scene
+    // has only ambient light, which is left alone.
+    if (end < shell.y) {
+        let surface = origin + direction * end;
+        let sun_height = dot(normalize(surface), atmosphere.sun_direction_radius.xyz);
+        let sunlit = clamp(sun_height * 10.0, 0.0, 1.0);
+        let tint = mix(vec3<f32>(1.0), sun_transmittance(atmosphere, surface), sunlit);
+        out.transmittance = out.transmittance * tint;
+    }
+
+    if (with_inscatter) {
+        let mu = dot(direction, atmosphere.sun_direction_radius.xyz);
+        let g = atmosphere.mie_scale_height_asymmetry.y;
+        let rayleigh_phase = 3.0 / (16.0 * PI) * (1.0 + mu * mu);
+        let mie_phase = 3.0 / (8.0 * PI) * ((1.0 - g * g) * (1.0 + mu * mu))
+            / ((2.0 + g * g) * pow(1.0 + g * g - 2.0 * g * mu, 1.5));
+        out.inscatter = atmosphere.sun_color_mie.rgb * (
+            rayleigh * atmosphere.rayleigh.xyz * rayleigh_phase
+            + mie * atmosphere.sun_color_mie.w * mie_phase
+        );
+    }
+    return out;
+}
+
+// Composites the atmospheres along the view ray through a pixel, from the
+// farthest to the nearest.
+fn scatter_all(pixel: vec4<f32>, with_inscatter: bool) -> Scattering {
+    let size = vec2<f32>(textureDimensions(depth_texture));
+    let ndc = vec2<f32>(2.0 * pixel.x / size.x - 1.0, 1.0 - 2.0 * pixel.y / size.y);
+    let forward = viewport.forward_xfov.xyz;
+    let up = viewport.up_yfov.xyz;
+    let right = cross(forward, up);
+    let direction = normalize(
+        forward
+        + right * ndc.x * tan(0.5 * viewport.forward_xfov.w)
+        + up * ndc.y * tan(0.5 * viewport.up_yfov.w)
+    );
+
+    // Distance to the scene along the ray, from its depth.
+    let depth = textureLoad(depth_texture, vec2<i32>(pixel.xy), 0);
+    var scene_distance = 3.0e38;
+    if (depth < 1.0) {
+        let near = uniforms.z_near;
+        let far = uniforms.z_far;
+        let view_depth = near * far / (far - depth * (far - near));
+        scene_distance = view_depth / dot(direction, forward);
+    }
+
+    var total: Scattering;
+    total.transmittance = vec3<f32>(1.0);
+    total.inscatter = vec3<f32>(0.0);
+    for (var i = 0u; i < uniforms.count; i = i + 1u) {
+        let atmosphere = uniforms.atmospheres[i];
+        let radius = atmosphere.sun_direction_radius.w;
+        let layer = scatter(atmosphere, direction, scene_distance / radius, with_inscatter);
+        total.transmittance = total.transmittance * layer.transmittance;
+        total.inscatter = total.inscatter * layer.transmittance + layer.inscatter;
+    }
+    return total;
+}
+
+[[stage(fragment)]]
+fn fs_transmittance(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return vec4<f32>(scatter_all(in.clip_position, false).transmittance, 1.0);
+}
+
+[[stage(fragment)]]
+fn fs_inscatter(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return vec4<f32>(scatter_all(in.clip_position, true).inscatter, 1.0);
+}
@@ -0,0 +1,277 @@
+//! Camera controllers that drive a [`Viewport`].
+
+use glam::{DMat3, DQuat, DVec3, Quat};
+use valet::Tag;
+
+use crate::{
+    viewport::{self, Viewport},
+    world::{Body, World},
+};
+
+/// How long it takes to move between targets (seconds).
+const TRANSITION_TIME: f64 = 1.0;
+
+/// Zoom factor applied per line of mouse wheel scrolling.
+const ZOOM_PER_LINE: f64 = 1.15;
+
+/// Closest approach to the target, in multiples of its radius.
+const MIN_DISTANCE: f64 = 1.05;
+
+/// Initial distance after selecting a target, in multiples of its radius.
+const DEFAULT_DISTANCE: f64 = 4.0;
+
+/// Furthest distance from the target (meters).
+const MAX_DISTANCE: f64 = 1.0e14;
+
+/// Rate at which the reference frame converges to the target frame (1/s).
+const FRAME_SMOOTHING: f32 = 5.0;
+
+/// Ratio between the far and near clipping planes.
+const DEPTH_RANGE: f32 = 1.0e6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Fly around freely with the movement keys.
+    FreeFly,
+    /// Orbit around the selected body, rotating by dragging with the mouse.
+    Orbit,
+}
+
+/// Camera that orbits and follows a body in the [`World`].
+///
+/// The viewing direction is taken from the viewport's `pitch` and `yaw`, so
+/// switching between camera modes keeps looking the same way.
+pub struct OrbitCamera {
+    target: Option<Tag<Body>>,
+    /// Distance from the center of the target (meters).
+    distance: f64,
+    /// Whether to rotate along with the target's orbital frame.
+    pub co_rotate: bool,
+    frame: Quat,
+    transition: Option<Transition>,
+}
+
+/// In-progress movement from one target to another.
+///
+/// Stored as offsets from the new target so the camera keeps up with it even
+/// when it is moving quickly.
+struct Transition {
+    focus_offset: DVec3,
+    log_distance_offset: f64,
+    elapsed: f64,
+}
+
+impl Transition {
+    /// Fraction of the offsets that remain, smoothly going from 1 to 0.
+    fn remaining(&self) -> f64 {
+        let t = (self.elapsed / TRANSITION_TIME).clamp(0.0, 1.0);
+        1.0 - t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl OrbitCamera {
+    pub fn new() -> Self {
+        Self {
+            target: None,
+            distance: 1.0,
+            co_rotate: false,
+            frame: Quat::IDENTITY,
+            transition: None,
+        }
+    }
+
+    pub fn target(&self) -> Option<&Tag<Body>> {
+        self.target.as_ref()
+    }
+
+    /// Distance from the point being looked at, including any transition in
+    /// progress.
+    pub fn distance(&self) -> f64 {
+        match &self.transition {
+            Some(transition) => {
+                self.distance * (transition.log_distance_offset * transition.remaining()).exp()
+            }
+            None => self.distance,
+        }
+    }
+
+    /// The point being looked at, including any transition in progress.
+    pub fn focus(&self, world: &World) -> Option<DVec3> {
+        let position = world.body(self.target.as_ref()?).position();
+        match &self.transition {
+            Some(transition) => Some(position + transition.focus_offset * transition.remaining()),
+            None => Some(position),
+        }
+    }
+
+    /// Position of the eye when looking along the unit vector `forward`,
+    /// `distance()` away from the focus.
+    pub fn eye(&self, world: &World, forward: DVec3) -> Option<DVec3> {
+        Some(self.focus(world)? - self.distance() * forward)
+    }
+
+    /// Select a new target, smoothly moving from the current one.
+    pub fn set_target(&mut self, world: &World, tag: Tag<Body>) {
+        let old_focus = self.focus(world);
+        let old_distance = self.distance();
+
+        let body = world.body(&tag);
+        self.target = Some(tag);
+        self.distance = body.radius() * DEFAULT_DISTANCE;
+        self.transition = old_focus.map(|old_focus| Transition {
+            focus_offset: old_focus - body.position(),
+            log_distance_offset: (old_distance / self.distance).ln(),
+            elapsed: 0.0,
+        });
+    }
+
+    /// Move `steps` bodies forward (or backward, if negative) through the
+    /// world's list of bodies.
+    pub fn cycle_target(&mut self, world: &World, steps: i32) {
+        let tags = &world.body_tags;
+        if tags.is_empty() {
+            return;
+        }
+        let current = self
+            .target
+            .and_then(|target| tags.iter().position(|&tag| tag == target));
+        let index = match current {
+            Some(index) => (index as i64 + steps as i64).rem_euclid(tags.len() as i64) as usize,
+            None => 0,
+        };
+        self.set_target(world, tags[index]);
+    }
+
+    /// Zoom in by the given number of scroll wheel lines (out, if negative).
+    pub fn zoom(&mut self, world: &World, lines: f64) {
+        let min_distance = match &self.target {
+            Some(tag) => world.body(tag).radius() * MIN_DISTANCE,
+            None => 0.0,
+        };
+        self.distance = (self.distance * ZOOM_PER_LINE.powf(-lines))
+            .clamp(min_distance, MAX_DISTANCE.max(min_distance));
+    }
+
+    pub fn update(&mut self, world: &World, viewport: &mut Viewport, dt: f64) {
+        if let Some(transition) = &mut self.transition {
+            transition.elapsed += dt;
+            if transition.elapsed >= TRANSITION_TIME {
+                self.transition = None;
+            }
+        }
+
+        let target_frame = match (&self.target, self.co_rotate) {
+            (Some(tag), true) => orbital_frame(world, tag).unwrap_or(Quat::IDENTITY),
+            _ => Quat::IDENTITY,
+        };
+        let blend = 1.0 - (-FRAME_SMOOTHING * dt as f32).exp();
+        self.frame = self.frame.slerp(target_frame, blend).normalize();
+
+        if self.target.is_none() {
+            return;
+        }
+        viewport.frame = self.frame;
+        if let Some(eye) = self.eye(world, viewport.forward().as_dvec3()) {
+            viewport.camera_position = eye.as_vec3();
+        }
+        let distance = self.distance();
+
+        let surface_distance = match &self.target {
+            Some(tag) => distance - world.body(tag).radius(),
+            None => distance,
+        };
+        viewport.z_near = (0.5 * surface_distance as f32).max(viewport::Z_NEAR);
+        viewport.z_far = viewport.z_near * DEPTH_RANGE;
+    }
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Frame of the body's orbit around its parent, with prograde as "up" (+Z)
+/// and radial-out as +X.
+///
+/// Returns `None` if the body is not moving relative to its parent.
+fn orbital_frame(world: &World, tag: &Tag<Body>) -> Option<Quat> {
+    let state = world.relative_state(tag);
+    let prograde = state.velocity.try_normalize()?;
+    let radial = state
+        .position
+        .reject_from_normalized(prograde)
+        .try_normalize()?;
+    let frame = DMat3::from_cols(radial, prograde.cross(radial), prograde);
+    Some(DQuat::from_mat3(&frame).as_f32())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= 1e-9 * expected.abs(),
+            "{} isn't {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn zoom_is_clamped_to_the_surface_and_the_maximum() {
+        let world = World::new();
+        let mut camera = OrbitCamera::new();
+        camera.cycle_target(&world, 1);
+        let radius = world.body(camera.target().unwrap()).radius();
+        assert_close(camera.distance(), DEFAULT_DISTANCE * radius);
+
+        camera.zoom(&world, 1.0);
+        assert_close(camera.distance(), DEFAULT_DISTANCE * radius / ZOOM_PER_LINE);
+        camera.zoom(&world, 1000.0);
+        assert_close(camera.distance(), MIN_DISTANCE * radius);
+        camera.zoom(&world, -1000.0);
+        assert_close(camera.distance(), MAX_DISTANCE);
+    }
+
+    #[test]
+    fn cycling_targets_wraps_around() {
+        let world = World::new();
+        let tags = &world.body_tags;
+        let last = tags.len() - 1;
+        let mut camera = OrbitCamera::new();
+        assert_eq!(camera.target(), None);
+
+        // The first step picks the first body, whichever way it goes.
+        camera.cycle_target(&world, -1);
+        assert_eq!(camera.target(), Some(&tags[0]));
+        camera.cycle_target(&world, -1);
+        assert_eq!(camera.target(), Some(&tags[last]));
+        camera.cycle_target(&world, 1);
+        assert_eq!(camera.target(), Some(&tags[0]));
+        camera.cycle_target(&world, tags.len() as i32 + 2);
+        assert_eq!(camera.target(), Some(&tags[2]));
+    }
+
+    #[test]
+    fn eye_stays_at_distance_from_the_target() {
+        let world = World::new();
+        let mut camera = OrbitCamera::new();
+        let forward = DVec3::new(1.0, 2.0, -0.5).normalize();
+        assert_eq!(camera.eye(&world, forward), None);
+
+        camera.cycle_target(&world, 1);
+        let target = world.body(camera.target().unwrap()).position();
+        camera.zoom(&world, 3.0);
+        let eye = camera.eye(&world, forward).unwrap();
+        assert_close(eye.distance(target), camera.distance());
+        assert!((target - eye).normalize().abs_diff_eq(forward, 1e-9));
+
+        // While moving to the next target, the eye follows the moving focus.
+        camera.cycle_target(&world, 1);
+        let focus = camera.focus(&world).unwrap();
+        let eye = camera.eye(&world, forward).unwrap();
+        assert_close(eye.distance(focus), camera.distance());
+    }
+}
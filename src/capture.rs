@@ -0,0 +1,106 @@
+//! Saving rendered frames as PNG files: screenshots on demand, and numbered
+//! frame sequences at a fixed step of simulated time, for making videos.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+use crate::{texture::Image, time::SimDuration};
+
+/// Directory that screenshots are saved in, relative to the working
+/// directory.
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+/// Saves a screenshot in [`SCREENSHOT_DIR`] under the first unused name,
+/// e.g. `screenshot-0001.png`, and returns its path.
+pub fn save_screenshot(image: &Image) -> anyhow::Result<PathBuf> {
+    save_screenshot_in(Path::new(SCREENSHOT_DIR), image)
+}
+
+fn save_screenshot_in(dir: &Path, image: &Image) -> anyhow::Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let path = (1..)
+        .map(|number| dir.join(format!("screenshot-{:04}.png", number)))
+        .find(|path| !path.exists())
+        .unwrap();
+    image.save_png(&path)?;
+    Ok(path)
+}
+
+/// A sequence of frames being saved to a directory as `frame-000000.png`,
+/// `frame-000001.png` and so on.
+pub struct FrameExport {
+    dir: PathBuf,
+    /// Simulated time between frames.
+    pub step: SimDuration,
+    next_frame: u32,
+}
+
+impl FrameExport {
+    pub fn new(dir: impl Into<PathBuf>, step: SimDuration) -> anyhow::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        Ok(Self {
+            dir,
+            step,
+            next_frame: 0,
+        })
+    }
+
+    /// Saves the next frame of the sequence.
+    pub fn save(&mut self, image: &Image) -> anyhow::Result<()> {
+        let path = self.dir.join(format!("frame-{:06}.png", self.next_frame));
+        image.save_png(path)?;
+        self.next_frame += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn screenshots_take_the_first_unused_name() {
+        let dir = temp_dir("screenshot-test");
+        let image = Image::solid([1, 2, 3, 255]);
+        let first = save_screenshot_in(&dir, &image).unwrap();
+        let second = save_screenshot_in(&dir, &image).unwrap();
+        fs::remove_file(&first).unwrap();
+        let third = save_screenshot_in(&dir, &image).unwrap();
+        let saved = Image::load_png(&third).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(first, dir.join("screenshot-0001.png"));
+        assert_eq!(second, dir.join("screenshot-0002.png"));
+        assert_eq!(third, first);
+        assert_eq!(saved, Some(image));
+    }
+
+    #[test]
+    fn frames_are_numbered_in_order() {
+        let dir = temp_dir("frame-export-test").join("frames");
+        let mut export = FrameExport::new(&dir, SimDuration::from_secs_f64(1.0)).unwrap();
+        let frames = [
+            Image::solid([255, 0, 0, 255]),
+            Image::solid([0, 255, 0, 255]),
+        ];
+        for frame in &frames {
+            export.save(frame).unwrap();
+        }
+        let saved = [
+            Image::load_png(dir.join("frame-000000.png")).unwrap(),
+            Image::load_png(dir.join("frame-000001.png")).unwrap(),
+        ];
+        fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+
+        assert_eq!(saved, frames.map(Some));
+    }
+}
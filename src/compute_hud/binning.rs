@@ -0,0 +1,447 @@
+//! Assigns HUD primitives to the screen-space tiles that they might cover, so
+//! that each pixel only has to test the primitives in its own tile.
+
+use std::{f32::consts::TAU, ops::Range};
+
+use bytemuck::{Pod, Zeroable};
+use glam::{UVec2, Vec2, Vec3, Vec4};
+
+use super::{Conic, Ellipse, Line, Point};
+use crate::hud::View;
+
+/// Width and height of a tile, in pixels. Must match `TILE_SIZE` in the
+/// shader.
+pub const TILE_SIZE: u32 = 16;
+
+/// Tile entries store the kind of primitive in their top bits, and its index
+/// in the rest.
+const KIND_SHIFT: u32 = 30;
+
+/// Number of points sampled along curves to estimate their bounds.
+const CURVE_SAMPLES: usize = 64;
+
+/// Extra space added around sampled curves, as a fraction of their size, to
+/// account for parts that bulge out between samples.
+const CURVE_MARGIN: f32 = 0.02;
+
+/// Smallest `w` coordinate that is considered to be in front of the eye.
+const MIN_W: f32 = 1.0e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum Kind {
+    Line = 0,
+    Ellipse = 1,
+    Conic = 2,
+    Point = 3,
+}
+
+impl Kind {
+    pub fn entry(self, index: usize) -> u32 {
+        ((self as u32) << KIND_SHIFT) | index as u32
+    }
+}
+
+/// Range of the entries list that belongs to a single tile.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
+#[repr(C)]
+pub struct TileRange {
+    pub offset: u32,
+    pub count: u32,
+}
+
+/// Rectangle in pixel coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Bounds {
+    /// The whole screen.
+    pub fn screen(view: &View) -> Self {
+        Self {
+            min: Vec2::ZERO,
+            max: view.dims.as_vec2(),
+        }
+    }
+
+    /// Smallest rectangle containing all of the points.
+    pub fn enclosing(points: impl IntoIterator<Item = Vec2>) -> Option<Self> {
+        points.into_iter().fold(None, |bounds, point| {
+            Some(match bounds {
+                Some(Self { min, max }) => Self {
+                    min: min.min(point),
+                    max: max.max(point),
+                },
+                None => Self {
+                    min: point,
+                    max: point,
+                },
+            })
+        })
+    }
+
+    pub fn expand(self, margin: f32) -> Self {
+        Self {
+            min: self.min - Vec2::splat(margin),
+            max: self.max + Vec2::splat(margin),
+        }
+    }
+
+    /// Range of tiles covered by these bounds, clipped to the screen.
+    ///
+    /// Returns the first and last tile (inclusive), or `None` if the bounds
+    /// are entirely offscreen.
+    pub fn tiles(&self, view: &View) -> Option<(UVec2, UVec2)> {
+        let screen = Self::screen(view);
+        let min = self.min.max(screen.min);
+        let max = self.max.min(screen.max - Vec2::ONE);
+        if min.x > max.x || min.y > max.y || min.is_nan() || max.is_nan() {
+            return None;
+        }
+        Some((min.as_uvec2() / TILE_SIZE, max.as_uvec2() / TILE_SIZE))
+    }
+}
+
+pub fn point_bounds(view: &View, point: &Point) -> Option<Bounds> {
+    let center = view.to_pixel(point.position.into())?;
+    Some(
+        Bounds {
+            min: center,
+            max: center,
+        }
+        .expand(point.size + 1.0),
+    )
+}
+
+pub fn line_bounds(view: &View, line: &Line) -> Option<Bounds> {
+    let mut start = view.clip(line.start.into());
+    let mut end = view.clip(line.end.into());
+
+    // Clip to the part that is in front of the eye.
+    if start.w < MIN_W && end.w < MIN_W {
+        return None;
+    }
+    if start.w < MIN_W {
+        start = start.lerp(end, (MIN_W - start.w) / (end.w - start.w));
+    } else if end.w < MIN_W {
+        end = end.lerp(start, (MIN_W - end.w) / (start.w - end.w));
+    }
+
+    Some(
+        Bounds::enclosing([view.clip_to_pixel(start), view.clip_to_pixel(end)])?
+            .expand(line.size + 1.0),
+    )
+}
+
+/// Bounds of the curve traced as its parameter goes over `range`, estimated
+/// by sampling points along it.
+fn curve_bounds(
+    view: &View,
+    size: f32,
+    range: Range<f32>,
+    curve: impl Fn(f32) -> Vec3,
+) -> Option<Bounds> {
+    let mut samples = Vec::with_capacity(CURVE_SAMPLES + 1);
+    for i in 0..=CURVE_SAMPLES {
+        let t = range.start + (range.end - range.start) * i as f32 / CURVE_SAMPLES as f32;
+        let clip: Vec4 = view.clip(curve(t));
+        if clip.w < MIN_W {
+            // Part of the curve is behind the eye, so it may wrap around to
+            // anywhere on the screen.
+            return Some(Bounds::screen(view));
+        }
+        samples.push(view.clip_to_pixel(clip));
+    }
+    let bounds = Bounds::enclosing(samples)?;
+    let extent = (bounds.max - bounds.min).max_element();
+    Some(bounds.expand(size + 1.0 + CURVE_MARGIN * extent))
+}
+
+pub fn ellipse_bounds(view: &View, ellipse: &Ellipse) -> Option<Bounds> {
+    let center = Vec3::from(ellipse.center);
+    let u = Vec3::from(ellipse.axis_1);
+    let v = Vec3::from(ellipse.axis_2);
+    curve_bounds(view, ellipse.size, 0.0..TAU, |theta| {
+        center + u * theta.cos() + v * theta.sin()
+    })
+}
+
+pub fn conic_bounds(view: &View, conic: &Conic) -> Option<Bounds> {
+    let focus = Vec3::from(conic.focus);
+    let u = Vec3::from(conic.e_vec).normalize();
+    let v = Vec3::from(conic.p_vec).normalize();
+    let angles = conic.start_angle..conic.end_angle;
+    curve_bounds(view, conic.size, angles, |theta| {
+        conic.p / (1.0 + conic.e * theta.cos()) * (u * theta.cos() + v * theta.sin()) + focus
+    })
+}
+
+/// Number of tiles along each axis needed to cover the view.
+pub fn tile_counts(view: &View) -> UVec2 {
+    (view.dims + UVec2::splat(TILE_SIZE - 1)) / TILE_SIZE
+}
+
+/// Builds the per-tile primitive lists.
+///
+/// `ranges` receives one range per tile in row-major order, each pointing
+/// into `entries`. Within each tile, entries are sorted by [`Kind`] and then
+/// by index, which is the order they are drawn in.
+pub fn bin(
+    view: &View,
+    lines: &[Line],
+    ellipses: &[Ellipse],
+    conics: &[Conic],
+    points: &[Point],
+    ranges: &mut Vec<TileRange>,
+    entries: &mut Vec<u32>,
+) {
+    let counts = tile_counts(view);
+
+    let mut binned = Vec::new();
+    let mut add = |kind: Kind, index, bounds: Option<Bounds>| {
+        if let Some(tiles) = bounds.and_then(|bounds| bounds.tiles(view)) {
+            binned.push((kind.entry(index), tiles));
+        }
+    };
+    for (i, line) in lines.iter().enumerate() {
+        add(Kind::Line, i, line_bounds(view, line));
+    }
+    for (i, ellipse) in ellipses.iter().enumerate() {
+        add(Kind::Ellipse, i, ellipse_bounds(view, ellipse));
+    }
+    for (i, conic) in conics.iter().enumerate() {
+        add(Kind::Conic, i, conic_bounds(view, conic));
+    }
+    for (i, point) in points.iter().enumerate() {
+        add(Kind::Point, i, point_bounds(view, point));
+    }
+
+    let tile_index = |x: u32, y: u32| (y * counts.x + x) as usize;
+
+    // Count the entries in each tile, then allocate space for them.
+    ranges.clear();
+    ranges.resize((counts.x * counts.y) as usize, TileRange::default());
+    for &(_, (min, max)) in &binned {
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                ranges[tile_index(x, y)].count += 1;
+            }
+        }
+    }
+    let mut offset = 0;
+    for range in ranges.iter_mut() {
+        range.offset = offset;
+        offset += range.count;
+        range.count = 0;
+    }
+
+    // Fill in the entries, preserving their order within each tile.
+    entries.clear();
+    entries.resize(offset as usize, 0);
+    for &(entry, (min, max)) in &binned {
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let range = &mut ranges[tile_index(x, y)];
+                entries[(range.offset + range.count) as usize] = entry;
+                range.count += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+    use glam::Mat4;
+
+    use super::*;
+
+    /// A 200×200 view from the origin along +Y, with a 90° field of view,
+    /// so that a unit offset at distance 10 is 10 pixels on screen.
+    fn view() -> View {
+        let fov = TAU / 4.0;
+        let projection = Mat4::perspective_rh(fov, 1.0, 0.1, 1000.0);
+        let camera = Mat4::look_at_rh(Vec3::ZERO, Vec3::Y, Vec3::Z);
+        View {
+            view_proj: projection * camera,
+            eye: Vec3::ZERO,
+            forward: Vec3::Y,
+            up: Vec3::Z,
+            x_fov: fov,
+            y_fov: fov,
+            dims: UVec2::splat(200),
+        }
+    }
+
+    fn point(position: Vec3) -> Point {
+        Point {
+            position: position.into(),
+            size: 1.0,
+            ..Zeroable::zeroed()
+        }
+    }
+
+    fn line(start: Vec3, end: Vec3) -> Line {
+        Line {
+            start: start.into(),
+            end: end.into(),
+            size: 1.0,
+            ..Zeroable::zeroed()
+        }
+    }
+
+    fn ellipse(center: Vec3, radius: f32) -> Ellipse {
+        Ellipse {
+            center: center.into(),
+            axis_1: [radius, 0.0, 0.0],
+            axis_2: [0.0, 0.0, radius],
+            size: 1.0,
+            ..Zeroable::zeroed()
+        }
+    }
+
+    /// Bins the primitives and returns the entries of each tile.
+    fn tiles(view: &View, lines: &[Line], ellipses: &[Ellipse], points: &[Point]) -> Vec<Vec<u32>> {
+        let (mut ranges, mut entries) = (Vec::new(), Vec::new());
+        bin(
+            view,
+            lines,
+            ellipses,
+            &[],
+            points,
+            &mut ranges,
+            &mut entries,
+        );
+        ranges
+            .iter()
+            .map(|range| {
+                entries[range.offset as usize..(range.offset + range.count) as usize].to_vec()
+            })
+            .collect()
+    }
+
+    fn tile(view: &View, x: u32, y: u32) -> usize {
+        (y * tile_counts(view).x + x) as usize
+    }
+
+    #[test]
+    fn partial_tiles_are_counted() {
+        assert_eq!(tile_counts(&view()), UVec2::splat(13));
+    }
+
+    #[test]
+    fn bounds_are_clipped_to_the_screen() {
+        let view = view();
+        let inside = Bounds {
+            min: Vec2::new(20.0, 40.0),
+            max: Vec2::new(40.0, 47.0),
+        };
+        assert_eq!(
+            inside.tiles(&view),
+            Some((UVec2::new(1, 2), UVec2::new(2, 2)))
+        );
+        let overhanging = Bounds {
+            min: Vec2::new(-50.0, 190.0),
+            max: Vec2::new(10.0, 500.0),
+        };
+        assert_eq!(
+            overhanging.tiles(&view),
+            Some((UVec2::new(0, 11), UVec2::new(0, 12)))
+        );
+        let offscreen = Bounds {
+            min: Vec2::new(-50.0, 20.0),
+            max: Vec2::new(-10.0, 30.0),
+        };
+        assert_eq!(offscreen.tiles(&view), None);
+    }
+
+    #[test]
+    fn offscreen_primitives_are_culled() {
+        let view = view();
+        let tiles = tiles(
+            &view,
+            &[
+                // Far off to the left.
+                line(Vec3::new(-50.0, 10.0, 0.0), Vec3::new(-40.0, 10.0, 5.0)),
+                // Behind the eye.
+                line(Vec3::new(-1.0, -10.0, 0.0), Vec3::new(1.0, -10.0, 0.0)),
+            ],
+            &[ellipse(Vec3::new(0.0, 10.0, 50.0), 2.0)],
+            &[point(Vec3::new(0.0, -10.0, 0.0))],
+        );
+        assert!(tiles.iter().all(|entries| entries.is_empty()));
+    }
+
+    #[test]
+    fn lines_are_clipped_at_the_eye() {
+        // From behind the eye to 10 pixels right of the center.
+        let view = view();
+        let crossing = line(Vec3::new(1.0, -10.0, 0.0), Vec3::new(1.0, 10.0, 0.0));
+        let bounds = line_bounds(&view, &crossing).unwrap();
+        assert!(bounds.min.is_finite() && bounds.max.is_finite());
+        let (min, max) = bounds.tiles(&view).unwrap();
+        assert!(min.x <= 110 / TILE_SIZE && 110 / TILE_SIZE <= max.x);
+        assert!(max.cmplt(tile_counts(&view)).all());
+    }
+
+    #[test]
+    fn curves_behind_the_eye_may_cover_the_screen() {
+        let view = view();
+        let around_eye = ellipse(Vec3::ZERO, 5.0);
+        assert_eq!(
+            ellipse_bounds(&view, &around_eye),
+            Some(Bounds::screen(&view))
+        );
+    }
+
+    #[test]
+    fn shapes_are_added_to_every_tile_they_cover() {
+        // A circle 20 pixels in radius around the center, at (100, 100).
+        let view = view();
+        let tiles = tiles(&view, &[], &[ellipse(Vec3::new(0.0, 10.0, 0.0), 2.0)], &[]);
+        let entry = Kind::Ellipse.entry(0);
+        for y in 0..13 {
+            for x in 0..13 {
+                let expected = if (4..=7).contains(&x) && (4..=7).contains(&y) {
+                    vec![entry]
+                } else {
+                    vec![]
+                };
+                assert_eq!(tiles[tile(&view, x, y)], expected, "tile ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn tiles_keep_the_draw_order() {
+        let view = view();
+        let center = Vec3::new(0.0, 10.0, 0.0);
+        let tiles = tiles(
+            &view,
+            &[
+                line(Vec3::new(-1.0, 10.0, 0.0), Vec3::new(1.0, 10.0, 0.0)),
+                // Only covers the right half, so it is missing from the
+                // tile to the left, along with the points.
+                line(center, Vec3::new(5.0, 10.0, 0.0)),
+            ],
+            &[ellipse(center, 0.5)],
+            &[point(center), point(Vec3::new(0.5, 10.0, 0.0))],
+        );
+        assert_eq!(
+            tiles[tile(&view, 6, 6)],
+            [
+                Kind::Line.entry(0),
+                Kind::Line.entry(1),
+                Kind::Ellipse.entry(0),
+                Kind::Point.entry(0),
+                Kind::Point.entry(1),
+            ]
+        );
+        assert_eq!(
+            tiles[tile(&view, 5, 6)],
+            [Kind::Line.entry(0), Kind::Ellipse.entry(0)]
+        );
+    }
+}
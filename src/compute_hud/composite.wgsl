@@ -0,0 +1,20 @@
+// Blends the premultiplied overlay produced by `raster_main` onto the frame.
+
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]] vec4<f32> {
+    // A single triangle that covers the whole screen.
+    var CLIP_POSITION = array<vec4<f32>, 3>(
+        vec4<f32>(-1.0, -1.0, 0.0, 1.0),
+        vec4<f32>(3.0, -1.0, 0.0, 1.0),
+        vec4<f32>(-1.0, 3.0, 0.0, 1.0),
+    );
+    return CLIP_POSITION[vertex_index];
+}
+
+[[group(0), binding(0)]]
+var overlay: texture_2d<f32>;
+
+[[stage(fragment)]]
+fn fs_main([[builtin(position)]] position: vec4<f32>) -> [[location(0)]] vec4<f32> {
+    return textureLoad(overlay, vec2<i32>(position.xy), 0);
+}
@@ -0,0 +1,379 @@
+struct Viewport {
+    view_proj: mat4x4<f32>;
+    eye: vec3<f32>;
+    forward_xfov: vec4<f32>;
+    up_yfov: vec4<f32>;
+};
+
+struct Point {
+    position_size: vec4<f32>;
+    color: vec4<f32>;
+};
+
+struct Line {
+    start_size: vec4<f32>;
+    end_dashes: vec4<f32>;
+    color: vec4<f32>;
+};
+
+struct Ellipse {
+    center_size: vec4<f32>;
+    axis_1_dashes: vec4<f32>;
+    axis_2: vec3<f32>;
+    color: vec4<f32>;
+};
+
+struct Conic {
+    focus_size: vec4<f32>;
+    e_vec: vec4<f32>;
+    p_vec: vec4<f32>;
+    color: vec4<f32>;
+    // x: dashes, y: start angle, z: end angle
+    style: vec4<f32>;
+};
+
+struct TileRange {
+    offset: u32;
+    count: u32;
+};
+
+struct Points {
+    points: array<Point>;
+};
+
+struct Lines {
+    lines: array<Line>;
+};
+
+struct Ellipses {
+    ellipses: array<Ellipse>;
+};
+
+struct Conics {
+    conics: array<Conic>;
+};
+
+struct Tiles {
+    tiles: array<TileRange>;
+};
+
+struct Entries {
+    entries: array<u32>;
+};
+
+// Must match binning.rs
+let TILE_SIZE: u32 = 16u;
+let KIND_SHIFT: u32 = 30u;
+let INDEX_MASK: u32 = 0x3fffffffu;
+let KIND_LINE: u32 = 0u;
+let KIND_ELLIPSE: u32 = 1u;
+let KIND_CONIC: u32 = 2u;
+let KIND_POINT: u32 = 3u;
+
+let TAU: f32 = 6.283185307179586;
+
+// Opacity of primitives that are behind something in the scene.
+let OCCLUDED_ALPHA: f32 = 0.25;
+
+[[group(0), binding(0)]]
+var<uniform> viewport: Viewport;
+
+[[group(1), binding(0)]]
+var output: texture_storage_2d<rgba8unorm, write>;
+
+[[group(1), binding(1)]]
+var<storage, read> points: Points;
+
+[[group(1), binding(2)]]
+var<storage, read> lines: Lines;
+
+[[group(1), binding(3)]]
+var<storage, read> ellipses: Ellipses;
+
+[[group(1), binding(4)]]
+var<storage, read> conics: Conics;
+
+[[group(1), binding(5)]]
+var<storage, read> tiles: Tiles;
+
+[[group(1), binding(6)]]
+var<storage, read> entries: Entries;
+
+[[group(1), binding(7)]]
+var depth_texture: texture_depth_2d;
+
+fn eye_ray(id: vec3<u32>) -> vec3<f32> {
+    let forward = normalize(viewport.forward_xfov.xyz);
+    let xfov = viewport.forward_xfov.w;
+
+    let up = normalize(viewport.up_yfov.xyz);
+    let yfov = viewport.up_yfov.w;
+
+    let right = normalize(cross(forward, up));
+    let dims = textureDimensions(output);
+    let dx = tan(xfov * 0.5) * 2.0 / f32(dims.x);
+    let dy = tan(yfov * 0.5) * 2.0 / f32(dims.y);
+
+    // Through the center of the pixel, matching the projection.
+    let eye_ray = normalize(
+        forward
+        + dx * (f32(id.x) + 0.5 - f32(dims.x) * 0.5) * right
+        - dy * (f32(id.y) + 0.5 - f32(dims.y) * 0.5) * up
+    );
+    return eye_ray;
+}
+
+// Antialiased coverage of a pixel whose center is `distance` pixels away
+// from a stroke with half-width `size`.
+fn stroke_coverage(distance: f32, size: f32) -> f32 {
+    return clamp(size + 0.5 - distance, 0.0, 1.0);
+}
+
+// Whether the point `t` along a primitive divided into `dashes` dashes falls
+// on a dash rather than a gap. Zero dashes is a solid line.
+fn dash_visible(t: f32, dashes: f32) -> bool {
+    return dashes <= 0.0 || fract(t * dashes) < 0.5;
+}
+
+// Fades out parts of primitives that are behind the scene.
+fn depth_fade(pixel: vec2<i32>, position: vec3<f32>) -> f32 {
+    let clip = viewport.view_proj * vec4<f32>(position, 1.0);
+    let depth = clip.z / clip.w;
+    let scene_depth = textureLoad(depth_texture, pixel, 0);
+    if (depth > scene_depth) {
+        return OCCLUDED_ALPHA;
+    }
+    return 1.0;
+}
+
+// Distance between two points on the screen, in pixels.
+fn screen_distance(a: vec3<f32>, b: vec3<f32>) -> f32 {
+    let dims = textureDimensions(output);
+    let p_a = viewport.view_proj * vec4<f32>(a, 1.0);
+    let p_b = viewport.view_proj * vec4<f32>(b, 1.0);
+    let n_a = p_a.xy / p_a.w;
+    let n_b = p_b.xy / p_b.w;
+    return length((n_a - n_b) * vec2<f32>(dims) * 0.5);
+}
+
+fn point_coverage(pixel: vec2<i32>, eye_ray: vec3<f32>, index: u32) -> f32 {
+    let eye = viewport.eye;
+
+    let point = points.points[index].position_size.xyz;
+    let point_size = points.points[index].position_size.w;
+
+    let t0 = (dot(eye_ray, point) - dot(eye_ray, eye)) / dot(eye_ray, eye_ray);
+    if (t0 < 0.0) {
+        return 0.0;
+    }
+
+    let eye_approach = eye + eye_ray * t0;
+    let distance = screen_distance(eye_approach, point);
+    return stroke_coverage(distance, point_size) * depth_fade(pixel, point);
+}
+
+fn line_coverage(pixel: vec2<i32>, eye_ray: vec3<f32>, index: u32) -> f32 {
+    let eye = viewport.eye;
+
+    let line_start = lines.lines[index].start_size.xyz;
+    let line_size = lines.lines[index].start_size.w;
+    let line_end = lines.lines[index].end_dashes.xyz;
+    let line_dashes = lines.lines[index].end_dashes.w;
+    let line_ray = line_end - line_start;
+
+    let a = dot(eye_ray, eye_ray);
+    let b = dot(eye_ray, line_ray);
+    let c = dot(eye_ray, line_start - eye);
+    let d = dot(eye_ray, line_ray);
+    let e = dot(line_ray, line_ray);
+    let f = dot(line_ray, line_start - eye);
+
+    let t0 = (c * e - f * b) / (a * e - d * b);
+    let u0 = (c * d - f * a) / (a * e - d * b);
+    let u0 = clamp(u0, 0.0, 1.0);
+    if (!(t0 >= 0.0) || !dash_visible(u0, line_dashes)) {
+        return 0.0;
+    }
+
+    let eye_approach = eye + eye_ray * t0;
+    let line_approach = line_start + line_ray * u0;
+    let distance = screen_distance(eye_approach, line_approach);
+    return stroke_coverage(distance, line_size) * depth_fade(pixel, line_approach);
+}
+
+fn ellipse_coverage(pixel: vec2<i32>, eye_ray: vec3<f32>, index: u32) -> f32 {
+    let eye = viewport.eye;
+
+    let el_center = ellipses.ellipses[index].center_size.xyz;
+    let stroke_width = ellipses.ellipses[index].center_size.w;
+    let el_axis_1 = ellipses.ellipses[index].axis_1_dashes.xyz;
+    let el_dashes = ellipses.ellipses[index].axis_1_dashes.w;
+    let el_axis_2 = ellipses.ellipses[index].axis_2;
+
+    let a = eye_ray;
+    let u = el_axis_1;
+    let v = el_axis_2;
+    let w = el_center - eye;
+    let n = cross(u, v);
+
+    // Cull if ellipse is behind eye
+    if (dot(w, a) < 0.0) {
+        return 0.0;
+    }
+
+    // Find intersection of ray with elliptical plane;
+    // cull if it is too far away from the ellipse curve (>1.25r or <0.75r).
+    // This assumes that the stroke width is << 0.25r.
+    let intersect = dot(w, n) / dot(a, n) * a - w;
+
+    // These projections are divided by the lengths of u and v an extra time.
+    // The resulting pair of projections creates a coordinate pair that is
+    // related to the unit circle - if r is greater than 1, it is outside the
+    // original ellipse.
+    let u_proj = dot(intersect, u) / dot(u, u);
+    let v_proj = dot(intersect, v) / dot(v, v);
+    let r = sqrt(u_proj * u_proj + v_proj * v_proj);
+    if (r > 1.25 || r < 0.75) {
+        return 0.0;
+    }
+
+    // Otherwise continue with numerical approximation.
+
+    // Initial guess, using the angle between u and the intersection point:
+    let su = dot(normalize(intersect), normalize(u));
+    let sv = dot(normalize(intersect), normalize(v));
+    var theta = atan2(sv, su);
+
+    for (var i = 0; i < 2; i = i + 1) {
+        let e = u * cos(theta) + v * sin(theta) + w;
+        let de = v * cos(theta) - u * sin(theta);
+        let dde = -(u * cos(theta) + v * sin(theta));
+
+        let g = dot(a, a) * dot(e, de) - dot(a, e) * dot(a, de);
+        let dg = dot(a, a) * (dot(e, dde) + dot(de, de))
+            - dot(a, de) * dot(a, de) - dot(a, e) * dot(a, dde);
+        
+        let delta = g / dg;
+        theta = theta - delta;
+    }
+
+    let el_approach = el_axis_1 * cos(theta) + el_axis_2 * sin(theta) + el_center;
+    let t = dot(el_approach - eye, a) / dot(a, a);
+    if (!(t >= 0.0) || !dash_visible(theta / TAU, el_dashes)) {
+        return 0.0;
+    }
+
+    let eye_approach = a * t + eye;
+    let distance = screen_distance(eye_approach, el_approach);
+    return stroke_coverage(distance, stroke_width) * depth_fade(pixel, el_approach);
+}
+
+fn conic_coverage(pixel: vec2<i32>, eye_ray: vec3<f32>, index: u32) -> f32 {
+    let eye = viewport.eye;
+
+    let con_focus = conics.conics[index].focus_size.xyz;
+    let stroke_width = conics.conics[index].focus_size.w;
+    let con_e_vec = conics.conics[index].e_vec;
+    let con_p_vec = conics.conics[index].p_vec;
+    let con_dashes = conics.conics[index].style.x;
+    let con_start = conics.conics[index].style.y;
+    let con_end = conics.conics[index].style.z;
+
+    let e = con_e_vec.w;
+    var u = normalize(con_e_vec.xyz);
+
+    let p = con_p_vec.w;
+    let v = normalize(con_p_vec.xyz);
+
+    let a = eye_ray;
+    let w = con_focus - eye;
+
+    let n = cross(u, v);
+
+    // Find intersection of ray with orbital plane:
+    let intersect = dot(w, n) / dot(a, n) * a - w;
+
+    // Initial guess, using the angle between u and the intersection point:
+    let su = dot(normalize(intersect), u);
+    let sv = dot(normalize(intersect), v);
+    var theta = atan2(sv, su);
+
+
+    for (var i = 0; i < 10; i = i + 1) {
+        let den = 1.0 + e * cos(theta);
+
+        let r = p / den * (u * cos(theta) + v * sin(theta)) + w;
+        let dr = p / (den * den) * (u * -sin(theta) + v * (e + cos(theta)));
+        let ddr = p / (den * den * den) * (
+            u * -(e + e * sin(theta) * sin(theta) + cos(theta))
+            + v * sin(theta) * (2.0 * e * e + e * cos(theta) - 1.0)
+        );
+
+        let g = dot(a, a) * dot(r, dr) - dot(a, r) * dot(a, dr);
+        let dg = (
+            dot(a, a) * (dot(dr, dr) + dot(r, ddr))
+            - dot(a, dr) * dot(a, dr)
+            - dot(a, r) * dot(a, ddr)
+        );
+
+        let delta = g / dg;
+        theta = theta - delta;
+    }
+
+    // Wrap into [-pi, pi), then limit to the arc being drawn.
+    theta = theta - TAU * floor(theta / TAU + 0.5);
+    theta = clamp(theta, con_start, con_end);
+
+    let con_approach = p / (1.0 + e * cos(theta)) * (u * cos(theta) + v * sin(theta)) + con_focus;
+    let t = dot(con_approach - eye, a) / dot(a, a);
+    if (!(t >= 0.0) || !dash_visible(theta / TAU, con_dashes)) {
+        return 0.0;
+    }
+
+    let eye_approach = a * t + eye;
+    let distance = screen_distance(eye_approach, con_approach);
+    return stroke_coverage(distance, stroke_width) * depth_fade(pixel, con_approach);
+}
+
+// Composites every primitive binned into the invocation's tile, in order,
+// producing a premultiplied color to be blended over the scene.
+[[stage(compute), workgroup_size(16, 16)]]
+fn raster_main([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    let dims = textureDimensions(output);
+    if (id.x >= u32(dims.x) || id.y >= u32(dims.y)) {
+        return;
+    }
+
+    let pixel = vec2<i32>(id.xy);
+    let tiles_x = (u32(dims.x) + TILE_SIZE - 1u) / TILE_SIZE;
+    let tile = tiles.tiles[id.y / TILE_SIZE * tiles_x + id.x / TILE_SIZE];
+    let eye_ray = eye_ray(id);
+
+    var result = vec4<f32>(0.0);
+    for (var i = 0u; i < tile.count; i = i + 1u) {
+        let entry = entries.entries[tile.offset + i];
+        let kind = entry >> KIND_SHIFT;
+        let index = entry & INDEX_MASK;
+
+        var coverage = 0.0;
+        var color = vec4<f32>(0.0);
+        if (kind == KIND_LINE) {
+            coverage = line_coverage(pixel, eye_ray, index);
+            color = lines.lines[index].color;
+        } else if (kind == KIND_ELLIPSE) {
+            coverage = ellipse_coverage(pixel, eye_ray, index);
+            color = ellipses.ellipses[index].color;
+        } else if (kind == KIND_CONIC) {
+            coverage = conic_coverage(pixel, eye_ray, index);
+            color = conics.conics[index].color;
+        } else if (kind == KIND_POINT) {
+            coverage = point_coverage(pixel, eye_ray, index);
+            color = points.points[index].color;
+        }
+
+        let alpha = color.a * coverage;
+        result = vec4<f32>(color.rgb * alpha, alpha) + result * (1.0 - alpha);
+    }
+
+    textureStore(output, pixel, result);
+}
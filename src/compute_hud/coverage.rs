@@ -0,0 +1,442 @@
+//! Per-pixel coverage of the primitives drawn by `compute_hud.wgsl`.
+//!
+//! [`dash_visible`] decides where dashes fall for both HUD backends. The
+//! other functions mirror the shader on the CPU, leaving out the depth fade
+//! since it depends on the rendered scene, and are only built for tests.
+
+#[cfg(test)]
+use std::f32::consts::TAU;
+
+#[cfg(test)]
+use glam::{UVec2, Vec3};
+
+#[cfg(test)]
+use super::{Conic, Ellipse, Line, Point};
+#[cfg(test)]
+use crate::hud::View;
+
+/// Antialiased coverage of a pixel whose center is `distance` pixels away
+/// from a stroke with half-width `size`.
+#[cfg(test)]
+pub fn stroke_coverage(distance: f32, size: f32) -> f32 {
+    (size + 0.5 - distance).clamp(0.0, 1.0)
+}
+
+/// Whether the point `t` along a primitive divided into `dashes` dashes falls
+/// on a dash rather than a gap. Zero dashes is a solid line.
+pub fn dash_visible(t: f32, dashes: f32) -> bool {
+    dashes <= 0.0 || (t * dashes).rem_euclid(1.0) < 0.5
+}
+
+/// Screen distance from the pixel to the point, or `None` if the point is
+/// behind the eye.
+#[cfg(test)]
+pub(super) fn point_distance(view: &View, pixel: UVec2, point: &Point) -> Option<f32> {
+    let eye = view.eye;
+    let eye_ray = view.eye_ray(pixel);
+    let position = Vec3::from(point.position);
+
+    let t0 = (eye_ray.dot(position) - eye_ray.dot(eye)) / eye_ray.dot(eye_ray);
+    if t0 < 0.0 {
+        return None;
+    }
+    let eye_approach = eye + eye_ray * t0;
+    Some(view.screen_distance(eye_approach, position))
+}
+
+/// Screen distance from the pixel to the closest point on the line segment,
+/// or `None` if that point is behind the eye or in a gap between dashes.
+#[cfg(test)]
+pub(super) fn line_distance(view: &View, pixel: UVec2, line: &Line) -> Option<f32> {
+    let eye = view.eye;
+    let eye_ray = view.eye_ray(pixel);
+    let line_start = Vec3::from(line.start);
+    let line_ray = Vec3::from(line.end) - line_start;
+
+    let a = eye_ray.dot(eye_ray);
+    let b = eye_ray.dot(line_ray);
+    let c = eye_ray.dot(line_start - eye);
+    let d = eye_ray.dot(line_ray);
+    let e = line_ray.dot(line_ray);
+    let f = line_ray.dot(line_start - eye);
+
+    let t0 = (c * e - f * b) / (a * e - d * b);
+    let u0 = ((c * d - f * a) / (a * e - d * b)).clamp(0.0, 1.0);
+    if t0.is_nan() || t0 < 0.0 || !dash_visible(u0, line.dashes) {
+        return None;
+    }
+
+    let eye_approach = eye + eye_ray * t0;
+    let line_approach = line_start + line_ray * u0;
+    Some(view.screen_distance(eye_approach, line_approach))
+}
+
+/// Screen distance from the pixel to the closest point on the ellipse, or
+/// `None` if the ellipse is behind the eye, the pixel is too far away from it
+/// to bother, or that point is in a gap between dashes.
+#[cfg(test)]
+pub(super) fn ellipse_distance(view: &View, pixel: UVec2, ellipse: &Ellipse) -> Option<f32> {
+    let eye = view.eye;
+    let a = view.eye_ray(pixel);
+    let center = Vec3::from(ellipse.center);
+    let u = Vec3::from(ellipse.axis_1);
+    let v = Vec3::from(ellipse.axis_2);
+    let w = center - eye;
+    let n = u.cross(v);
+
+    if w.dot(a) < 0.0 {
+        return None;
+    }
+
+    let intersect = w.dot(n) / a.dot(n) * a - w;
+    let u_proj = intersect.dot(u) / u.dot(u);
+    let v_proj = intersect.dot(v) / v.dot(v);
+    let r = (u_proj * u_proj + v_proj * v_proj).sqrt();
+    if !(0.75..=1.25).contains(&r) {
+        return None;
+    }
+
+    let su = intersect.normalize().dot(u.normalize());
+    let sv = intersect.normalize().dot(v.normalize());
+    let mut theta = sv.atan2(su);
+
+    for _ in 0..2 {
+        let (sin, cos) = theta.sin_cos();
+        let e = u * cos + v * sin + w;
+        let de = v * cos - u * sin;
+        let dde = -(u * cos + v * sin);
+
+        let g = a.dot(a) * e.dot(de) - a.dot(e) * a.dot(de);
+        let dg =
+            a.dot(a) * (e.dot(dde) + de.dot(de)) - a.dot(de) * a.dot(de) - a.dot(e) * a.dot(dde);
+
+        theta -= g / dg;
+    }
+
+    let el_approach = u * theta.cos() + v * theta.sin() + center;
+    let t = (el_approach - eye).dot(a) / a.dot(a);
+    if t.is_nan() || t < 0.0 || !dash_visible(theta / TAU, ellipse.dashes) {
+        return None;
+    }
+    let eye_approach = a * t + eye;
+    Some(view.screen_distance(eye_approach, el_approach))
+}
+
+/// Screen distance from the pixel to the closest point on the conic, or
+/// `None` if that point is behind the eye or in a gap between dashes.
+#[cfg(test)]
+pub(super) fn conic_distance(view: &View, pixel: UVec2, conic: &Conic) -> Option<f32> {
+    let eye = view.eye;
+    let a = view.eye_ray(pixel);
+    let focus = Vec3::from(conic.focus);
+    let e = conic.e;
+    let u = Vec3::from(conic.e_vec).normalize();
+    let p = conic.p;
+    let v = Vec3::from(conic.p_vec).normalize();
+    let w = focus - eye;
+    let n = u.cross(v);
+
+    let intersect = w.dot(n) / a.dot(n) * a - w;
+    let su = intersect.normalize().dot(u);
+    let sv = intersect.normalize().dot(v);
+    let mut theta = sv.atan2(su);
+
+    for _ in 0..10 {
+        let (sin, cos) = theta.sin_cos();
+        let den = 1.0 + e * cos;
+
+        let r = p / den * (u * cos + v * sin) + w;
+        let dr = p / (den * den) * (u * -sin + v * (e + cos));
+        let ddr = p / (den * den * den)
+            * (u * -(e + e * sin * sin + cos) + v * sin * (2.0 * e * e + e * cos - 1.0));
+
+        let g = a.dot(a) * r.dot(dr) - a.dot(r) * a.dot(dr);
+        let dg =
+            a.dot(a) * (dr.dot(dr) + r.dot(ddr)) - a.dot(dr) * a.dot(dr) - a.dot(r) * a.dot(ddr);
+
+        theta -= g / dg;
+    }
+
+    theta -= TAU * (theta / TAU + 0.5).floor();
+    theta = theta.clamp(conic.start_angle, conic.end_angle);
+
+    let con_approach = p / (1.0 + e * theta.cos()) * (u * theta.cos() + v * theta.sin()) + focus;
+    let t = (con_approach - eye).dot(a) / a.dot(a);
+    if t.is_nan() || t < 0.0 || !dash_visible(theta / TAU, conic.dashes) {
+        return None;
+    }
+    let eye_approach = a * t + eye;
+    Some(view.screen_distance(eye_approach, con_approach))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+    use glam::Mat4;
+
+    use super::*;
+    use crate::hud::{canvas::Canvas, Stroke};
+
+    /// A 201×201 view from the origin along +Y, with a 90° field of view,
+    /// so that the center of pixel (100, 100) is straight ahead and a unit
+    /// offset at distance 10 is about 10 pixels on screen.
+    fn view() -> View {
+        let fov = TAU / 4.0;
+        let projection = Mat4::perspective_rh(fov, 1.0, 0.1, 1000.0);
+        let camera = Mat4::look_at_rh(Vec3::ZERO, Vec3::Y, Vec3::Z);
+        View {
+            view_proj: projection * camera,
+            eye: Vec3::ZERO,
+            forward: Vec3::Y,
+            up: Vec3::Z,
+            x_fov: fov,
+            y_fov: fov,
+            dims: UVec2::splat(201),
+        }
+    }
+
+    fn assert_close(actual: Option<f32>, expected: f32) {
+        let actual = actual.expect("primitive was culled");
+        assert!(
+            (actual - expected).abs() < 0.5,
+            "{} isn't {}",
+            actual,
+            expected
+        );
+    }
+
+    fn point(position: Vec3) -> Point {
+        Point {
+            position: position.into(),
+            size: 1.0,
+            ..Zeroable::zeroed()
+        }
+    }
+
+    fn line(start: Vec3, end: Vec3, dashes: f32) -> Line {
+        Line {
+            start: start.into(),
+            end: end.into(),
+            size: 1.0,
+            dashes,
+            ..Zeroable::zeroed()
+        }
+    }
+
+    /// A circle of radius 2 facing the eye, 20 pixels across on screen.
+    fn circle() -> Ellipse {
+        Ellipse {
+            center: [0.0, 10.0, 0.0],
+            axis_1: [2.0, 0.0, 0.0],
+            axis_2: [0.0, 0.0, 2.0],
+            size: 1.0,
+            ..Zeroable::zeroed()
+        }
+    }
+
+    #[test]
+    fn coverage_fades_over_one_pixel_at_the_edge() {
+        assert_eq!(stroke_coverage(0.0, 1.0), 1.0);
+        assert_eq!(stroke_coverage(1.0, 1.0), 0.5);
+        assert_eq!(stroke_coverage(1.5, 1.0), 0.0);
+        assert_eq!(stroke_coverage(20.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn distance_to_a_point() {
+        let view = view();
+        let center = point(Vec3::new(0.0, 10.0, 0.0));
+        assert_close(point_distance(&view, UVec2::new(100, 100), &center), 0.0);
+        assert_close(point_distance(&view, UVec2::new(103, 96), &center), 5.0);
+    }
+
+    #[test]
+    fn points_behind_the_eye_are_culled() {
+        let view = view();
+        let behind = point(Vec3::new(0.0, -10.0, 0.0));
+        assert_eq!(point_distance(&view, UVec2::new(100, 100), &behind), None);
+    }
+
+    #[test]
+    fn distance_to_a_line_segment() {
+        // From 10 pixels left of the center to 10 pixels right of it.
+        let view = view();
+        let segment = line(Vec3::new(-1.0, 10.0, 0.0), Vec3::new(1.0, 10.0, 0.0), 0.0);
+        // Beside the middle of the segment.
+        assert_close(line_distance(&view, UVec2::new(105, 100), &segment), 0.0);
+        assert_close(line_distance(&view, UVec2::new(95, 107), &segment), 7.0);
+        // Past its ends, the closest point is the end itself.
+        assert_close(line_distance(&view, UVec2::new(130, 100), &segment), 20.0);
+        assert_close(line_distance(&view, UVec2::new(80, 100), &segment), 10.0);
+    }
+
+    #[test]
+    fn lines_skip_gaps_between_dashes() {
+        // One dash covers the left half of the segment, and a gap the right.
+        let view = view();
+        let segment = line(Vec3::new(-1.0, 10.0, 0.0), Vec3::new(1.0, 10.0, 0.0), 1.0);
+        assert_close(line_distance(&view, UVec2::new(95, 100), &segment), 0.0);
+        assert_eq!(line_distance(&view, UVec2::new(105, 100), &segment), None);
+    }
+
+    #[test]
+    fn lines_behind_the_eye_are_culled() {
+        let view = view();
+        let segment = line(Vec3::new(-1.0, -10.0, 0.0), Vec3::new(1.0, -10.0, 0.0), 0.0);
+        assert_eq!(line_distance(&view, UVec2::new(100, 100), &segment), None);
+    }
+
+    #[test]
+    fn distance_to_an_ellipse() {
+        let view = view();
+        let circle = circle();
+        assert_close(ellipse_distance(&view, UVec2::new(120, 100), &circle), 0.0);
+        assert_close(ellipse_distance(&view, UVec2::new(100, 80), &circle), 0.0);
+        assert_close(ellipse_distance(&view, UVec2::new(123, 100), &circle), 3.0);
+        assert_close(ellipse_distance(&view, UVec2::new(100, 117), &circle), 3.0);
+        // The center is too far from the outline to be considered.
+        assert_eq!(ellipse_distance(&view, UVec2::new(100, 100), &circle), None);
+    }
+
+    #[test]
+    fn ellipses_behind_the_eye_are_culled() {
+        let view = view();
+        let circle = Ellipse {
+            center: [0.0, -10.0, 0.0],
+            ..circle()
+        };
+        assert_eq!(ellipse_distance(&view, UVec2::new(120, 100), &circle), None);
+    }
+
+    #[test]
+    fn circular_conic_matches_the_ellipse() {
+        let view = view();
+        let conic = Conic {
+            focus: [0.0, 10.0, 0.0],
+            e_vec: [1.0, 0.0, 0.0],
+            e: 0.0,
+            p_vec: [0.0, 0.0, 1.0],
+            p: 2.0,
+            size: 1.0,
+            start_angle: -TAU / 2.0,
+            end_angle: TAU / 2.0,
+            ..Zeroable::zeroed()
+        };
+        for pixel in [
+            UVec2::new(120, 100),
+            UVec2::new(123, 100),
+            UVec2::new(100, 117),
+        ] {
+            let expected = ellipse_distance(&view, pixel, &circle()).unwrap();
+            assert_close(conic_distance(&view, pixel, &conic), expected);
+        }
+    }
+
+    /// Checks that the canvas covers the same pixels as the reference, at a
+    /// scale factor of 2, where `draw` adds a primitive in logical pixels and
+    /// `distance` measures the distance to it in physical pixels.
+    fn assert_matches_canvas(
+        size: f32,
+        draw: impl FnOnce(&mut Canvas),
+        distance: impl Fn(&View, UVec2) -> Option<f32>,
+    ) {
+        let view = view();
+        let mut canvas = Canvas::new(view.dims).unwrap();
+        canvas.set_scale_factor(2.0);
+        draw(&mut canvas);
+        canvas.rasterize(&view);
+
+        let size = Stroke::solid(size, [1.0; 4]).scaled(2.0).size;
+        let (mut expected, mut actual) = (0.0, 0.0);
+        for y in 0..view.dims.y {
+            for x in 0..view.dims.x {
+                let pixel = UVec2::new(x, y);
+                let reference = distance(&view, pixel).map_or(0.0, |d| stroke_coverage(d, size));
+                let drawn = canvas.pixmap().pixel(x, y).unwrap().alpha() as f32 / 255.0;
+                assert!(
+                    (drawn - reference).abs() < 0.25,
+                    "({}, {}) is {} but should be {}",
+                    x,
+                    y,
+                    drawn,
+                    reference
+                );
+                expected += reference;
+                actual += drawn;
+            }
+        }
+        assert!(
+            (actual - expected).abs() < 0.03 * expected,
+            "covered {} but should be {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn lines_match_the_canvas() {
+        let (start, end) = (Vec3::new(-5.0, 10.0, 3.0), Vec3::new(4.0, 10.0, -2.0));
+        let segment = Line {
+            size: Stroke::solid(1.5, [1.0; 4]).scaled(2.0).size,
+            ..line(start, end, 0.0)
+        };
+        assert_matches_canvas(
+            1.5,
+            |canvas| canvas.line(start, end, Stroke::solid(1.5, [1.0; 4])),
+            |view, pixel| line_distance(view, pixel, &segment),
+        );
+    }
+
+    #[test]
+    fn ellipses_match_the_canvas() {
+        let circle = circle();
+        assert_matches_canvas(
+            1.0,
+            |canvas| {
+                canvas.ellipse(
+                    circle.center.into(),
+                    circle.axis_1.into(),
+                    circle.axis_2.into(),
+                    Stroke::solid(1.0, [1.0; 4]),
+                )
+            },
+            |view, pixel| ellipse_distance(view, pixel, &circle),
+        );
+    }
+
+    #[test]
+    fn points_match_the_canvas() {
+        let center = point(Vec3::new(1.0, 10.0, -1.0));
+        assert_matches_canvas(
+            3.0,
+            |canvas| canvas.point(center.position.into(), 3.0, [1.0; 4]),
+            |view, pixel| point_distance(view, pixel, &center),
+        );
+    }
+
+    #[test]
+    fn solid_line_is_always_visible() {
+        for t in [-1.0, 0.0, 0.25, 0.5, 0.99, 1.0, 7.3] {
+            assert!(dash_visible(t, 0.0));
+        }
+    }
+
+    #[test]
+    fn dashes_alternate_with_gaps() {
+        // Four dashes: [0, 0.125) is a dash and [0.125, 0.25) a gap.
+        assert!(dash_visible(0.0, 4.0));
+        assert!(dash_visible(0.1, 4.0));
+        assert!(!dash_visible(0.2, 4.0));
+        assert!(dash_visible(0.3, 4.0));
+        assert!(!dash_visible(0.95, 4.0));
+    }
+
+    #[test]
+    fn dashes_wrap_like_the_shader() {
+        // Angles of ellipses and conics can be negative, and the shader uses
+        // a floored remainder rather than a truncated one.
+        assert!(!dash_visible(-0.05, 4.0));
+        assert!(dash_visible(-0.2, 4.0));
+        assert_eq!(dash_visible(1.1, 4.0), dash_visible(0.1, 4.0));
+    }
+}
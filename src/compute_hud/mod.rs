@@ -0,0 +1,581 @@
+mod binning;
+pub mod coverage;
+mod text;
+
+use std::ops::Range;
+
+use bytemuck::{Pod, Zeroable};
+use glam::{UVec2, Vec2, Vec3};
+
+use crate::{
+    hud::{HudRenderer, Stroke, View},
+    viewport::Viewport,
+    GraphicsContext,
+};
+
+use self::{
+    binning::{TileRange, TILE_SIZE},
+    text::TextRenderer,
+};
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct Point {
+    // vec4<f32>
+    position: [f32; 3],
+    size: f32,
+
+    // vec4<f32>
+    color: [f32; 4],
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct Line {
+    // vec4<f32>
+    start: [f32; 3],
+    size: f32,
+
+    // vec4<f32>
+    end: [f32; 3],
+    dashes: f32,
+
+    // vec4<f32>
+    color: [f32; 4],
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct Ellipse {
+    // vec4<f32>
+    center: [f32; 3],
+    size: f32,
+
+    // vec4<f32>
+    axis_1: [f32; 3],
+    dashes: f32,
+
+    // vec3<f32>
+    axis_2: [f32; 3],
+    _axis_2_padding: [u8; 4],
+
+    // vec4<f32>
+    color: [f32; 4],
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct Conic {
+    // vec4<f32>
+    focus: [f32; 3],
+    size: f32,
+
+    // vec4<f32>
+    e_vec: [f32; 3],
+    e: f32,
+
+    // vec4<f32>
+    p_vec: [f32; 3],
+    p: f32,
+
+    // vec4<f32>
+    color: [f32; 4],
+
+    // vec4<f32>
+    dashes: f32,
+    start_angle: f32,
+    end_angle: f32,
+    _padding: [u8; 4],
+}
+
+/// A list of primitives, uploaded to a storage buffer that grows as needed.
+struct PrimitiveBuffer<T> {
+    label: &'static str,
+    items: Vec<T>,
+    buffer: wgpu::Buffer,
+    capacity: usize,
+}
+
+impl<T: Pod> PrimitiveBuffer<T> {
+    const INITIAL_CAPACITY: usize = 16;
+
+    fn new(gfx: &GraphicsContext, label: &'static str) -> Self {
+        Self {
+            label,
+            items: Vec::new(),
+            buffer: Self::create_buffer(gfx, label, Self::INITIAL_CAPACITY),
+            capacity: Self::INITIAL_CAPACITY,
+        }
+    }
+
+    fn create_buffer(gfx: &GraphicsContext, label: &'static str, capacity: usize) -> wgpu::Buffer {
+        gfx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: (capacity * std::mem::size_of::<T>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn push(&mut self, item: T) {
+        self.items.push(item);
+    }
+
+    /// Copies the items into the buffer, reallocating it if they don't fit.
+    fn upload(&mut self, gfx: &GraphicsContext) {
+        if self.items.len() > self.capacity {
+            self.capacity = self.items.len().next_power_of_two();
+            self.buffer = Self::create_buffer(gfx, self.label, self.capacity);
+        }
+        if !self.items.is_empty() {
+            gfx.queue
+                .write_buffer(&self.buffer, 0, bytemuck::cast_slice(&self.items));
+        }
+    }
+
+    fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+/// Draws points, lines and curves over the scene using compute shaders.
+///
+/// Primitives are added each frame through [`HudRenderer`], and are cleared
+/// after being drawn. Their sizes are converted from logical to physical
+/// pixels as they are added.
+///
+/// They are rasterized with antialiasing into an overlay texture, which is
+/// then blended onto the frame. Parts that are hidden behind the scene are
+/// faded out, using its depth buffer.
+pub struct Hud {
+    gfx: GraphicsContext,
+    points: PrimitiveBuffer<Point>,
+    lines: PrimitiveBuffer<Line>,
+    ellipses: PrimitiveBuffer<Ellipse>,
+    conics: PrimitiveBuffer<Conic>,
+    tiles: PrimitiveBuffer<TileRange>,
+    entries: PrimitiveBuffer<u32>,
+    text: TextRenderer,
+    overlay: Overlay,
+    bind_group_layout: wgpu::BindGroupLayout,
+    raster_pipeline: wgpu::ComputePipeline,
+    composite_bind_group_layout: wgpu::BindGroupLayout,
+    composite_pipeline: wgpu::RenderPipeline,
+}
+
+/// Texture that primitives are rasterized into, matching the window size.
+struct Overlay {
+    size: UVec2,
+    view: wgpu::TextureView,
+}
+
+impl Overlay {
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+    /// Size of the window, but at least one pixel, since it is zero while the
+    /// window is minimized.
+    fn size(gfx: &GraphicsContext) -> UVec2 {
+        let size = gfx.size();
+        UVec2::new(size.width, size.height).max(UVec2::ONE)
+    }
+
+    fn new(gfx: &GraphicsContext, size: UVec2) -> Self {
+        let texture = gfx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Hud::overlay_texture"),
+            size: wgpu::Extent3d {
+                width: size.x,
+                height: size.y,
+                ..Default::default()
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+        });
+        Self {
+            size,
+            view: texture.create_view(&Default::default()),
+        }
+    }
+}
+
+impl Hud {
+    pub fn new(gfx: &GraphicsContext, viewport: &Viewport) -> Self {
+        let points = PrimitiveBuffer::new(gfx, "Hud::points_buffer");
+        let lines = PrimitiveBuffer::new(gfx, "Hud::lines_buffer");
+        let ellipses = PrimitiveBuffer::new(gfx, "Hud::ellipses_buffer");
+        let conics = PrimitiveBuffer::new(gfx, "Hud::conics_buffer");
+        let tiles = PrimitiveBuffer::new(gfx, "Hud::tiles_buffer");
+        let entries = PrimitiveBuffer::new(gfx, "Hud::entries_buffer");
+
+        let bind_group_layout =
+            gfx.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Hud::bind_group_layout"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: wgpu::BindingType::StorageTexture {
+                                access: wgpu::StorageTextureAccess::WriteOnly,
+                                format: Overlay::FORMAT,
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 2,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 3,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 4,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 5,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 6,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 7,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: wgpu::BindingType::Texture {
+                                sample_type: wgpu::TextureSampleType::Depth,
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                                multisampled: false,
+                            },
+                            count: None,
+                        },
+                    ],
+                });
+
+        let shader_module = gfx
+            .device
+            .create_shader_module(&wgpu::include_wgsl!("compute_hud.wgsl"));
+
+        let pipeline_layout = gfx
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Hud::pipeline_layout"),
+                bind_group_layouts: &[viewport.bind_group_layout(), &bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let raster_pipeline =
+            gfx.device
+                .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: Some("Hud::raster_pipeline"),
+                    layout: Some(&pipeline_layout),
+                    module: &shader_module,
+                    entry_point: "raster_main",
+                });
+
+        let composite_bind_group_layout =
+            gfx.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Hud::composite_bind_group_layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    }],
+                });
+
+        let composite_module = gfx
+            .device
+            .create_shader_module(&wgpu::include_wgsl!("composite.wgsl"));
+
+        let composite_pipeline_layout =
+            gfx.device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Hud::composite_pipeline_layout"),
+                    bind_group_layouts: &[&composite_bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+
+        let composite_pipeline =
+            gfx.device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("Hud::composite_pipeline"),
+                    layout: Some(&composite_pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &composite_module,
+                        entry_point: "vs_main",
+                        buffers: &[],
+                    },
+                    primitive: Default::default(),
+                    depth_stencil: None,
+                    multisample: Default::default(),
+                    fragment: Some(wgpu::FragmentState {
+                        module: &composite_module,
+                        entry_point: "fs_main",
+                        targets: &[wgpu::ColorTargetState {
+                            format: gfx.render_format,
+                            blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                            write_mask: wgpu::ColorWrites::all(),
+                        }],
+                    }),
+                    multiview: None,
+                });
+
+        let overlay = Overlay::new(gfx, Overlay::size(gfx));
+        let text = TextRenderer::new(gfx);
+
+        Self {
+            gfx: gfx.clone(),
+            points,
+            lines,
+            ellipses,
+            conics,
+            tiles,
+            entries,
+            text,
+            overlay,
+            bind_group_layout,
+            raster_pipeline,
+            composite_bind_group_layout,
+            composite_pipeline,
+        }
+    }
+}
+
+impl HudRenderer for Hud {
+    fn point(&mut self, position: Vec3, size: f32, color: [f32; 4]) {
+        self.points.push(Point {
+            position: position.into(),
+            size: size * self.gfx.scale_factor() as f32,
+            color,
+        });
+    }
+
+    fn line(&mut self, start: Vec3, end: Vec3, stroke: Stroke) {
+        let stroke = stroke.scaled(self.gfx.scale_factor() as f32);
+        self.lines.push(Line {
+            start: start.into(),
+            size: stroke.size,
+            end: end.into(),
+            dashes: stroke.dashes,
+            color: stroke.color,
+        });
+    }
+
+    fn ellipse(&mut self, center: Vec3, axis_1: Vec3, axis_2: Vec3, stroke: Stroke) {
+        let stroke = stroke.scaled(self.gfx.scale_factor() as f32);
+        self.ellipses.push(Ellipse {
+            center: center.into(),
+            size: stroke.size,
+            axis_1: axis_1.into(),
+            dashes: stroke.dashes,
+            axis_2: axis_2.into(),
+            _axis_2_padding: Default::default(),
+            color: stroke.color,
+        });
+    }
+
+    fn conic_arc(
+        &mut self,
+        focus: Vec3,
+        e_dir: Vec3,
+        e: f32,
+        p_dir: Vec3,
+        p: f32,
+        angles: Range<f32>,
+        stroke: Stroke,
+    ) {
+        let stroke = stroke.scaled(self.gfx.scale_factor() as f32);
+        self.conics.push(Conic {
+            focus: focus.into(),
+            size: stroke.size,
+            e_vec: e_dir.into(),
+            e,
+            p_vec: p_dir.into(),
+            p,
+            color: stroke.color,
+            dashes: stroke.dashes,
+            start_angle: angles.start,
+            end_angle: angles.end,
+            _padding: Default::default(),
+        });
+    }
+
+    fn text(&mut self, position: Vec2, text: &str, color: [f32; 4]) {
+        self.text.text(position, text, color);
+    }
+
+    fn label(&mut self, position: Vec3, text: &str, color: [f32; 4]) {
+        self.text.label(position, text, color);
+    }
+
+    fn draw(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        frame_view: &wgpu::TextureView,
+        depth_view: &wgpu::TextureView,
+        viewport: &Viewport,
+    ) {
+        let size = Overlay::size(&self.gfx);
+        if size != self.overlay.size {
+            self.overlay = Overlay::new(&self.gfx, size);
+        }
+
+        let view = View::new(viewport, size);
+        binning::bin(
+            &view,
+            &self.lines.items,
+            &self.ellipses.items,
+            &self.conics.items,
+            &self.points.items,
+            &mut self.tiles.items,
+            &mut self.entries.items,
+        );
+
+        self.points.upload(&self.gfx);
+        self.lines.upload(&self.gfx);
+        self.ellipses.upload(&self.gfx);
+        self.conics.upload(&self.gfx);
+        self.tiles.upload(&self.gfx);
+        self.entries.upload(&self.gfx);
+
+        let bind_group = self
+            .gfx
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Hud::bind_group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&self.overlay.view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: self.points.buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: self.lines.buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: self.ellipses.buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 4,
+                        resource: self.conics.buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 5,
+                        resource: self.tiles.buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 6,
+                        resource: self.entries.buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 7,
+                        resource: wgpu::BindingResource::TextureView(depth_view),
+                    },
+                ],
+            });
+        let text_bind_group = self.text.upload(&self.gfx, &view);
+        let composite_bind_group = self
+            .gfx
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Hud::composite_bind_group"),
+                layout: &self.composite_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&self.overlay.view),
+                }],
+            });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Hud::compute_pass"),
+            });
+            pass.set_bind_group(0, viewport.bind_group(), &[]);
+            pass.set_bind_group(1, &bind_group, &[]);
+            pass.set_pipeline(&self.raster_pipeline);
+            pass.dispatch(size.x.div_ceil(TILE_SIZE), size.y.div_ceil(TILE_SIZE), 1);
+        }
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Hud::composite_pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: frame_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+            pass.set_pipeline(&self.composite_pipeline);
+            pass.set_bind_group(0, &composite_bind_group, &[]);
+            pass.draw(0..3, 0..1);
+
+            if let Some(text_bind_group) = &text_bind_group {
+                self.text.draw(&mut pass, text_bind_group);
+            }
+        }
+
+        self.points.clear();
+        self.lines.clear();
+        self.ellipses.clear();
+        self.conics.clear();
+    }
+}
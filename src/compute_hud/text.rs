@@ -0,0 +1,220 @@
+//! Draws text from the glyph atlas as textured quads.
+
+use bytemuck::{Pod, Zeroable};
+use glam::{Vec2, Vec3};
+
+use super::PrimitiveBuffer;
+use crate::{
+    hud::View,
+    text::{self, Anchor, Atlas, Text},
+    GraphicsContext,
+};
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct Glyph {
+    // vec4<f32>
+    rect: [f32; 4],
+
+    // vec4<f32>
+    atlas_rect: [f32; 4],
+
+    // vec4<f32>
+    color: [f32; 4],
+}
+
+pub(super) struct TextRenderer {
+    atlas: Atlas,
+    texts: Vec<Text>,
+    glyphs: PrimitiveBuffer<Glyph>,
+    atlas_view: wgpu::TextureView,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl TextRenderer {
+    pub fn new(gfx: &GraphicsContext) -> Self {
+        let atlas = Atlas::new();
+        let glyphs = PrimitiveBuffer::new(gfx, "TextRenderer::glyphs_buffer");
+
+        let atlas_size = wgpu::Extent3d {
+            width: atlas.size.x,
+            height: atlas.size.y,
+            depth_or_array_layers: 1,
+        };
+        let atlas_texture = gfx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("TextRenderer::atlas_texture"),
+            size: atlas_size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+        });
+        gfx.queue.write_texture(
+            atlas_texture.as_image_copy(),
+            &atlas.pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(atlas.size.x),
+                rows_per_image: None,
+            },
+            atlas_size,
+        );
+        let atlas_view = atlas_texture.create_view(&Default::default());
+
+        let bind_group_layout =
+            gfx.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("TextRenderer::bind_group_layout"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::VERTEX,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Texture {
+                                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                                multisampled: false,
+                            },
+                            count: None,
+                        },
+                    ],
+                });
+
+        let shader_module = gfx
+            .device
+            .create_shader_module(&wgpu::include_wgsl!("text.wgsl"));
+
+        let pipeline_layout = gfx
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("TextRenderer::pipeline_layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let pipeline = gfx
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("TextRenderer::pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                primitive: Default::default(),
+                depth_stencil: None,
+                multisample: Default::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: "fs_main",
+                    targets: &[wgpu::ColorTargetState {
+                        format: gfx.render_format,
+                        blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::all(),
+                    }],
+                }),
+                multiview: None,
+            });
+
+        Self {
+            atlas,
+            texts: Vec::new(),
+            glyphs,
+            atlas_view,
+            bind_group_layout,
+            pipeline,
+        }
+    }
+
+    pub fn text(&mut self, position: Vec2, text: &str, color: [f32; 4]) {
+        self.texts.push(Text {
+            anchor: Anchor::Screen(position),
+            text: text.to_owned(),
+            color,
+        });
+    }
+
+    pub fn label(&mut self, position: Vec3, text: &str, color: [f32; 4]) {
+        self.texts.push(Text {
+            anchor: Anchor::World(position),
+            text: text.to_owned(),
+            color,
+        });
+    }
+
+    /// Lays out all of the text and uploads the glyphs, returning the bind
+    /// group to draw them with, or `None` if there is nothing to draw.
+    ///
+    /// The bitmap font is magnified by the window's scale factor, rounded to
+    /// a whole number so that it stays crisp.
+    pub fn upload(&mut self, gfx: &GraphicsContext, view: &View) -> Option<wgpu::BindGroup> {
+        let dims = view.dims.as_vec2();
+        let to_clip =
+            |pixel: Vec2| Vec2::new(pixel.x / dims.x * 2.0 - 1.0, 1.0 - pixel.y / dims.y * 2.0);
+        let scale_factor = gfx.scale_factor() as f32;
+        let scale = scale_factor.round().max(1.0);
+
+        self.glyphs.clear();
+        for text in self.texts.drain(..) {
+            let origin = match text.anchor.resolve(view, scale_factor) {
+                Some(origin) => origin.round(),
+                None => continue,
+            };
+            for quad in text::layout(&self.atlas, &text.text, Vec2::ZERO) {
+                let size = quad.size.as_vec2();
+                let atlas_position = quad.atlas_position.as_vec2();
+                let position = origin + quad.position * scale;
+                let min = to_clip(position);
+                let max = to_clip(position + size * scale);
+                self.glyphs.push(Glyph {
+                    rect: [min.x, min.y, max.x, max.y],
+                    atlas_rect: [
+                        atlas_position.x,
+                        atlas_position.y,
+                        atlas_position.x + size.x,
+                        atlas_position.y + size.y,
+                    ],
+                    color: text.color,
+                });
+            }
+        }
+
+        if self.glyphs.items.is_empty() {
+            return None;
+        }
+        self.glyphs.upload(gfx);
+
+        Some(gfx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("TextRenderer::bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.glyphs.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&self.atlas_view),
+                },
+            ],
+        }))
+    }
+
+    pub fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, bind_group: &'a wgpu::BindGroup) {
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.draw(0..6, 0..self.glyphs.items.len() as u32);
+    }
+}
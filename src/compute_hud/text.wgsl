@@ -0,0 +1,55 @@
+// Draws glyph quads from the text atlas over the frame.
+
+struct Glyph {
+    // Corners of the quad, in clip space.
+    rect: vec4<f32>;
+    // Corners of the glyph in the atlas, in texels.
+    atlas_rect: vec4<f32>;
+    color: vec4<f32>;
+};
+
+struct Glyphs {
+    glyphs: array<Glyph>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] atlas_position: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+};
+
+[[group(0), binding(0)]]
+var<storage, read> glyphs: Glyphs;
+
+[[group(0), binding(1)]]
+var atlas: texture_2d<f32>;
+
+[[stage(vertex)]]
+fn vs_main(
+    [[builtin(vertex_index)]] vertex_index: u32,
+    [[builtin(instance_index)]] instance_index: u32,
+) -> VertexOutput {
+    var CORNERS = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(0.0, 0.0),
+    );
+    let corner = CORNERS[vertex_index];
+    let glyph = glyphs.glyphs[instance_index];
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(mix(glyph.rect.xy, glyph.rect.zw, corner), 0.0, 1.0);
+    out.atlas_position = mix(glyph.atlas_rect.xy, glyph.atlas_rect.zw, corner);
+    out.color = glyph.color;
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let coverage = textureLoad(atlas, vec2<i32>(floor(in.atlas_position)), 0).r;
+    let alpha = in.color.a * coverage;
+    return vec4<f32>(in.color.rgb * alpha, alpha);
+}
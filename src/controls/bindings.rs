@@ -0,0 +1,626 @@
+//! Mapping from inputs to named actions, loaded from a config file.
+//!
+//! Each line of the config binds an input to an action:
+//!
+//! ```text
+//! # Comments start with a hash.
+//! move_forward = W
+//! cycle_target_back = Shift+Tab
+//! look_yaw = MouseX * -0.001
+//! look_yaw = PadRightStickX * -2
+//! ```
+//!
+//! An action can have several bindings, one per line. Modifier keys go
+//! before the input, joined with `+`, and the level of the input is
+//! multiplied by the optional scale. Binding an action to nothing (`warp_up =`) unbinds it.
+
+use std::{collections::HashMap, fmt, fs, io, path::Path};
+
+use anyhow::{anyhow, bail, Context};
+use winit::event::{ModifiersState, MouseButton, VirtualKeyCode};
+
+use super::input::{GamepadAxis, GamepadButton, Input};
+
+/// Bindings used for actions that the config file doesn't mention.
+const DEFAULT_BINDINGS: &str = include_str!("default_bindings.cfg");
+
+macro_rules! actions {
+    ($($(#[$meta:meta])* $variant:ident => $name:literal,)*) => {
+        /// Something the user can do, independent of the inputs bound to it.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Action {
+            $($(#[$meta])* $variant,)*
+        }
+
+        impl Action {
+            pub const ALL: &'static [Action] = &[$(Self::$variant,)*];
+
+            /// Name used in the config file, e.g. `move_forward`.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $($name => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+actions! {
+    MoveForward => "move_forward",
+    MoveBack => "move_back",
+    MoveLeft => "move_left",
+    MoveRight => "move_right",
+    MoveUp => "move_up",
+    MoveDown => "move_down",
+    /// Turn left (radians), or turning rate for absolute inputs (radians
+    /// per second).
+    LookYaw => "look_yaw",
+    /// Look up (radians), or turning rate for absolute inputs (radians per
+    /// second).
+    LookPitch => "look_pitch",
+    /// Zoom in (wheel lines), or zooming rate for absolute inputs (lines per
+    /// second).
+    Zoom => "zoom",
+    /// Click to select, or drag to rotate the camera.
+    Select => "select",
+    CycleTarget => "cycle_target",
+    CycleTargetBack => "cycle_target_back",
+    ToggleCameraMode => "toggle_camera_mode",
+    ToggleCoRotate => "toggle_co_rotate",
+    ToggleMap => "toggle_map",
+    ToggleHudBackend => "toggle_hud_backend",
+    ToggleCursor => "toggle_cursor",
+    WarpUp => "warp_up",
+    WarpDown => "warp_down",
+    /// Craft rotation, as a fraction of the available torque.
+    PitchUp => "pitch_up",
+    PitchDown => "pitch_down",
+    YawLeft => "yaw_left",
+    YawRight => "yaw_right",
+    RollLeft => "roll_left",
+    RollRight => "roll_right",
+    ToggleSas => "toggle_sas",
+    ToggleRcs => "toggle_rcs",
+    SasStability => "sas_stability",
+    SasPrograde => "sas_prograde",
+    SasRetrograde => "sas_retrograde",
+    SasNormal => "sas_normal",
+    SasRadial => "sas_radial",
+    SasTarget => "sas_target",
+    /// Save the next frame in the screenshots directory.
+    Screenshot => "screenshot",
+}
+
+/// An input bound to an action.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Binding {
+    pub input: Input,
+    /// Modifier keys that must be held for the binding to apply.
+    pub modifiers: ModifiersState,
+    /// Multiplier for the level or movement of the input.
+    pub scale: f32,
+}
+
+impl Binding {
+    pub fn new(input: Input) -> Self {
+        Self {
+            input,
+            modifiers: ModifiersState::empty(),
+            scale: 1.0,
+        }
+    }
+
+    pub fn with_modifiers(self, modifiers: ModifiersState) -> Self {
+        Self { modifiers, ..self }
+    }
+
+    pub fn with_scale(self, scale: f32) -> Self {
+        Self { scale, ..self }
+    }
+
+    /// Parses the right hand side of a config line, e.g. `Shift+Tab` or
+    /// `MouseX * -0.001`.
+    fn parse(text: &str) -> anyhow::Result<Self> {
+        let (input, scale) = match text.split_once('*') {
+            Some((input, scale)) => {
+                let scale = scale.trim();
+                let scale = scale
+                    .parse()
+                    .with_context(|| format!("invalid scale `{}`", scale))?;
+                (input.trim(), scale)
+            }
+            None => (text.trim(), 1.0),
+        };
+
+        let mut parts: Vec<&str> = input.split('+').map(str::trim).collect();
+        let input = parts.pop().unwrap_or_default();
+        let mut modifiers = ModifiersState::empty();
+        for part in parts {
+            modifiers |= match part {
+                "Shift" => ModifiersState::SHIFT,
+                "Ctrl" => ModifiersState::CTRL,
+                "Alt" => ModifiersState::ALT,
+                "Logo" => ModifiersState::LOGO,
+                _ => bail!("unknown modifier `{}`", part),
+            };
+        }
+        let input = input_from_name(input).ok_or_else(|| anyhow!("unknown input `{}`", input))?;
+
+        Ok(Self::new(input).with_modifiers(modifiers).with_scale(scale))
+    }
+
+    fn matches(&self, input: Input, modifiers: ModifiersState) -> bool {
+        self.input == input && modifiers.contains(self.modifiers)
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (ModifiersState::SHIFT, "Shift"),
+            (ModifiersState::CTRL, "Ctrl"),
+            (ModifiersState::ALT, "Alt"),
+            (ModifiersState::LOGO, "Logo"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", input_name(self.input))?;
+        if self.scale != 1.0 {
+            write!(f, " * {}", self.scale)?;
+        }
+        Ok(())
+    }
+}
+
+/// The bindings for every action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bindings {
+    bindings: HashMap<Action, Vec<Binding>>,
+}
+
+impl Bindings {
+    /// No bindings at all.
+    pub fn empty() -> Self {
+        Self {
+            bindings: HashMap::new(),
+        }
+    }
+
+    /// The default bindings, with any actions mentioned in the config file
+    /// at `path` rebound. A missing file is not an error.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let mut bindings = Self::default();
+        match fs::read_to_string(path) {
+            Ok(text) => bindings
+                .apply(&text)
+                .with_context(|| format!("failed to load bindings from {}", path.display()))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        Ok(bindings)
+    }
+
+    /// Parses a config, replacing the bindings of every action it mentions.
+    pub fn apply(&mut self, config: &str) -> anyhow::Result<()> {
+        let mut rebound: HashMap<Action, Vec<Binding>> = HashMap::new();
+        for (index, line) in config.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parse_line = || -> anyhow::Result<(Action, Option<Binding>)> {
+                let (action, binding) = line
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("expected `action = input`"))?;
+                let action = action.trim();
+                let action = Action::from_name(action)
+                    .ok_or_else(|| anyhow!("unknown action `{}`", action))?;
+                let binding = match binding.trim() {
+                    "" => None,
+                    binding => Some(Binding::parse(binding)?),
+                };
+                Ok((action, binding))
+            };
+            let (action, binding) = parse_line().with_context(|| format!("line {}", index + 1))?;
+            rebound.entry(action).or_default().extend(binding);
+        }
+        self.bindings.extend(rebound);
+        Ok(())
+    }
+
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Adds a binding, keeping any others that the action already has.
+    pub fn bind(&mut self, action: Action, binding: Binding) {
+        self.bindings.entry(action).or_default().push(binding);
+    }
+
+    /// Removes all of the action's bindings.
+    pub fn unbind(&mut self, action: Action) {
+        self.bindings.remove(&action);
+    }
+
+    /// The bindings that an input triggers while `modifiers` are held.
+    ///
+    /// Only the most specific bindings apply: with Shift held, Tab triggers
+    /// a `Shift+Tab` binding instead of a plain `Tab` one, but W still
+    /// triggers a plain `W` binding.
+    pub fn resolve(
+        &self,
+        input: Input,
+        modifiers: ModifiersState,
+    ) -> impl Iterator<Item = (Action, &Binding)> {
+        let matching = || {
+            self.bindings.iter().flat_map(move |(&action, bindings)| {
+                bindings
+                    .iter()
+                    .filter(move |binding| binding.matches(input, modifiers))
+                    .map(move |binding| (action, binding))
+            })
+        };
+        let specificity = |binding: &Binding| binding.modifiers.bits().count_ones();
+        let most_specific = matching()
+            .map(|(_, binding)| specificity(binding))
+            .max()
+            .unwrap_or_default();
+        matching().filter(move |(_, binding)| specificity(binding) == most_specific)
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let mut bindings = Self::empty();
+        bindings
+            .apply(DEFAULT_BINDINGS)
+            .expect("invalid default bindings");
+        bindings
+    }
+}
+
+/// Writes the bindings in the config format, so that they can be saved
+/// after rebinding.
+impl fmt::Display for Bindings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &action in Action::ALL {
+            let bindings = self.bindings(action);
+            if bindings.is_empty() {
+                writeln!(f, "{} =", action.name())?;
+            }
+            for binding in bindings {
+                writeln!(f, "{} = {}", action.name(), binding)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+macro_rules! named {
+    ($ty:ident, $prefix:literal, [$($variant:ident),* $(,)?]) => {
+        impl NamedInput for $ty {
+            fn name(self) -> String {
+                match self {
+                    $(Self::$variant => concat!($prefix, stringify!($variant)).to_owned(),)*
+                    #[allow(unreachable_patterns)]
+                    _ => format!("{}{:?}", $prefix, self),
+                }
+            }
+
+            fn from_name(name: &str) -> Option<Self> {
+                match name.strip_prefix($prefix)? {
+                    $(stringify!($variant) => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+trait NamedInput: Sized {
+    fn name(self) -> String;
+    fn from_name(name: &str) -> Option<Self>;
+}
+
+named!(
+    VirtualKeyCode,
+    "",
+    [
+        A,
+        B,
+        C,
+        D,
+        E,
+        F,
+        G,
+        H,
+        I,
+        J,
+        K,
+        L,
+        M,
+        N,
+        O,
+        P,
+        Q,
+        R,
+        S,
+        T,
+        U,
+        V,
+        W,
+        X,
+        Y,
+        Z,
+        Key1,
+        Key2,
+        Key3,
+        Key4,
+        Key5,
+        Key6,
+        Key7,
+        Key8,
+        Key9,
+        Key0,
+        F1,
+        F2,
+        F3,
+        F4,
+        F5,
+        F6,
+        F7,
+        F8,
+        F9,
+        F10,
+        F11,
+        F12,
+        Escape,
+        Tab,
+        Space,
+        Return,
+        Back,
+        Insert,
+        Delete,
+        Home,
+        End,
+        PageUp,
+        PageDown,
+        Left,
+        Up,
+        Right,
+        Down,
+        LShift,
+        RShift,
+        LControl,
+        RControl,
+        LAlt,
+        RAlt,
+        Comma,
+        Period,
+        Minus,
+        Equals,
+        Slash,
+        Backslash,
+        Semicolon,
+        Apostrophe,
+        LBracket,
+        RBracket,
+        Grave,
+        Numpad0,
+        Numpad1,
+        Numpad2,
+        Numpad3,
+        Numpad4,
+        Numpad5,
+        Numpad6,
+        Numpad7,
+        Numpad8,
+        Numpad9,
+        NumpadAdd,
+        NumpadSubtract,
+    ]
+);
+
+named!(MouseButton, "Mouse", [Left, Right, Middle]);
+
+named!(
+    GamepadButton,
+    "Pad",
+    [
+        South,
+        East,
+        North,
+        West,
+        LeftBumper,
+        RightBumper,
+        LeftStick,
+        RightStick,
+        DPadUp,
+        DPadDown,
+        DPadLeft,
+        DPadRight,
+        Start,
+        Select,
+    ]
+);
+
+named!(
+    GamepadAxis,
+    "Pad",
+    [
+        LeftStickX,
+        LeftStickY,
+        RightStickX,
+        RightStickY,
+        LeftTrigger,
+        RightTrigger,
+    ]
+);
+
+/// Name of the input in the config file, e.g. `LShift` or `PadSouth`.
+pub(super) fn input_name(input: Input) -> String {
+    match input {
+        Input::Key(key) => key.name(),
+        Input::Mouse(button) => button.name(),
+        Input::MouseX => "MouseX".to_owned(),
+        Input::MouseY => "MouseY".to_owned(),
+        Input::Wheel => "Wheel".to_owned(),
+        Input::GamepadButton(button) => button.name(),
+        Input::GamepadAxis(axis) => axis.name(),
+    }
+}
+
+pub(super) fn input_from_name(name: &str) -> Option<Input> {
+    match name {
+        "MouseX" => Some(Input::MouseX),
+        "MouseY" => Some(Input::MouseY),
+        "Wheel" => Some(Input::Wheel),
+        _ => None,
+    }
+    .or_else(|| VirtualKeyCode::from_name(name).map(Input::Key))
+    .or_else(|| MouseButton::from_name(name).map(Input::Mouse))
+    .or_else(|| GamepadButton::from_name(name).map(Input::GamepadButton))
+    .or_else(|| GamepadAxis::from_name(name).map(Input::GamepadAxis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(config: &str) -> String {
+        let err = Bindings::empty().apply(config).unwrap_err();
+        format!("{:#}", err)
+    }
+
+    #[test]
+    fn parses_bindings() {
+        let mut bindings = Bindings::empty();
+        bindings
+            .apply(
+                "# comment\n\
+                 move_forward = W  # trailing comment\n\
+                 move_forward = PadLeftStickY\n\
+                 cycle_target_back = Shift+Ctrl+Tab\n\
+                 look_yaw = MouseX * -0.5\n",
+            )
+            .unwrap();
+        assert_eq!(
+            bindings.bindings(Action::MoveForward),
+            [
+                Binding::new(Input::Key(VirtualKeyCode::W)),
+                Binding::new(Input::GamepadAxis(GamepadAxis::LeftStickY)),
+            ]
+        );
+        assert_eq!(
+            bindings.bindings(Action::CycleTargetBack),
+            [Binding::new(Input::Key(VirtualKeyCode::Tab))
+                .with_modifiers(ModifiersState::SHIFT | ModifiersState::CTRL)]
+        );
+        assert_eq!(
+            bindings.bindings(Action::LookYaw),
+            [Binding::new(Input::MouseX).with_scale(-0.5)]
+        );
+        assert_eq!(bindings.bindings(Action::Zoom), []);
+    }
+
+    #[test]
+    fn config_replaces_and_unbinds_actions() {
+        let mut bindings = Bindings::default();
+        bindings.apply("move_forward = Up\nwarp_up =\n").unwrap();
+        assert_eq!(
+            bindings.bindings(Action::MoveForward),
+            [Binding::new(Input::Key(VirtualKeyCode::Up))]
+        );
+        assert_eq!(bindings.bindings(Action::WarpUp), []);
+        // Actions the config doesn't mention keep their defaults.
+        assert_eq!(
+            bindings.bindings(Action::Select),
+            Bindings::default().bindings(Action::Select)
+        );
+    }
+
+    #[test]
+    fn reports_config_errors_with_line_numbers() {
+        assert_eq!(error("\nmove_forward"), "line 2: expected `action = input`");
+        assert_eq!(error("fly = W"), "line 1: unknown action `fly`");
+        assert_eq!(error("zoom = Wheel2"), "line 1: unknown input `Wheel2`");
+        assert_eq!(error("zoom = Hyper+W"), "line 1: unknown modifier `Hyper`");
+        assert!(error("zoom = Wheel * lots").starts_with("line 1: invalid scale `lots`"));
+    }
+
+    #[test]
+    fn failed_config_changes_nothing() {
+        let mut bindings = Bindings::default();
+        assert!(bindings.apply("move_forward = Up\nfly = W\n").is_err());
+        assert_eq!(bindings, Bindings::default());
+    }
+
+    #[test]
+    fn written_config_reads_back_the_same() {
+        let mut bindings = Bindings::default();
+        bindings.unbind(Action::WarpDown);
+        bindings.bind(
+            Action::Zoom,
+            Binding::new(Input::Key(VirtualKeyCode::Z))
+                .with_modifiers(ModifiersState::ALT)
+                .with_scale(2.5),
+        );
+        let mut read = Bindings::empty();
+        read.apply(&bindings.to_string()).unwrap();
+        for &action in Action::ALL {
+            assert_eq!(read.bindings(action), bindings.bindings(action));
+        }
+    }
+
+    #[test]
+    fn every_input_name_reads_back() {
+        let inputs = [
+            Input::Key(VirtualKeyCode::Numpad7),
+            Input::Mouse(MouseButton::Middle),
+            Input::MouseY,
+            Input::Wheel,
+            Input::GamepadButton(GamepadButton::DPadLeft),
+            Input::GamepadAxis(GamepadAxis::RightTrigger),
+        ];
+        for input in inputs {
+            assert_eq!(input_from_name(&input_name(input)), Some(input));
+        }
+    }
+
+    #[test]
+    fn most_specific_bindings_win() {
+        let mut bindings = Bindings::empty();
+        bindings
+            .apply("cycle_target = Tab\ncycle_target_back = Shift+Tab\nmove_forward = W\n")
+            .unwrap();
+        let actions = |input, modifiers| -> Vec<Action> {
+            bindings
+                .resolve(Input::Key(input), modifiers)
+                .map(|(action, _)| action)
+                .collect()
+        };
+        assert_eq!(
+            actions(VirtualKeyCode::Tab, ModifiersState::empty()),
+            [Action::CycleTarget]
+        );
+        assert_eq!(
+            actions(VirtualKeyCode::Tab, ModifiersState::SHIFT),
+            [Action::CycleTargetBack]
+        );
+        assert_eq!(
+            actions(VirtualKeyCode::W, ModifiersState::SHIFT),
+            [Action::MoveForward]
+        );
+        assert_eq!(actions(VirtualKeyCode::Q, ModifiersState::empty()), []);
+    }
+}
@@ -0,0 +1,329 @@
+//! Gamepads read from the Linux joystick interface (`/dev/input/js*`).
+//!
+//! Each device is read on its own thread, since reads block until the next
+//! event. Buttons and axes are numbered the way the `xpad` driver numbers
+//! them for Xbox controllers; other gamepads may order theirs differently.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+use super::input::{GamepadAxis, GamepadButton, Input, InputEvent, InputSource};
+
+/// Directory containing the joystick devices.
+const DEVICE_DIR: &str = "/dev/input";
+
+/// Stick positions closer to the center than this count as being at rest,
+/// since sticks rarely return exactly to the center.
+const DEAD_ZONE: f32 = 0.1;
+
+const EVENT_BUTTON: u8 = 0x01;
+const EVENT_AXIS: u8 = 0x02;
+/// Flag set on the events reporting the state of the device when it is
+/// opened.
+const EVENT_INIT: u8 = 0x80;
+
+/// A `struct js_event` from the joystick interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RawEvent {
+    kind: u8,
+    number: u8,
+    value: i16,
+}
+
+impl RawEvent {
+    const SIZE: usize = 8;
+
+    fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        // The first four bytes are a timestamp, which isn't needed.
+        Self {
+            value: i16::from_ne_bytes([bytes[4], bytes[5]]),
+            kind: bytes[6] & !EVENT_INIT,
+            number: bytes[7],
+        }
+    }
+}
+
+/// A gamepad, as an [`InputSource`].
+pub struct Gamepad {
+    events: Receiver<RawEvent>,
+    /// Level of every input that isn't at rest, so that they can be released
+    /// if the gamepad is unplugged.
+    levels: HashMap<Input, f32>,
+}
+
+impl Gamepad {
+    /// Opens every connected gamepad, skipping any that can't be opened.
+    pub fn open_all() -> Vec<Self> {
+        let entries = match fs::read_dir(DEVICE_DIR) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("js"))
+            })
+            .collect();
+        paths.sort();
+        paths
+            .iter()
+            .filter_map(|path| Self::open(path).ok())
+            .collect()
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let (sender, events) = mpsc::channel();
+        thread::spawn(move || {
+            let mut bytes = [0; RawEvent::SIZE];
+            // Reading fails once the gamepad is unplugged.
+            while file.read_exact(&mut bytes).is_ok() {
+                if sender.send(RawEvent::from_bytes(bytes)).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self::new(events))
+    }
+
+    fn new(events: Receiver<RawEvent>) -> Self {
+        Self {
+            events,
+            levels: HashMap::new(),
+        }
+    }
+
+    fn translate(&mut self, event: RawEvent, emit: &mut dyn FnMut(InputEvent)) {
+        let value = (event.value as f32 / i16::MAX as f32).clamp(-1.0, 1.0);
+        match event.kind {
+            EVENT_BUTTON => {
+                if let Some(button) = button(event.number) {
+                    let level = if event.value != 0 { 1.0 } else { 0.0 };
+                    self.set_level(Input::GamepadButton(button), level, emit);
+                }
+            }
+            EVENT_AXIS => match event.number {
+                // The hat axes of the d-pad are reported as buttons.
+                6 => {
+                    let (left, right) = hat_levels(value);
+                    self.set_level(Input::GamepadButton(GamepadButton::DPadLeft), left, emit);
+                    self.set_level(Input::GamepadButton(GamepadButton::DPadRight), right, emit);
+                }
+                7 => {
+                    let (up, down) = hat_levels(value);
+                    self.set_level(Input::GamepadButton(GamepadButton::DPadUp), up, emit);
+                    self.set_level(Input::GamepadButton(GamepadButton::DPadDown), down, emit);
+                }
+                number => {
+                    if let Some((axis, level)) = axis(number, value) {
+                        let level = if level.abs() < DEAD_ZONE { 0.0 } else { level };
+                        self.set_level(Input::GamepadAxis(axis), level, emit);
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    fn set_level(&mut self, input: Input, level: f32, emit: &mut dyn FnMut(InputEvent)) {
+        let old_level = self.levels.get(&input).copied().unwrap_or_default();
+        if level == old_level {
+            return;
+        }
+        if level == 0.0 {
+            self.levels.remove(&input);
+        } else {
+            self.levels.insert(input, level);
+        }
+        emit(InputEvent::Level { input, level });
+    }
+}
+
+impl InputSource for Gamepad {
+    fn poll(&mut self, emit: &mut dyn FnMut(InputEvent)) {
+        loop {
+            match self.events.try_recv() {
+                Ok(event) => self.translate(event, emit),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    // Unplugged, so nothing is held anymore.
+                    for (input, _) in self.levels.drain() {
+                        emit(InputEvent::Level { input, level: 0.0 });
+                    }
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn button(number: u8) -> Option<GamepadButton> {
+    Some(match number {
+        0 => GamepadButton::South,
+        1 => GamepadButton::East,
+        2 => GamepadButton::West,
+        3 => GamepadButton::North,
+        4 => GamepadButton::LeftBumper,
+        5 => GamepadButton::RightBumper,
+        6 => GamepadButton::Select,
+        7 => GamepadButton::Start,
+        9 => GamepadButton::LeftStick,
+        10 => GamepadButton::RightStick,
+        _ => return None,
+    })
+}
+
+/// The axis and its level, given the raw value from -1 to 1. The joystick
+/// interface has sticks positive down, and triggers from -1 at rest.
+fn axis(number: u8, value: f32) -> Option<(GamepadAxis, f32)> {
+    Some(match number {
+        0 => (GamepadAxis::LeftStickX, value),
+        1 => (GamepadAxis::LeftStickY, -value),
+        2 => (GamepadAxis::LeftTrigger, (value + 1.0) * 0.5),
+        3 => (GamepadAxis::RightStickX, value),
+        4 => (GamepadAxis::RightStickY, -value),
+        5 => (GamepadAxis::RightTrigger, (value + 1.0) * 0.5),
+        _ => return None,
+    })
+}
+
+/// Levels of the negative and positive buttons of a hat axis.
+fn hat_levels(value: f32) -> (f32, f32) {
+    (
+        if value < -0.5 { 1.0 } else { 0.0 },
+        if value > 0.5 { 1.0 } else { 0.0 },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::Sender;
+
+    use super::*;
+
+    fn gamepad() -> (Sender<RawEvent>, Gamepad) {
+        let (sender, events) = mpsc::channel();
+        (sender, Gamepad::new(events))
+    }
+
+    fn poll(gamepad: &mut Gamepad) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+        gamepad.poll(&mut |event| events.push(event));
+        events
+    }
+
+    fn level(input: Input, level: f32) -> InputEvent {
+        InputEvent::Level { input, level }
+    }
+
+    #[test]
+    fn decodes_raw_events() {
+        let value = (-1234i16).to_ne_bytes();
+        let bytes = [1, 2, 3, 4, value[0], value[1], EVENT_AXIS | EVENT_INIT, 5];
+        assert_eq!(
+            RawEvent::from_bytes(bytes),
+            RawEvent {
+                kind: EVENT_AXIS,
+                number: 5,
+                value: -1234,
+            }
+        );
+    }
+
+    #[test]
+    fn buttons_press_and_release() {
+        let (sender, mut gamepad) = gamepad();
+        let south = Input::GamepadButton(GamepadButton::South);
+        for value in [1, 0] {
+            sender
+                .send(RawEvent {
+                    kind: EVENT_BUTTON,
+                    number: 0,
+                    value,
+                })
+                .unwrap();
+        }
+        // Unknown buttons are ignored.
+        sender
+            .send(RawEvent {
+                kind: EVENT_BUTTON,
+                number: 42,
+                value: 1,
+            })
+            .unwrap();
+        assert_eq!(poll(&mut gamepad), [level(south, 1.0), level(south, 0.0)]);
+    }
+
+    #[test]
+    fn axes_are_oriented_and_dead_zoned() {
+        let (sender, mut gamepad) = gamepad();
+        let axis = |number, value| RawEvent {
+            kind: EVENT_AXIS,
+            number,
+            value,
+        };
+        sender.send(axis(1, -i16::MAX)).unwrap();
+        sender.send(axis(1, 1000)).unwrap();
+        sender.send(axis(5, -i16::MAX)).unwrap();
+        sender.send(axis(5, i16::MAX)).unwrap();
+        assert_eq!(
+            poll(&mut gamepad),
+            [
+                level(Input::GamepadAxis(GamepadAxis::LeftStickY), 1.0),
+                level(Input::GamepadAxis(GamepadAxis::LeftStickY), 0.0),
+                level(Input::GamepadAxis(GamepadAxis::RightTrigger), 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn hat_axes_are_buttons() {
+        let (sender, mut gamepad) = gamepad();
+        let hat = |number, value| RawEvent {
+            kind: EVENT_AXIS,
+            number,
+            value,
+        };
+        sender.send(hat(7, -i16::MAX)).unwrap();
+        sender.send(hat(7, i16::MAX)).unwrap();
+        sender.send(hat(7, 0)).unwrap();
+        let up = Input::GamepadButton(GamepadButton::DPadUp);
+        let down = Input::GamepadButton(GamepadButton::DPadDown);
+        assert_eq!(
+            poll(&mut gamepad),
+            [
+                level(up, 1.0),
+                level(up, 0.0),
+                level(down, 1.0),
+                level(down, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn unplugging_releases_everything() {
+        let (sender, mut gamepad) = gamepad();
+        sender
+            .send(RawEvent {
+                kind: EVENT_BUTTON,
+                number: 4,
+                value: 1,
+            })
+            .unwrap();
+        poll(&mut gamepad);
+        drop(sender);
+        assert_eq!(
+            poll(&mut gamepad),
+            [level(Input::GamepadButton(GamepadButton::LeftBumper), 0.0)]
+        );
+        assert_eq!(poll(&mut gamepad), []);
+    }
+}
@@ -0,0 +1,154 @@
+//! Raw inputs, before they are mapped to actions, and the devices that
+//! produce them.
+
+use glam::Vec2;
+use winit::event::{
+    DeviceEvent, ElementState, Event, ModifiersState, MouseButton, MouseScrollDelta,
+    VirtualKeyCode, WindowEvent,
+};
+
+/// Approximate number of pixels in one "line" of mouse wheel scrolling.
+const PIXELS_PER_LINE: f64 = 40.0;
+
+/// A single button or axis on some input device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Input {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+    /// Horizontal mouse movement, in device units. Positive is right.
+    MouseX,
+    /// Vertical mouse movement, in device units. Positive is down.
+    MouseY,
+    /// Mouse wheel scrolling, in lines. Positive is away from the user.
+    Wheel,
+    GamepadButton(GamepadButton),
+    GamepadAxis(GamepadAxis),
+}
+
+impl Input {
+    /// Relative inputs report movement since the last event, rather than
+    /// the current position of a button or stick.
+    pub fn is_relative(self) -> bool {
+        matches!(self, Self::MouseX | Self::MouseY | Self::Wheel)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    /// Bottom face button (A on Xbox controllers).
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Start,
+    Select,
+}
+
+/// Analog axes, each from -1 to 1, or 0 to 1 for the triggers. Positive is
+/// right and up for the sticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+/// A change to an input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEvent {
+    /// A button or absolute axis moved to a new level: 0 or 1 for buttons,
+    /// the position for axes.
+    Level { input: Input, level: f32 },
+    /// A relative input moved by some amount.
+    Motion { input: Input, delta: f64 },
+    /// The set of held modifier keys changed.
+    Modifiers(ModifiersState),
+    /// The cursor moved to a new position in the window, in physical pixels.
+    CursorMoved(Vec2),
+}
+
+impl InputEvent {
+    /// Translates a winit event into input events, of which there can be
+    /// none or several.
+    pub fn from_winit<T>(event: &Event<T>) -> Vec<Self> {
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                // Key repeat sends additional presses without a release,
+                // which don't change the level.
+                WindowEvent::KeyboardInput { input, .. } => match input.virtual_keycode {
+                    Some(key) => vec![Self::Level {
+                        input: Input::Key(key),
+                        level: input.state.level(),
+                    }],
+                    None => vec![],
+                },
+                WindowEvent::ModifiersChanged(modifiers) => vec![Self::Modifiers(*modifiers)],
+                WindowEvent::MouseInput { button, state, .. } => vec![Self::Level {
+                    input: Input::Mouse(*button),
+                    level: state.level(),
+                }],
+                WindowEvent::CursorMoved { position, .. } => vec![Self::CursorMoved(Vec2::new(
+                    position.x as f32,
+                    position.y as f32,
+                ))],
+                WindowEvent::MouseWheel { delta, .. } => {
+                    let lines = match delta {
+                        MouseScrollDelta::LineDelta(_, y) => *y as f64,
+                        MouseScrollDelta::PixelDelta(position) => position.y / PIXELS_PER_LINE,
+                    };
+                    vec![Self::Motion {
+                        input: Input::Wheel,
+                        delta: lines,
+                    }]
+                }
+                _ => vec![],
+            },
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta: (x, y) },
+                ..
+            } => vec![
+                Self::Motion {
+                    input: Input::MouseX,
+                    delta: *x,
+                },
+                Self::Motion {
+                    input: Input::MouseY,
+                    delta: *y,
+                },
+            ],
+            _ => vec![],
+        }
+    }
+}
+
+/// A device that isn't handled through winit events, such as a gamepad.
+///
+/// Sources are polled by [`Controls`](super::Controls) once per frame.
+pub trait InputSource {
+    /// Passes every input event since the last call to `emit`.
+    fn poll(&mut self, emit: &mut dyn FnMut(InputEvent));
+}
+
+trait ElementStateExt {
+    fn level(&self) -> f32;
+}
+
+impl ElementStateExt for ElementState {
+    fn level(&self) -> f32 {
+        match self {
+            ElementState::Pressed => 1.0,
+            ElementState::Released => 0.0,
+        }
+    }
+}
@@ -0,0 +1,327 @@
+//! User input, mapped to named [`Action`]s through configurable
+//! [`Bindings`].
+//!
+//! Window events from winit and events from other [`InputSource`]s are
+//! turned into [`InputEvent`]s, which can also be fed in directly with
+//! [`Controls::input`].
+
+mod bindings;
+mod gamepad;
+mod input;
+mod recording;
+
+use std::{collections::HashMap, mem::take};
+
+use glam::{Vec2, Vec3};
+use winit::event::ModifiersState;
+
+pub use self::{
+    bindings::{Action, Binding, Bindings},
+    gamepad::Gamepad,
+    input::{GamepadAxis, GamepadButton, Input, InputEvent, InputSource},
+    recording::{Frame, Recorder, Replay, WindowSize},
+};
+
+/// How far the mouse can move while selecting for it to still count as a
+/// click rather than a drag.
+const CLICK_TOLERANCE: f64 = 4.0;
+
+/// Level at which an analog input counts as pressed.
+const PRESS_THRESHOLD: f32 = 0.5;
+
+pub struct Controls {
+    bindings: Bindings,
+    sources: Vec<Box<dyn InputSource>>,
+    modifiers: ModifiersState,
+    /// Current level of every absolute input that isn't at rest.
+    levels: HashMap<Input, f32>,
+    /// Scaled movement of relative inputs since the last call to
+    /// [`Controls::take_axis`].
+    deltas: HashMap<Action, f64>,
+    /// Number of presses since the last call to [`Controls::take_presses`].
+    presses: HashMap<Action, u32>,
+    /// Mouse movement since `select` was pressed.
+    drag_distance: f64,
+    clicked: bool,
+    cursor_position: Vec2,
+}
+
+impl Controls {
+    pub fn new(bindings: Bindings) -> Self {
+        Self {
+            bindings,
+            sources: Vec::new(),
+            modifiers: ModifiersState::empty(),
+            levels: HashMap::new(),
+            deltas: HashMap::new(),
+            presses: HashMap::new(),
+            drag_distance: 0.0,
+            clicked: false,
+            cursor_position: Vec2::ZERO,
+        }
+    }
+
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
+    pub fn bindings_mut(&mut self) -> &mut Bindings {
+        &mut self.bindings
+    }
+
+    /// Adds a device to be polled by [`Controls::poll_sources`].
+    pub fn add_source(&mut self, source: Box<dyn InputSource>) {
+        self.sources.push(source);
+    }
+
+    /// Collects the input events from every added [`InputSource`], to be
+    /// handled with [`Controls::input`].
+    pub fn poll_sources(&mut self) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+        for source in &mut self.sources {
+            source.poll(&mut |event| events.push(event));
+        }
+        events
+    }
+
+    /// Maps a single input event to actions.
+    pub fn input(&mut self, event: InputEvent) {
+        let was_selecting = self.held(Action::Select);
+        match event {
+            InputEvent::Level { input, level } => {
+                let old_level = self.levels.get(&input).copied().unwrap_or_default();
+                if level == 0.0 {
+                    self.levels.remove(&input);
+                } else {
+                    self.levels.insert(input, level);
+                }
+                if old_level.abs() < PRESS_THRESHOLD && level.abs() >= PRESS_THRESHOLD {
+                    for (action, _) in self.bindings.resolve(input, self.modifiers) {
+                        *self.presses.entry(action).or_default() += 1;
+                    }
+                }
+            }
+            InputEvent::Motion { input, delta } => {
+                for (action, binding) in self.bindings.resolve(input, self.modifiers) {
+                    *self.deltas.entry(action).or_default() += delta * binding.scale as f64;
+                }
+                if was_selecting && matches!(input, Input::MouseX | Input::MouseY) {
+                    self.drag_distance += delta.abs();
+                }
+            }
+            InputEvent::Modifiers(modifiers) => self.modifiers = modifiers,
+            InputEvent::CursorMoved(position) => self.cursor_position = position,
+        }
+
+        match (was_selecting, self.held(Action::Select)) {
+            (false, true) => self.drag_distance = 0.0,
+            (true, false) if self.drag_distance <= CLICK_TOLERANCE => self.clicked = true,
+            _ => {}
+        }
+    }
+
+    /// Sum of the scaled levels of the absolute inputs bound to the action.
+    pub fn value(&self, action: Action) -> f32 {
+        self.levels
+            .iter()
+            .flat_map(|(&input, &level)| {
+                self.bindings
+                    .resolve(input, self.modifiers)
+                    .filter(move |&(bound, _)| bound == action)
+                    .map(move |(_, binding)| level * binding.scale)
+            })
+            .sum()
+    }
+
+    /// Whether the action's inputs are pressed down.
+    pub fn held(&self, action: Action) -> bool {
+        self.value(action).abs() >= PRESS_THRESHOLD
+    }
+
+    /// Number of times the action was triggered since the last call.
+    pub fn take_presses(&mut self, action: Action) -> u32 {
+        self.presses.remove(&action).unwrap_or_default()
+    }
+
+    /// Whether the action was triggered at least once since the last call.
+    pub fn take_pressed(&mut self, action: Action) -> bool {
+        self.take_presses(action) > 0
+    }
+
+    /// How far an axis-like action moved over the last `dt` seconds: the
+    /// movement of relative inputs like the mouse since the last call, plus
+    /// the level of absolute inputs like sticks as a rate.
+    pub fn take_axis(&mut self, action: Action, dt: f64) -> f64 {
+        self.deltas.remove(&action).unwrap_or_default() + self.value(action) as f64 * dt
+    }
+
+    pub fn net_movement(&self) -> Vec3 {
+        Vec3::new(
+            self.value(Action::MoveRight) - self.value(Action::MoveLeft),
+            self.value(Action::MoveForward) - self.value(Action::MoveBack),
+            self.value(Action::MoveUp) - self.value(Action::MoveDown),
+        )
+    }
+
+    /// Commanded craft torque about each body axis: pitch up, roll right
+    /// and yaw left.
+    pub fn net_rotation(&self) -> Vec3 {
+        Vec3::new(
+            self.value(Action::PitchUp) - self.value(Action::PitchDown),
+            self.value(Action::RollRight) - self.value(Action::RollLeft),
+            self.value(Action::YawLeft) - self.value(Action::YawRight),
+        )
+    }
+
+    /// Whether `select` is held down, for dragging.
+    pub fn dragging(&self) -> bool {
+        self.held(Action::Select)
+    }
+
+    /// Position of the cursor in the window, in physical pixels. Only
+    /// meaningful while the cursor isn't grabbed.
+    pub fn cursor_position(&self) -> Vec2 {
+        self.cursor_position
+    }
+
+    /// Whether `select` was clicked, without dragging, since the last call.
+    pub fn take_click(&mut self) -> bool {
+        take(&mut self.clicked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::event::{MouseButton, VirtualKeyCode};
+
+    use super::*;
+
+    fn controls(config: &str) -> Controls {
+        let mut bindings = Bindings::empty();
+        bindings.apply(config).unwrap();
+        Controls::new(bindings)
+    }
+
+    fn key(key: VirtualKeyCode, level: f32) -> InputEvent {
+        InputEvent::Level {
+            input: Input::Key(key),
+            level,
+        }
+    }
+
+    fn motion(input: Input, delta: f64) -> InputEvent {
+        InputEvent::Motion { input, delta }
+    }
+
+    /// Emits the same events every time it is polled.
+    struct Script(Vec<InputEvent>);
+
+    impl InputSource for Script {
+        fn poll(&mut self, emit: &mut dyn FnMut(InputEvent)) {
+            for &event in &self.0 {
+                emit(event);
+            }
+        }
+    }
+
+    #[test]
+    fn keys_are_held_and_pressed() {
+        let mut controls = controls("move_forward = W\nwarp_up = Period\n");
+        controls.input(key(VirtualKeyCode::W, 1.0));
+        assert!(controls.held(Action::MoveForward));
+        assert_eq!(controls.net_movement(), Vec3::Y);
+
+        for _ in 0..3 {
+            controls.input(key(VirtualKeyCode::Period, 1.0));
+            controls.input(key(VirtualKeyCode::Period, 0.0));
+        }
+        assert_eq!(controls.take_presses(Action::WarpUp), 3);
+        assert_eq!(controls.take_presses(Action::WarpUp), 0);
+
+        controls.input(key(VirtualKeyCode::W, 0.0));
+        assert!(!controls.held(Action::MoveForward));
+        assert_eq!(controls.net_movement(), Vec3::ZERO);
+    }
+
+    #[test]
+    fn modifiers_pick_bindings() {
+        let mut controls = controls("cycle_target = Tab\ncycle_target_back = Shift+Tab\n");
+        controls.input(InputEvent::Modifiers(ModifiersState::SHIFT));
+        controls.input(key(VirtualKeyCode::Tab, 1.0));
+        assert!(!controls.take_pressed(Action::CycleTarget));
+        assert!(controls.take_pressed(Action::CycleTargetBack));
+    }
+
+    #[test]
+    fn relative_and_absolute_inputs_drive_axes() {
+        let mut controls = controls("look_yaw = MouseX * -0.5\nlook_yaw = PadRightStickX * -2\n");
+        controls.input(motion(Input::MouseX, 4.0));
+        controls.input(motion(Input::MouseX, 2.0));
+        assert_eq!(controls.take_axis(Action::LookYaw, 0.1), -3.0);
+
+        controls.input(InputEvent::Level {
+            input: Input::GamepadAxis(GamepadAxis::RightStickX),
+            level: 0.2,
+        });
+        assert_eq!(controls.value(Action::LookYaw), -0.4);
+        assert!(!controls.held(Action::LookYaw));
+        assert!((controls.take_axis(Action::LookYaw, 0.1) + 0.04).abs() < 1e-6);
+    }
+
+    #[test]
+    fn analog_inputs_press_past_the_threshold() {
+        let mut controls = controls("toggle_map = PadLeftTrigger\n");
+        let trigger = |level| InputEvent::Level {
+            input: Input::GamepadAxis(GamepadAxis::LeftTrigger),
+            level,
+        };
+        controls.input(trigger(0.3));
+        assert!(!controls.take_pressed(Action::ToggleMap));
+        controls.input(trigger(0.6));
+        controls.input(trigger(0.9));
+        assert_eq!(controls.take_presses(Action::ToggleMap), 1);
+    }
+
+    #[test]
+    fn clicks_are_told_apart_from_drags() {
+        let mut controls = controls("select = MouseLeft\n");
+        let button = |level| InputEvent::Level {
+            input: Input::Mouse(MouseButton::Left),
+            level,
+        };
+
+        controls.input(button(1.0));
+        controls.input(motion(Input::MouseX, 1.0));
+        controls.input(button(0.0));
+        assert!(controls.take_click());
+        assert!(!controls.take_click());
+
+        controls.input(button(1.0));
+        assert!(controls.dragging());
+        controls.input(motion(Input::MouseX, 3.0));
+        controls.input(motion(Input::MouseY, -3.0));
+        controls.input(button(0.0));
+        assert!(!controls.take_click());
+    }
+
+    #[test]
+    fn sources_are_polled() {
+        let mut controls = controls("toggle_sas = PadStart\n");
+        controls.add_source(Box::new(Script(vec![
+            InputEvent::Level {
+                input: Input::GamepadButton(GamepadButton::Start),
+                level: 1.0,
+            },
+            InputEvent::Level {
+                input: Input::GamepadButton(GamepadButton::Start),
+                level: 0.0,
+            },
+        ])));
+        for _ in 0..2 {
+            for event in controls.poll_sources() {
+                controls.input(event);
+            }
+        }
+        assert_eq!(controls.take_presses(Action::ToggleSas), 2);
+    }
+}
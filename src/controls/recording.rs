@@ -0,0 +1,367 @@
+//! Recording input events, and replaying them so that a session plays out
+//! exactly the same way again.
+//!
+//! A recording is a text file with one entry per line. Each entry starts with
+//! the real time since recording started, in seconds, then one of:
+//!
+//! ```text
+//! level <input> <level>
+//! motion <input> <delta>
+//! modifiers <bits>
+//! cursor <x> <y>
+//! window <width> <height> <scale factor>
+//! frame <real time step in nanoseconds> <sim time in microseconds>
+//! ```
+//!
+//! Inputs are named as in the bindings config. Events belong to the frame
+//! that follows them, and the sim time is the world's clock at the start of
+//! that frame, before it is advanced. The window's size is recorded before
+//! the first frame and whenever it changes, since the camera's aspect ratio
+//! and what the cursor points at depend on it.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context};
+use glam::Vec2;
+use winit::event::ModifiersState;
+
+use super::{
+    bindings::{input_from_name, input_name},
+    input::{Input, InputEvent},
+};
+use crate::time::{SimDuration, SimInstant};
+
+/// Size of the frames rendered for the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowSize {
+    /// Width in physical pixels.
+    pub width: u32,
+    /// Height in physical pixels.
+    pub height: u32,
+    /// Physical pixels per logical pixel.
+    pub scale_factor: f64,
+}
+
+/// Everything that happened between two updates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub events: Vec<InputEvent>,
+    /// Size of the window from this frame on, if it changed.
+    pub window: Option<WindowSize>,
+    /// Real time since the previous update.
+    pub dt: Duration,
+    /// The world's clock at the start of the update.
+    pub sim_time: SimInstant,
+}
+
+/// Writes input events and frame timings to a file as they happen.
+pub struct Recorder<W = BufWriter<File>> {
+    writer: W,
+    start: Instant,
+    /// Last recorded window size.
+    window: Option<WindowSize>,
+}
+
+impl Recorder {
+    pub fn create(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("failed to create recording {}", path.display()))?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            start: Instant::now(),
+            window: None,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Records an event for the next frame.
+    ///
+    /// Inputs that don't have a name in the bindings config can't be bound
+    /// to anything, so they are left out.
+    pub fn event(&mut self, event: &InputEvent) -> io::Result<()> {
+        let time = self.start.elapsed().as_secs_f64();
+        match *event {
+            InputEvent::Level { input, level } if is_named(input) => {
+                writeln!(
+                    self.writer,
+                    "{} level {} {}",
+                    time,
+                    input_name(input),
+                    level
+                )
+            }
+            InputEvent::Motion { input, delta } if is_named(input) => {
+                writeln!(
+                    self.writer,
+                    "{} motion {} {}",
+                    time,
+                    input_name(input),
+                    delta
+                )
+            }
+            InputEvent::Modifiers(modifiers) => {
+                writeln!(self.writer, "{} modifiers {}", time, modifiers.bits())
+            }
+            InputEvent::CursorMoved(position) => {
+                writeln!(self.writer, "{} cursor {} {}", time, position.x, position.y)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Ends the current frame, which is updated with the given time step
+    /// starting at `sim_time` and rendered at the given window size.
+    pub fn frame(
+        &mut self,
+        dt: Duration,
+        sim_time: SimInstant,
+        window: WindowSize,
+    ) -> io::Result<()> {
+        if self.window != Some(window) {
+            writeln!(
+                self.writer,
+                "{} window {} {} {}",
+                self.start.elapsed().as_secs_f64(),
+                window.width,
+                window.height,
+                window.scale_factor
+            )?;
+            self.window = Some(window);
+        }
+        writeln!(
+            self.writer,
+            "{} frame {} {}",
+            self.start.elapsed().as_secs_f64(),
+            dt.as_nanos(),
+            (sim_time - SimInstant::epoch()).as_micros()
+        )?;
+        // Flush every frame, so that the recording is usable even if the
+        // app crashes.
+        self.writer.flush()
+    }
+}
+
+fn is_named(input: Input) -> bool {
+    input_from_name(&input_name(input)) == Some(input)
+}
+
+/// The frames of a recording, in order.
+pub struct Replay {
+    frames: std::vec::IntoIter<Frame>,
+}
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read recording {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("invalid recording {}", path.display()))
+    }
+
+    /// Parses a recording. Events after the last frame are ignored, since
+    /// the app exited before handling them.
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut frames = Vec::new();
+        let mut events = Vec::new();
+        let mut window = None;
+        for (index, line) in text.lines().enumerate() {
+            match parse_entry(line).with_context(|| format!("line {}", index + 1))? {
+                Entry::Event(event) => events.push(event),
+                Entry::Window(size) => window = Some(size),
+                Entry::Frame { dt, sim_time } => frames.push(Frame {
+                    events: std::mem::take(&mut events),
+                    window: window.take(),
+                    dt,
+                    sim_time,
+                }),
+            }
+        }
+        Ok(Self {
+            frames: frames.into_iter(),
+        })
+    }
+
+    /// Number of frames left to replay.
+    pub fn remaining(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn next_frame(&mut self) -> Option<Frame> {
+        self.frames.next()
+    }
+}
+
+fn parse_input(name: &str) -> anyhow::Result<Input> {
+    input_from_name(name).ok_or_else(|| anyhow!("unknown input `{}`", name))
+}
+
+/// A single line of a recording.
+enum Entry {
+    Event(InputEvent),
+    Window(WindowSize),
+    Frame { dt: Duration, sim_time: SimInstant },
+}
+
+fn parse_entry(line: &str) -> anyhow::Result<Entry> {
+    // Skip the real time; replays are driven by the frame time steps.
+    let mut words = line.split_whitespace().skip(1);
+    let mut word = || words.next().ok_or_else(|| anyhow!("missing field"));
+    let event = match word()? {
+        "level" => InputEvent::Level {
+            input: parse_input(word()?)?,
+            level: word()?.parse()?,
+        },
+        "motion" => InputEvent::Motion {
+            input: parse_input(word()?)?,
+            delta: word()?.parse()?,
+        },
+        "modifiers" => InputEvent::Modifiers(ModifiersState::from_bits_truncate(word()?.parse()?)),
+        "cursor" => InputEvent::CursorMoved(Vec2::new(word()?.parse()?, word()?.parse()?)),
+        "window" => {
+            return Ok(Entry::Window(WindowSize {
+                width: word()?.parse()?,
+                height: word()?.parse()?,
+                scale_factor: word()?.parse()?,
+            }))
+        }
+        "frame" => {
+            return Ok(Entry::Frame {
+                dt: Duration::from_nanos(word()?.parse()?),
+                sim_time: SimInstant::epoch() + SimDuration::from_micros(word()?.parse()?),
+            })
+        }
+        kind => bail!("unknown entry `{}`", kind),
+    };
+    Ok(Entry::Event(event))
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::event::{MouseButton, VirtualKeyCode};
+
+    use super::*;
+    use crate::controls::GamepadAxis;
+
+    const WINDOW: WindowSize = WindowSize {
+        width: 1280,
+        height: 720,
+        scale_factor: 1.5,
+    };
+
+    fn error(text: &str) -> String {
+        format!("{:#}", Replay::parse(text).err().unwrap())
+    }
+
+    #[test]
+    fn recordings_replay_the_same_frames() {
+        let frames = vec![
+            Frame {
+                events: vec![
+                    InputEvent::Modifiers(ModifiersState::SHIFT | ModifiersState::ALT),
+                    InputEvent::Level {
+                        input: Input::Key(VirtualKeyCode::W),
+                        level: 1.0,
+                    },
+                    InputEvent::Motion {
+                        input: Input::MouseX,
+                        delta: -3.25,
+                    },
+                ],
+                window: Some(WINDOW),
+                dt: Duration::from_nanos(16_666_667),
+                sim_time: SimInstant::epoch(),
+            },
+            Frame {
+                events: vec![],
+                window: None,
+                dt: Duration::from_millis(17),
+                sim_time: SimInstant::epoch() + SimDuration::from_micros(16_666),
+            },
+            Frame {
+                events: vec![
+                    InputEvent::CursorMoved(Vec2::new(640.5, 0.1)),
+                    InputEvent::Level {
+                        input: Input::GamepadAxis(GamepadAxis::LeftStickX),
+                        level: -0.3,
+                    },
+                    InputEvent::Level {
+                        input: Input::Mouse(MouseButton::Left),
+                        level: 0.0,
+                    },
+                ],
+                window: Some(WindowSize {
+                    width: 800,
+                    ..WINDOW
+                }),
+                dt: Duration::from_millis(15),
+                sim_time: SimInstant::epoch() + SimDuration::from_micros(33_666),
+            },
+        ];
+
+        let mut recorder = Recorder::new(Vec::new());
+        let mut window = WINDOW;
+        for frame in &frames {
+            for event in &frame.events {
+                recorder.event(event).unwrap();
+            }
+            window = frame.window.unwrap_or(window);
+            recorder.frame(frame.dt, frame.sim_time, window).unwrap();
+        }
+        // The app exits before this event is handled.
+        recorder
+            .event(&InputEvent::Motion {
+                input: Input::Wheel,
+                delta: 1.0,
+            })
+            .unwrap();
+        let text = String::from_utf8(recorder.into_inner()).unwrap();
+
+        let mut replay = Replay::parse(&text).unwrap();
+        assert_eq!(replay.remaining(), frames.len());
+        for frame in frames {
+            assert_eq!(replay.next_frame(), Some(frame));
+        }
+        assert_eq!(replay.next_frame(), None);
+    }
+
+    #[test]
+    fn unnamed_inputs_are_not_recorded() {
+        let mut recorder = Recorder::new(Vec::new());
+        recorder
+            .event(&InputEvent::Level {
+                input: Input::Key(VirtualKeyCode::Snapshot),
+                level: 1.0,
+            })
+            .unwrap();
+        assert!(recorder.into_inner().is_empty());
+    }
+
+    #[test]
+    fn reports_invalid_entries() {
+        assert_eq!(
+            error("0 frame 1 0\n0.5 level Foo 1"),
+            "line 2: unknown input `Foo`"
+        );
+        assert_eq!(error("0 jump"), "line 1: unknown entry `jump`");
+        assert_eq!(error("0 frame 16000000"), "line 1: missing field");
+        assert_eq!(
+            error("0 window 1280 -720 1"),
+            "line 1: invalid digit found in string"
+        );
+    }
+}
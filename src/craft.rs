@@ -0,0 +1,356 @@
+//! A controllable spacecraft: rigid-body attitude dynamics, torque from
+//! reaction wheels and RCS thrusters, and an SAS that holds the craft
+//! pointed along a direction taken from its orbit.
+//!
+//! The craft's body frame matches the camera's: +Y is the nose, +Z is the
+//! top and +X is the right side. Pitching up turns about +X, rolling right
+//! about +Y and yawing left about +Z.
+
+use glam::{DMat3, DQuat, DVec3};
+use valet::Tag;
+
+use crate::world::{Body, World};
+
+/// Longest time step used for integrating the rotation (s). Longer steps are
+/// split up.
+const MAX_STEP: f64 = 1.0 / 120.0;
+
+/// Rate at which the SAS turns towards its target, per radian of error
+/// (1/s).
+const SAS_GAIN: f64 = 1.5;
+
+/// Fastest rotation the SAS commands while turning towards its target
+/// (rad/s).
+const SAS_MAX_RATE: f64 = 0.5;
+
+/// Time over which the SAS tries to correct an error in angular velocity
+/// (s).
+const SAS_RESPONSE_TIME: f64 = 0.25;
+
+/// The rotation of a rigid body with no forces acting on its center of
+/// mass.
+///
+/// The state is the attitude and the angular momentum, rather than the
+/// angular velocity, so that with no torque the angular momentum is kept
+/// exactly and only the attitude is integrated.
+#[derive(Debug, Clone, Copy)]
+pub struct RigidBody {
+    /// Rotation from the body frame to the world frame.
+    pub attitude: DQuat,
+    /// Angular momentum in the world frame (kg m^2/s).
+    pub angular_momentum: DVec3,
+    /// Inertia tensor in the body frame (kg m^2).
+    pub inertia: DMat3,
+}
+
+impl RigidBody {
+    pub fn new(inertia: DMat3) -> Self {
+        Self {
+            attitude: DQuat::IDENTITY,
+            angular_momentum: DVec3::ZERO,
+            inertia,
+        }
+    }
+
+    /// Inertia tensor in the world frame, `R I R^T`.
+    pub fn world_inertia(&self) -> DMat3 {
+        let rotation = DMat3::from_quat(self.attitude);
+        rotation * self.inertia * rotation.transpose()
+    }
+
+    /// Angular velocity in the world frame (rad/s).
+    pub fn angular_velocity(&self) -> DVec3 {
+        self.angular_velocity_at(self.attitude)
+    }
+
+    /// Sets the angular momentum that gives the angular velocity, in the
+    /// world frame.
+    pub fn set_angular_velocity(&mut self, angular_velocity: DVec3) {
+        self.angular_momentum = self.world_inertia() * angular_velocity;
+    }
+
+    /// Rotational kinetic energy (J).
+    pub fn kinetic_energy(&self) -> f64 {
+        0.5 * self.angular_velocity().dot(self.angular_momentum)
+    }
+
+    /// Advances the rotation by `dt` seconds under a constant torque given
+    /// in the world frame (N m).
+    pub fn integrate(&mut self, torque: DVec3, dt: f64) {
+        let steps = (dt / MAX_STEP).ceil().max(1.0);
+        let step = dt / steps;
+        for _ in 0..steps as usize {
+            self.step(torque, step);
+        }
+    }
+
+    /// A single step that splits the torque into two half kicks around a
+    /// torque-free rotation. The rotation uses the angular velocity at the
+    /// midpoint of the step, which keeps the energy from drifting for
+    /// tumbling bodies.
+    fn step(&mut self, torque: DVec3, dt: f64) {
+        self.angular_momentum += torque * (dt / 2.0);
+        let half = DQuat::from_scaled_axis(self.angular_velocity() * (dt / 2.0)) * self.attitude;
+        let rotation = DQuat::from_scaled_axis(self.angular_velocity_at(half) * dt);
+        self.attitude = (rotation * self.attitude).normalize();
+        self.angular_momentum += torque * (dt / 2.0);
+    }
+
+    fn angular_velocity_at(&self, attitude: DQuat) -> DVec3 {
+        attitude * (self.inertia.inverse() * (attitude.inverse() * self.angular_momentum))
+    }
+}
+
+/// Which way the SAS keeps the craft pointed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SasMode {
+    /// Stop any rotation, without holding a direction.
+    Stability,
+    /// Along the velocity relative to the parent body.
+    Prograde,
+    Retrograde,
+    /// Along the orbit's angular momentum.
+    Normal,
+    /// Away from the parent body.
+    Radial,
+    /// Towards the craft's target body.
+    Target,
+}
+
+impl SasMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Stability => "Stability",
+            Self::Prograde => "Prograde",
+            Self::Retrograde => "Retrograde",
+            Self::Normal => "Normal",
+            Self::Radial => "Radial",
+            Self::Target => "Target",
+        }
+    }
+}
+
+/// A craft that flies along the orbit of a body in the world, and can be
+/// turned around freely.
+#[derive(Debug, Clone)]
+pub struct Craft {
+    /// The body that carries the craft's orbit.
+    pub body: Tag<Body>,
+    pub rigid_body: RigidBody,
+    /// Most torque the reaction wheels can give about each body axis (N m).
+    pub wheel_torque: DVec3,
+    /// Most torque the RCS thrusters can give about each body axis (N m).
+    pub rcs_torque: DVec3,
+    pub rcs: bool,
+    /// The SAS mode, or `None` if it is turned off.
+    pub sas: Option<SasMode>,
+    /// Body pointed at by [`SasMode::Target`].
+    pub target: Option<Tag<Body>>,
+}
+
+impl Craft {
+    pub fn new(body: Tag<Body>, inertia: DMat3, wheel_torque: DVec3, rcs_torque: DVec3) -> Self {
+        Self {
+            body,
+            rigid_body: RigidBody::new(inertia),
+            wheel_torque,
+            rcs_torque,
+            rcs: false,
+            sas: Some(SasMode::Stability),
+            target: None,
+        }
+    }
+
+    /// Rotation from the body frame to the world frame.
+    pub fn attitude(&self) -> DQuat {
+        self.rigid_body.attitude
+    }
+
+    /// Direction of the nose in the world frame.
+    pub fn forward(&self) -> DVec3 {
+        self.attitude() * DVec3::Y
+    }
+
+    /// Most torque available about each body axis (N m).
+    pub fn max_torque(&self) -> DVec3 {
+        if self.rcs {
+            self.wheel_torque + self.rcs_torque
+        } else {
+            self.wheel_torque
+        }
+    }
+
+    /// Fraction of sunlight reaching the craft, from 0 in the umbra of a
+    /// body to 1 in full light.
+    pub fn sunlight(&self, world: &World) -> f64 {
+        world.illumination(world.body(&self.body).position())
+    }
+
+    /// Whether a body blocks any of the sunlight.
+    pub fn in_eclipse(&self, world: &World) -> bool {
+        self.sunlight(world) < 1.0
+    }
+
+    /// Direction the SAS is pointing the craft in, in the world frame.
+    ///
+    /// Returns `None` if the SAS is off or only stopping rotation, or if the
+    /// direction isn't defined: the body isn't orbiting anything for the
+    /// orbit-relative modes, or there is no target.
+    pub fn sas_direction(&self, world: &World) -> Option<DVec3> {
+        let body = world.body(&self.body);
+        let state = || Some(body.orbit()?.current_state(world.time()));
+        let direction = match self.sas? {
+            SasMode::Stability => return None,
+            SasMode::Prograde => state()?.velocity,
+            SasMode::Retrograde => -state()?.velocity,
+            SasMode::Normal => {
+                let state = state()?;
+                state.position.cross(state.velocity)
+            }
+            SasMode::Radial => state()?.position,
+            SasMode::Target => world.body(self.target.as_ref()?).position() - body.position(),
+        };
+        direction.try_normalize()
+    }
+
+    /// Turns the craft for `dt` seconds.
+    ///
+    /// `input` is the commanded torque about each body axis, as a fraction
+    /// of [`Craft::max_torque`] from -1 to 1. While there is no input, the
+    /// SAS takes over and turns towards `sas_direction`, or just stops the
+    /// rotation if it is `None`.
+    pub fn update(&mut self, input: DVec3, sas_direction: Option<DVec3>, dt: f64) {
+        let command = if input == DVec3::ZERO && self.sas.is_some() {
+            self.sas_command(sas_direction)
+        } else {
+            input
+        };
+        let torque = command.clamp(-DVec3::ONE, DVec3::ONE) * self.max_torque();
+        self.rigid_body.integrate(self.attitude() * torque, dt);
+    }
+
+    /// Torque that the SAS asks for, as a fraction of the maximum about each
+    /// body axis.
+    fn sas_command(&self, direction: Option<DVec3>) -> DVec3 {
+        let attitude = self.attitude();
+        let angular_velocity = attitude.inverse() * self.rigid_body.angular_velocity();
+
+        // Turn the nose towards the direction along the shortest arc, more
+        // slowly as it gets close.
+        let desired = match direction {
+            Some(direction) => {
+                let direction = attitude.inverse() * direction;
+                let axis = DVec3::Y
+                    .cross(direction)
+                    .try_normalize()
+                    .unwrap_or(DVec3::X);
+                let error = DVec3::Y.angle_between(direction);
+                axis * (error * SAS_GAIN).min(SAS_MAX_RATE)
+            }
+            None => DVec3::ZERO,
+        };
+        let torque = self.rigid_body.inertia * (desired - angular_velocity) / SAS_RESPONSE_TIME;
+        torque / self.max_torque()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A body with three different principal moments, spinning mostly about
+    /// its unstable intermediate axis so that it tumbles.
+    fn tumbling() -> RigidBody {
+        let mut body = RigidBody::new(DMat3::from_diagonal(DVec3::new(2.0, 3.0, 5.0)));
+        body.attitude = DQuat::from_rotation_x(0.3) * DQuat::from_rotation_z(1.1);
+        body.set_angular_velocity(body.attitude * DVec3::new(0.01, 2.0, 0.02));
+        body
+    }
+
+    #[test]
+    fn torque_free_tumbling_conserves_momentum_and_energy() {
+        let mut body = tumbling();
+        let momentum = body.angular_momentum;
+        let energy = body.kinetic_energy();
+        let start = body.attitude;
+        for _ in 0..600 {
+            body.integrate(DVec3::ZERO, 0.1);
+            assert!((body.angular_momentum.length() - momentum.length()).abs() < 1e-9);
+            assert!(
+                (body.kinetic_energy() - energy).abs() < 1e-3 * energy,
+                "energy {} drifted from {}",
+                body.kinetic_energy(),
+                energy
+            );
+            assert!((body.attitude.length() - 1.0).abs() < 1e-9);
+        }
+        assert_eq!(body.angular_momentum, momentum);
+        // The body really did tumble rather than spin steadily.
+        let axis = |attitude: DQuat| attitude * DVec3::Y;
+        assert!(axis(body.attitude).dot(axis(start)) < 0.9);
+    }
+
+    #[test]
+    fn body_frame_momentum_keeps_its_length() {
+        let mut body = tumbling();
+        let length = body.angular_momentum.length();
+        for _ in 0..100 {
+            body.integrate(DVec3::ZERO, 0.5);
+            let body_momentum = body.attitude.inverse() * body.angular_momentum;
+            assert!((body_momentum.length() - length).abs() < 1e-9 * length);
+        }
+    }
+
+    #[test]
+    fn spin_about_the_major_axis_is_steady() {
+        let mut body = RigidBody::new(DMat3::from_diagonal(DVec3::new(2.0, 3.0, 5.0)));
+        body.set_angular_velocity(DVec3::new(0.0, 0.0, 1.5));
+        body.integrate(DVec3::ZERO, 30.0);
+        assert!((body.angular_velocity() - DVec3::new(0.0, 0.0, 1.5)).length() < 1e-9);
+        let expected = DQuat::from_rotation_z(45.0);
+        assert!(body.attitude.dot(expected).abs() > 1.0 - 1e-9);
+    }
+
+    #[test]
+    fn target_mode_works_without_an_orbit() {
+        let world = World::new();
+        let find = |name| {
+            *world
+                .body_tags
+                .iter()
+                .find(|tag| world.body(tag).name() == name)
+                .unwrap()
+        };
+        let (sun, earth) = (find("Sun"), find("Earth"));
+        let mut craft = Craft::new(sun, DMat3::IDENTITY, DVec3::ONE, DVec3::ONE);
+        craft.target = Some(earth);
+
+        craft.sas = Some(SasMode::Prograde);
+        assert_eq!(craft.sas_direction(&world), None);
+        craft.sas = Some(SasMode::Target);
+        let expected = (world.body(&earth).position() - world.body(&sun).position()).normalize();
+        let direction = craft.sas_direction(&world).unwrap();
+        assert!((direction - expected).length() < 1e-9);
+    }
+
+    #[test]
+    fn default_craft_orbits_within_its_sphere_of_influence() {
+        let world = World::new();
+        let tag = world.craft().unwrap().body;
+        let body = world.body(&tag);
+        let parent = body.parent().unwrap();
+        let shape = body.orbit().unwrap().shape();
+        assert!(shape.rp() > world.body(parent).radius() + body.radius());
+        assert!(shape.ra() < world.soi_radius(parent).unwrap());
+        assert!(body.radius() < 100.0);
+    }
+
+    #[test]
+    fn torque_changes_momentum_linearly() {
+        let mut body = tumbling();
+        let momentum = body.angular_momentum;
+        let torque = DVec3::new(0.5, -1.0, 2.0);
+        body.integrate(torque, 3.0);
+        assert!((body.angular_momentum - (momentum + torque * 3.0)).length() < 1e-9);
+    }
+}
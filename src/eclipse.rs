@@ -0,0 +1,207 @@
+//! Shadows that bodies cast on each other in the light of the root body.
+//!
+//! Bodies are spheres, so the light and each body in front of it cover
+//! discs in the sky of a point. The light reaching the point is the part of
+//! the light's disc that no body covers: none of it in the umbra, some of it
+//! in the penumbra, and all of it outside the shadow. The same calculation
+//! is done per pixel in the scene shader.
+
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use glam::DVec3;
+
+/// A sphere that light can come from or be blocked by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sphere {
+    pub center: DVec3,
+    pub radius: f64,
+}
+
+/// Fraction of the light's disc that can be seen from `point`, from 0 in
+/// the umbra to 1 in full light.
+///
+/// Each occluder blocks light independently of the others, which only
+/// differs from the exact answer when several of them overlap the light at
+/// once. A point inside an occluder, such as one just below the surface of
+/// a body, is treated as being on its surface.
+pub fn illumination(
+    point: DVec3,
+    light: Sphere,
+    occluders: impl IntoIterator<Item = Sphere>,
+) -> f64 {
+    let to_light = light.center - point;
+    let light_radius = angular_radius(light.radius, to_light.length());
+    occluders
+        .into_iter()
+        .map(|occluder| {
+            let to_occluder = occluder.center - point;
+            let occluder_radius = angular_radius(occluder.radius, to_occluder.length());
+            let separation = to_light.angle_between(to_occluder);
+            1.0 - covered_fraction(light_radius, occluder_radius, separation)
+        })
+        .product()
+}
+
+/// Angle from the center of a sphere's disc to its edge, seen from
+/// `distance` away, or a right angle from inside it.
+fn angular_radius(radius: f64, distance: f64) -> f64 {
+    if distance <= radius {
+        FRAC_PI_2
+    } else {
+        (radius / distance).asin()
+    }
+}
+
+/// Fraction of a disc of angular radius `light` covered by one of radius
+/// `occluder` with their centers `separation` apart (radians). The sky is
+/// treated as flat, which is close enough where the light's disc is small.
+pub fn covered_fraction(light: f64, occluder: f64, separation: f64) -> f64 {
+    if light <= 0.0 || separation >= light + occluder {
+        return 0.0;
+    }
+    if separation <= (light - occluder).abs() {
+        // One disc is entirely inside the other.
+        return (occluder / light).min(1.0).powi(2);
+    }
+    // Area of the lens where two circles overlap.
+    let (a, b, c) = (light, occluder, separation);
+    let light_angle = ((c * c + a * a - b * b) / (2.0 * c * a))
+        .clamp(-1.0, 1.0)
+        .acos();
+    let occluder_angle = ((c * c + b * b - a * a) / (2.0 * c * b))
+        .clamp(-1.0, 1.0)
+        .acos();
+    let kite = ((-c + a + b) * (c + a - b) * (c - a + b) * (c + a + b))
+        .max(0.0)
+        .sqrt();
+    let overlap = a * a * light_angle + b * b * occluder_angle - 0.5 * kite;
+    (overlap / (PI * a * a)).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUN: Sphere = Sphere {
+        center: DVec3::ZERO,
+        radius: 1.0,
+    };
+    const MOON: Sphere = Sphere {
+        center: glam::const_dvec3!([10.0, 0.0, 0.0]),
+        radius: 0.5,
+    };
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} isn't {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn separate_discs_dont_overlap() {
+        assert_eq!(covered_fraction(0.1, 0.05, 0.2), 0.0);
+        assert_eq!(covered_fraction(0.1, 0.05, 0.15), 0.0);
+        assert_eq!(covered_fraction(0.0, 0.05, 0.0), 0.0);
+    }
+
+    #[test]
+    fn contained_discs() {
+        // A small occluder inside the light's disc.
+        assert_close(covered_fraction(0.1, 0.05, 0.0), 0.25);
+        assert_close(covered_fraction(0.1, 0.05, 0.04), 0.25);
+        // The light's disc inside a large occluder.
+        assert_eq!(covered_fraction(0.05, 0.1, 0.0), 1.0);
+        assert_eq!(covered_fraction(0.05, 0.1, 0.05), 1.0);
+        assert_eq!(covered_fraction(0.1, 0.1, 0.0), 1.0);
+    }
+
+    #[test]
+    fn partially_overlapping_discs() {
+        // Equal discs a radius apart overlap in a lens of area
+        // 2π/3 - √3/2.
+        let lens = 2.0 * PI / 3.0 - 3f64.sqrt() / 2.0;
+        assert_close(covered_fraction(1.0, 1.0, 1.0), lens / PI);
+        assert_close(covered_fraction(0.01, 0.01, 0.01), lens / PI);
+
+        // The overlap shrinks steadily from containment to touching.
+        let (light, occluder) = (0.1, 0.05);
+        assert_close(covered_fraction(light, occluder, 0.05 + 1e-12), 0.25);
+        assert_close(covered_fraction(light, occluder, 0.15 - 1e-12), 0.0);
+        let mut previous = 0.25;
+        for i in 1..100 {
+            let separation = 0.05 + 0.1 * i as f64 / 100.0;
+            let covered = covered_fraction(light, occluder, separation);
+            assert!(covered < previous && covered > 0.0);
+            previous = covered;
+        }
+    }
+
+    #[test]
+    fn umbra() {
+        // The moon exactly covers the sun from here.
+        let point = DVec3::new(20.0, 0.0, 0.0);
+        assert_close(illumination(point, SUN, [MOON]), 0.0);
+        // And covers more of it from closer.
+        let point = DVec3::new(15.0, 0.0, 0.0);
+        assert_eq!(illumination(point, SUN, [MOON]), 0.0);
+    }
+
+    #[test]
+    fn antumbra() {
+        // Beyond the umbra, the moon is smaller than the sun.
+        let point = DVec3::new(30.0, 0.0, 0.0);
+        let ratio = (0.5 / 20.0f64).asin() / (1.0 / 30.0f64).asin();
+        assert_close(illumination(point, SUN, [MOON]), 1.0 - ratio * ratio);
+    }
+
+    #[test]
+    fn penumbra() {
+        let mut previous = 0.0;
+        for i in 0..=10 {
+            let point = DVec3::new(20.0, 0.25 * i as f64, 0.0);
+            let light = illumination(point, SUN, [MOON]);
+            assert!(light >= previous);
+            previous = light;
+        }
+        let light = illumination(DVec3::new(20.0, 0.5, 0.0), SUN, [MOON]);
+        assert!(light > 0.0 && light < 1.0, "{}", light);
+        assert_close(previous, 1.0);
+    }
+
+    #[test]
+    fn full_light() {
+        let point = DVec3::new(20.0, 5.0, 0.0);
+        assert_eq!(illumination(point, SUN, [MOON]), 1.0);
+        assert_eq!(illumination(point, SUN, []), 1.0);
+        // Occluders behind the point don't shade it.
+        let point = DVec3::new(5.0, 0.0, 0.0);
+        assert_eq!(illumination(point, SUN, [MOON]), 1.0);
+    }
+
+    #[test]
+    fn points_inside_occluders() {
+        // Just below the night side.
+        let point = DVec3::new(10.4, 0.0, 0.0);
+        assert_eq!(illumination(point, SUN, [MOON]), 0.0);
+        // Just below the day side.
+        let point = DVec3::new(9.6, 0.0, 0.0);
+        assert_eq!(illumination(point, SUN, [MOON]), 1.0);
+        assert_eq!(angular_radius(0.5, 0.2), FRAC_PI_2);
+    }
+
+    #[test]
+    fn occluders_combine() {
+        let other = Sphere {
+            center: DVec3::new(10.0, 0.0, 0.6),
+            ..MOON
+        };
+        let point = DVec3::new(20.0, 0.6, 0.0);
+        let one = illumination(point, SUN, [MOON]);
+        let both = illumination(point, SUN, [MOON, other]);
+        assert_close(both, one * illumination(point, SUN, [other]));
+        assert!(both < one);
+    }
+}
@@ -1,6 +1,12 @@
+use std::collections::HashMap;
+
 use glam::Vec3;
 
-use crate::{geometry::icosahedron, model::Model, GraphicsContext};
+use crate::{
+    geometry::icosahedron,
+    model::{MeshData, Model},
+    GraphicsContext,
+};
 
 pub struct Geodesic {
     pub subdivisions: usize,
@@ -8,76 +14,103 @@ pub struct Geodesic {
 }
 
 impl Geodesic {
-    fn using_lerp(
-        gfx: &GraphicsContext,
-        subdivisions: usize,
-        lerp: fn(Vec3, Vec3, f32) -> Vec3,
-    ) -> Self {
-        use icosahedron::{INDICES, VERTICES};
-
-        let mut vertices = Vec::new();
-        let mut triangles = Vec::new();
-
-        for triangle in INDICES.chunks_exact(3) {
-            let up = VERTICES[triangle[0] as usize];
-            let right = VERTICES[triangle[1] as usize];
-            let left = VERTICES[triangle[2] as usize];
-
-            let mut last_row = Vec::new();
-            last_row.push(up);
+    pub fn new(gfx: &GraphicsContext, subdivisions: usize) -> Self {
+        let model = Model::from_mesh(gfx, Some("Geodesic"), &Self::mesh(subdivisions));
+        Self {
+            subdivisions,
+            model,
+        }
+    }
 
-            let mut this_row = Vec::new();
+    /// A unit sphere made by splitting each triangle of an icosahedron into
+    /// four, `subdivisions` times over, and pushing the new vertices out
+    /// onto the sphere. It has `10 * 4^subdivisions + 2` vertices.
+    ///
+    /// Triangles that share an edge share the vertex in the middle of it, so
+    /// the mesh is closed and its normals are smooth across the edges of the
+    /// icosahedron.
+    pub fn mesh(subdivisions: usize) -> MeshData {
+        let mut positions = icosahedron::VERTICES.to_vec();
+        let mut indices = icosahedron::INDICES.to_vec();
 
-            for row in 1..=subdivisions {
-                // Construct vertexes for this row
-                let factor = row as f32 / subdivisions as f32;
-                let row_start = lerp(up, left, factor);
-                let row_end = lerp(up, right, factor);
-                for col in 0..=row {
-                    let col_factor = col as f32 / row as f32;
-                    this_row.push(lerp(row_start, row_end, col_factor).normalize());
-                }
+        for _ in 0..subdivisions {
+            let mut midpoints = HashMap::new();
+            let mut next = Vec::with_capacity(indices.len() * 4);
+            for tri in indices.chunks_exact(3) {
+                let (a, b, c) = (tri[0], tri[1], tri[2]);
+                let ab = midpoint(&mut positions, &mut midpoints, a, b);
+                let bc = midpoint(&mut positions, &mut midpoints, b, c);
+                let ca = midpoint(&mut positions, &mut midpoints, c, a);
+                next.extend([a, ab, ca, ab, b, bc, ca, bc, c, ab, bc, ca]);
+            }
+            indices = next;
+        }
 
-                // Construct triangles for this row
-                let last_row_base = vertices.len();
-                let this_row_base = last_row_base + last_row.len();
-                for i in 0..row {
-                    triangles.extend([
-                        u16::try_from(this_row_base + i).unwrap(),
-                        u16::try_from(last_row_base + i).unwrap(),
-                        u16::try_from(this_row_base + i + 1).unwrap(),
-                    ])
-                }
+        let mesh = MeshData::with_computed_normals(positions, indices);
+        debug_assert!(mesh.is_watertight());
+        debug_assert_eq!(mesh.euler_characteristic(), 2);
+        mesh
+    }
+}
 
-                for i in 0..row - 1 {
-                    triangles.extend([
-                        u16::try_from(last_row_base + i).unwrap(),
-                        u16::try_from(last_row_base + i + 1).unwrap(),
-                        u16::try_from(this_row_base + i + 1).unwrap(),
-                    ])
-                }
+/// Index of the vertex in the middle of the edge from `a` to `b`, on the
+/// sphere, adding it the first time the edge is split.
+fn midpoint(
+    positions: &mut Vec<Vec3>,
+    midpoints: &mut HashMap<(u32, u32), u32>,
+    a: u32,
+    b: u32,
+) -> u32 {
+    *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+        let position = (positions[a as usize] + positions[b as usize]).normalize();
+        positions.push(position);
+        u32::try_from(positions.len() - 1).unwrap()
+    })
+}
 
-                // Shift rows
-                vertices.append(&mut last_row);
-                last_row.append(&mut this_row);
-            }
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
 
-            // Shift in last row
-            vertices.append(&mut last_row)
-        }
+    use super::*;
 
-        let model = Model::with_computed_normals(gfx, Some("Geodesic"), &vertices, &triangles);
-        Self {
-            subdivisions,
-            model,
+    #[test]
+    fn vertex_count_follows_subdivisions() {
+        for subdivisions in 0..=4 {
+            let mesh = Geodesic::mesh(subdivisions);
+            assert_eq!(
+                mesh.positions.len(),
+                10 * 4usize.pow(subdivisions as u32) + 2
+            );
+            assert_eq!(mesh.triangle_count(), 20 * 4usize.pow(subdivisions as u32));
         }
     }
 
-    pub fn new(gfx: &GraphicsContext, subdivisions: usize) -> Self {
-        Self::using_lerp(gfx, subdivisions, Vec3::lerp)
+    #[test]
+    fn edge_vertices_are_shared() {
+        let mesh = Geodesic::mesh(3);
+        // No two vertices are in the same place.
+        let distinct: HashSet<_> = mesh
+            .positions
+            .iter()
+            .map(|position| (*position * 1e5).round().as_ivec3().to_array())
+            .collect();
+        assert_eq!(distinct.len(), mesh.positions.len());
+        assert!(mesh.is_watertight());
+        assert_eq!(mesh.euler_characteristic(), 2);
     }
 
-    pub fn with_slerp(gfx: &GraphicsContext, subdivisions: usize) -> Self {
-        Self::using_lerp(gfx, subdivisions, crate::math::slerp)
+    #[test]
+    fn vertices_are_on_the_unit_sphere_facing_out() {
+        let mesh = Geodesic::mesh(2);
+        for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+            assert!((position.length() - 1.0).abs() < 1e-6);
+            assert!(normal.dot(*position) > 0.99);
+        }
+        for tri in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.positions[tri[i] as usize]);
+            // Clockwise seen from outside.
+            assert!((c - a).cross(b - a).dot(a + b + c) > 0.0);
+        }
     }
 }
@@ -29,7 +29,7 @@ fn vertices() -> [Vec3; 12] {
 pub static VERTICES: Lazy<[Vec3; 12]> = Lazy::new(vertices);
 
 #[rustfmt::skip]
-pub const INDICES: [u16; 60] = [
+pub const INDICES: [u32; 60] = [
     1, 0, 2,
     2, 0, 3,
     3, 0, 4,
@@ -61,12 +61,7 @@ pub struct Icosahedron {
 
 impl Icosahedron {
     pub fn new(gfx: &GraphicsContext) -> Self {
-        let model = Model::with_computed_normals(
-            gfx,
-            Some("Icosahedron"),
-            &*VERTICES,
-            bytemuck::cast_slice(&INDICES),
-        );
+        let model = Model::with_computed_normals(gfx, Some("Icosahedron"), &*VERTICES, &INDICES);
         Self { model }
     }
 }
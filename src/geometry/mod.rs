@@ -22,14 +22,20 @@ impl Triangle {
                 Vertex {
                     position: [-1.0, 0.0, -1.0],
                     normal: [0.0, -1.0, 0.0],
+                    color: [1.0; 3],
+                    uv: [0.0; 2],
                 },
                 Vertex {
                     position: [0.0, 0.0, 1.0],
                     normal: [0.0, -1.0, 0.0],
+                    color: [1.0; 3],
+                    uv: [0.0; 2],
                 },
                 Vertex {
                     position: [1.0, 0.0, -1.0],
                     normal: [0.0, -1.0, 0.0],
+                    color: [1.0; 3],
+                    uv: [0.0; 2],
                 },
             ],
             &[0, 1, 2],
@@ -52,18 +58,26 @@ impl Square {
                 Vertex {
                     position: [-1.0, -1.0, 0.0],
                     normal: [0.0, 0.0, 1.0],
+                    color: [1.0; 3],
+                    uv: [0.0; 2],
                 },
                 Vertex {
                     position: [-1.0, 1.0, 0.0],
                     normal: [0.0, 0.0, 1.0],
+                    color: [1.0; 3],
+                    uv: [0.0; 2],
                 },
                 Vertex {
                     position: [1.0, 1.0, 0.0],
                     normal: [0.0, 0.0, 1.0],
+                    color: [1.0; 3],
+                    uv: [0.0; 2],
                 },
                 Vertex {
                     position: [1.0, -1.0, 0.0],
                     normal: [0.0, 0.0, 1.0],
+                    color: [1.0; 3],
+                    uv: [0.0; 2],
                 },
             ],
             &[0, 1, 2, 2, 3, 0],
@@ -0,0 +1,473 @@
+//! CPU rasterizer for HUD primitives, using tiny-skia.
+//!
+//! This needs no GPU or window, so it can render HUD frames offscreen, e.g.
+//! as reference images for the compute backend.
+
+use std::{f32::consts::TAU, ops::Range};
+
+use glam::{UVec2, Vec2, Vec3, Vec4};
+use tiny_skia::{
+    Color, FillRule, LineCap, Paint, PathBuilder, Pixmap, PremultipliedColorU8,
+    Stroke as SkiaStroke, Transform,
+};
+
+use super::{Stroke, View};
+use crate::{
+    compute_hud::coverage::dash_visible,
+    text::{self, Anchor, Atlas, Text},
+};
+
+/// Number of segments that a full revolution of a curve is split into.
+const CURVE_SEGMENTS: usize = 256;
+
+struct Point {
+    position: Vec3,
+    size: f32,
+    color: [f32; 4],
+}
+
+struct Line {
+    start: Vec3,
+    end: Vec3,
+    stroke: Stroke,
+}
+
+struct Ellipse {
+    center: Vec3,
+    axis_1: Vec3,
+    axis_2: Vec3,
+    stroke: Stroke,
+}
+
+struct Conic {
+    focus: Vec3,
+    e_dir: Vec3,
+    e: f32,
+    p_dir: Vec3,
+    p: f32,
+    angles: Range<f32>,
+    stroke: Stroke,
+}
+
+/// Collects HUD primitives and rasterizes them into a pixmap.
+///
+/// Primitives are drawn in the same order as the compute backend: lines,
+/// ellipses, conics, points, then text. There is no depth buffer, so nothing
+/// is faded out behind the scene.
+///
+/// Sizes and screen positions are in logical pixels, which are converted to
+/// the physical pixels of the pixmap using the scale factor.
+pub struct Canvas {
+    pixmap: Pixmap,
+    scale_factor: f32,
+    atlas: Atlas,
+    points: Vec<Point>,
+    lines: Vec<Line>,
+    ellipses: Vec<Ellipse>,
+    conics: Vec<Conic>,
+    texts: Vec<Text>,
+}
+
+impl Canvas {
+    /// Creates a canvas of the given size, or `None` if it is empty.
+    pub fn new(size: UVec2) -> Option<Self> {
+        Some(Self {
+            pixmap: Pixmap::new(size.x, size.y)?,
+            scale_factor: 1.0,
+            atlas: Atlas::new(),
+            points: Vec::new(),
+            lines: Vec::new(),
+            ellipses: Vec::new(),
+            conics: Vec::new(),
+            texts: Vec::new(),
+        })
+    }
+
+    pub fn size(&self) -> UVec2 {
+        UVec2::new(self.pixmap.width(), self.pixmap.height())
+    }
+
+    /// Sets the number of physical pixels per logical pixel.
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        self.scale_factor = scale_factor;
+    }
+
+    /// The most recently rasterized image, with premultiplied alpha.
+    pub fn pixmap(&self) -> &Pixmap {
+        &self.pixmap
+    }
+
+    pub fn point(&mut self, position: Vec3, size: f32, color: [f32; 4]) {
+        self.points.push(Point {
+            position,
+            size,
+            color,
+        });
+    }
+
+    pub fn line(&mut self, start: Vec3, end: Vec3, stroke: Stroke) {
+        self.lines.push(Line { start, end, stroke });
+    }
+
+    pub fn ellipse(&mut self, center: Vec3, axis_1: Vec3, axis_2: Vec3, stroke: Stroke) {
+        self.ellipses.push(Ellipse {
+            center,
+            axis_1,
+            axis_2,
+            stroke,
+        });
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn conic_arc(
+        &mut self,
+        focus: Vec3,
+        e_dir: Vec3,
+        e: f32,
+        p_dir: Vec3,
+        p: f32,
+        angles: Range<f32>,
+        stroke: Stroke,
+    ) {
+        self.conics.push(Conic {
+            focus,
+            e_dir,
+            e,
+            p_dir,
+            p,
+            angles,
+            stroke,
+        });
+    }
+
+    pub fn text(&mut self, position: Vec2, text: &str, color: [f32; 4]) {
+        self.texts.push(Text {
+            anchor: Anchor::Screen(position),
+            text: text.to_owned(),
+            color,
+        });
+    }
+
+    pub fn label(&mut self, position: Vec3, text: &str, color: [f32; 4]) {
+        self.texts.push(Text {
+            anchor: Anchor::World(position),
+            text: text.to_owned(),
+            color,
+        });
+    }
+
+    /// Clears the pixmap and draws all of the primitives added since the
+    /// last call, then clears them.
+    ///
+    /// `view.dims` should match the size of the canvas.
+    pub fn rasterize(&mut self, view: &View) {
+        self.pixmap.fill(Color::TRANSPARENT);
+
+        for line in std::mem::take(&mut self.lines) {
+            let ray = line.end - line.start;
+            // Split at every dash boundary, so that each segment is either
+            // entirely on or off.
+            let segments = (2.0 * line.stroke.dashes).ceil().max(1.0) as usize;
+            self.stroke_curve(view, &line.stroke, 0.0..1.0, segments, |t| {
+                line.start + ray * t
+            });
+        }
+        for ellipse in std::mem::take(&mut self.ellipses) {
+            self.stroke_curve(view, &ellipse.stroke, 0.0..1.0, CURVE_SEGMENTS, |t| {
+                let theta = t * TAU;
+                ellipse.center + ellipse.axis_1 * theta.cos() + ellipse.axis_2 * theta.sin()
+            });
+        }
+        for conic in std::mem::take(&mut self.conics) {
+            let u = conic.e_dir.normalize();
+            let v = conic.p_dir.normalize();
+            let range = conic.angles.start / TAU..conic.angles.end / TAU;
+            let segments = (CURVE_SEGMENTS as f32 * (range.end - range.start)).ceil() as usize;
+            self.stroke_curve(view, &conic.stroke, range, segments.max(1), |t| {
+                let theta = t * TAU;
+                conic.p / (1.0 + conic.e * theta.cos()) * (u * theta.cos() + v * theta.sin())
+                    + conic.focus
+            });
+        }
+        for point in std::mem::take(&mut self.points) {
+            let center = match view.to_pixel(point.position) {
+                Some(center) => center,
+                None => continue,
+            };
+            let radius = point.size * self.scale_factor;
+            if let Some(path) = PathBuilder::from_circle(center.x, center.y, radius) {
+                self.pixmap.fill_path(
+                    &path,
+                    &paint(point.color),
+                    FillRule::Winding,
+                    Transform::identity(),
+                    None,
+                );
+            }
+        }
+        for text in std::mem::take(&mut self.texts) {
+            if let Some(origin) = text.anchor.resolve(view, self.scale_factor) {
+                self.blit_text(&text.text, origin, text.color);
+            }
+        }
+    }
+
+    /// Strokes the curve traced by `curve` as `t` goes over `range`, where a
+    /// whole dash and gap take up `1 / stroke.dashes` of `t`.
+    ///
+    /// Segments that cross the near plane are cut off where they cross it.
+    fn stroke_curve(
+        &mut self,
+        view: &View,
+        stroke: &Stroke,
+        range: std::ops::Range<f32>,
+        segments: usize,
+        curve: impl Fn(f32) -> Vec3,
+    ) {
+        let param = |i: usize| range.start + (range.end - range.start) * i as f32 / segments as f32;
+
+        let mut path = PathBuilder::new();
+        let mut pen_down = false;
+        let mut start = view.clip(curve(param(0)));
+        for i in 0..segments {
+            let end = view.clip(curve(param(i + 1)));
+            let middle = 0.5 * (param(i) + param(i + 1));
+            let clipped =
+                clip_to_near_plane(start, end).filter(|_| dash_visible(middle, stroke.dashes));
+            match clipped {
+                Some((a, b)) => {
+                    if !pen_down || a != start {
+                        let a = view.clip_to_pixel(a);
+                        path.move_to(a.x, a.y);
+                    }
+                    let b_pixel = view.clip_to_pixel(b);
+                    path.line_to(b_pixel.x, b_pixel.y);
+                    pen_down = b == end;
+                }
+                None => pen_down = false,
+            }
+            start = end;
+        }
+
+        if let Some(path) = path.finish() {
+            // Round caps, like the compute backend, which measures the
+            // distance to the closest point of the stroke.
+            let skia_stroke = SkiaStroke {
+                width: 2.0 * stroke.size * self.scale_factor,
+                line_cap: LineCap::Round,
+                ..Default::default()
+            };
+            self.pixmap.stroke_path(
+                &path,
+                &paint(stroke.color),
+                &skia_stroke,
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+
+    /// Composites text from the glyph atlas onto the pixmap.
+    ///
+    /// The bitmap font is magnified by the scale factor, rounded to a whole
+    /// number so that it stays crisp.
+    fn blit_text(&mut self, text: &str, origin: Vec2, color: [f32; 4]) {
+        let scale = self.scale_factor.round().max(1.0) as i32;
+        let width = self.pixmap.width() as i32;
+        let height = self.pixmap.height() as i32;
+        let origin = origin.round().as_ivec2();
+        let atlas = &self.atlas;
+        let pixels = self.pixmap.pixels_mut();
+        for quad in text::layout(atlas, text, Vec2::ZERO) {
+            let position = origin + quad.position.as_ivec2() * scale;
+            for y in 0..quad.size.y {
+                for x in 0..quad.size.x {
+                    let source = quad.atlas_position + UVec2::new(x, y);
+                    let coverage =
+                        atlas.pixels[(source.y * atlas.size.x + source.x) as usize] as f32 / 255.0;
+                    let block = position + UVec2::new(x, y).as_ivec2() * scale;
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            let (tx, ty) = (block.x + dx, block.y + dy);
+                            if tx < 0 || ty < 0 || tx >= width || ty >= height {
+                                continue;
+                            }
+                            let pixel = &mut pixels[(ty * width + tx) as usize];
+                            *pixel = blend_over(*pixel, color, coverage);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Cuts off the part of a line segment (in clip coordinates) that is behind
+/// the near plane, or returns `None` if all of it is.
+fn clip_to_near_plane(a: Vec4, b: Vec4) -> Option<(Vec4, Vec4)> {
+    match (a.z >= 0.0, b.z >= 0.0) {
+        (true, true) => Some((a, b)),
+        (false, false) => None,
+        (true, false) => Some((a, b.lerp(a, b.z / (b.z - a.z)))),
+        (false, true) => Some((a.lerp(b, a.z / (a.z - b.z)), b)),
+    }
+}
+
+fn paint(color: [f32; 4]) -> Paint<'static> {
+    let [r, g, b, a] = color.map(|c| c.clamp(0.0, 1.0));
+    let mut paint = Paint::default();
+    paint.set_color(Color::from_rgba(r, g, b, a).unwrap());
+    paint.anti_alias = true;
+    paint
+}
+
+/// Composites a straight-alpha color with the given coverage over a
+/// premultiplied pixel.
+fn blend_over(pixel: PremultipliedColorU8, color: [f32; 4], coverage: f32) -> PremultipliedColorU8 {
+    let alpha = color[3].clamp(0.0, 1.0) * coverage;
+    let over = |source: f32, dest: u8| {
+        (source * alpha * 255.0 + dest as f32 * (1.0 - alpha)).round() as u8
+    };
+    let a = over(1.0, pixel.alpha());
+    let channel = |source: f32, dest: u8| over(source.clamp(0.0, 1.0), dest).min(a);
+    PremultipliedColorU8::from_rgba(
+        channel(color[0], pixel.red()),
+        channel(color[1], pixel.green()),
+        channel(color[2], pixel.blue()),
+        a,
+    )
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Mat4;
+
+    use super::*;
+
+    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+    const GREEN: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
+    const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+    const WHITE: [f32; 4] = [1.0; 4];
+
+    /// A 100×100 view from the origin along +Y, with a 90° field of view,
+    /// so that a unit offset at distance 1 is 50 pixels on screen.
+    fn view() -> View {
+        let fov = TAU / 4.0;
+        let projection = Mat4::perspective_rh(fov, 1.0, 0.1, 1000.0);
+        let camera = Mat4::look_at_rh(Vec3::ZERO, Vec3::Y, Vec3::Z);
+        View {
+            view_proj: projection * camera,
+            eye: Vec3::ZERO,
+            forward: Vec3::Y,
+            up: Vec3::Z,
+            x_fov: fov,
+            y_fov: fov,
+            dims: UVec2::splat(100),
+        }
+    }
+
+    /// Position in front of the eye that lands on the given pixel.
+    fn at(x: f32, y: f32) -> Vec3 {
+        Vec3::new((x - 50.0) / 50.0, 1.0, (50.0 - y) / 50.0)
+    }
+
+    /// Color of a pixel, or `None` where nothing was drawn.
+    fn color(canvas: &Canvas, x: u32, y: u32) -> Option<[u8; 3]> {
+        let pixel = canvas.pixmap().pixel(x, y).unwrap();
+        (pixel.alpha() > 0).then(|| [pixel.red(), pixel.green(), pixel.blue()])
+    }
+
+    /// Sum of the alpha of the pixels in a rectangle, in whole pixels.
+    fn coverage(canvas: &Canvas, x: Range<u32>, y: Range<u32>) -> f32 {
+        y.flat_map(|y| x.clone().map(move |x| (x, y)))
+            .map(|(x, y)| canvas.pixmap().pixel(x, y).unwrap().alpha() as f32 / 255.0)
+            .sum()
+    }
+
+    /// A solid line, a dashed line, a circle, and points in front of and
+    /// behind the eye.
+    fn scene() -> Canvas {
+        let mut canvas = Canvas::new(UVec2::splat(100)).unwrap();
+        canvas.line(at(20.0, 20.0), at(80.0, 20.0), Stroke::solid(1.0, RED));
+        canvas.line(
+            at(20.0, 80.0),
+            at(80.0, 80.0),
+            Stroke::dashed(1.0, GREEN, 3.0),
+        );
+        canvas.ellipse(
+            at(50.0, 50.0),
+            at(70.0, 50.0) - at(50.0, 50.0),
+            at(50.0, 30.0) - at(50.0, 50.0),
+            Stroke::solid(1.0, BLUE),
+        );
+        canvas.point(at(50.0, 50.0), 3.0, WHITE);
+        canvas.point(-Vec3::Y, 3.0, WHITE);
+        canvas.rasterize(&view());
+        canvas
+    }
+
+    #[test]
+    fn lines_cover_their_width_along_their_length() {
+        let canvas = scene();
+        assert_eq!(color(&canvas, 50, 19), Some([255, 0, 0]));
+        assert_eq!(color(&canvas, 50, 20), Some([255, 0, 0]));
+        assert_eq!(color(&canvas, 50, 23), None);
+        assert_eq!(color(&canvas, 10, 20), None);
+        assert_eq!(color(&canvas, 90, 20), None);
+        // 60 pixels long and 2 wide, with round caps.
+        let covered = coverage(&canvas, 0..100, 15..25);
+        let expected = 120.0 + TAU / 2.0;
+        assert!((covered - expected).abs() < 1.0, "covered {}", covered);
+    }
+
+    #[test]
+    fn dashed_lines_leave_gaps() {
+        // Three dashes of 10 pixels with gaps of 10 pixels between them.
+        let canvas = scene();
+        for x in [25, 45, 65] {
+            assert_eq!(color(&canvas, x, 80), Some([0, 255, 0]), "x = {}", x);
+        }
+        for x in [35, 55, 75] {
+            assert_eq!(color(&canvas, x, 80), None, "x = {}", x);
+        }
+        let covered = coverage(&canvas, 0..100, 75..85);
+        let expected = 3.0 * (20.0 + TAU / 2.0);
+        assert!((covered - expected).abs() < 1.0, "covered {}", covered);
+    }
+
+    #[test]
+    fn ellipses_are_outlined() {
+        let canvas = scene();
+        for (x, y) in [(70, 50), (29, 50), (50, 30), (50, 69)] {
+            assert_eq!(color(&canvas, x, y), Some([0, 0, 255]), "({}, {})", x, y);
+        }
+        assert_eq!(color(&canvas, 60, 50), None);
+        assert_eq!(color(&canvas, 80, 50), None);
+        // A ring 20 pixels in radius and 2 wide, leaving out the point in
+        // the middle.
+        let covered = coverage(&canvas, 25..75, 25..75) - coverage(&canvas, 40..60, 40..60);
+        let ring = 2.0 * TAU * 20.0;
+        assert!((covered - ring).abs() < 0.05 * ring, "covered {}", covered);
+    }
+
+    #[test]
+    fn points_are_filled_discs() {
+        let canvas = scene();
+        assert_eq!(color(&canvas, 50, 50), Some([255, 255, 255]));
+        // The disc behind the eye isn't drawn anywhere, so only the one
+        // in front, with area π·3², is white.
+        let white = (0..100)
+            .flat_map(|y| (0..100).map(move |x| (x, y)))
+            .filter(|&(x, y)| color(&canvas, x, y) == Some([255, 255, 255]))
+            .count();
+        assert!((20..=36).contains(&white), "{} white pixels", white);
+    }
+
+    #[test]
+    fn rasterizing_clears_the_primitives() {
+        let mut canvas = scene();
+        canvas.rasterize(&view());
+        assert_eq!(coverage(&canvas, 0..100, 0..100), 0.0);
+    }
+}
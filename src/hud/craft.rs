@@ -0,0 +1,36 @@
+//! Draws the craft's attitude: where its nose points, and where the SAS is
+//! turning it.
+
+use super::{HudRenderer, Stroke};
+use crate::world::World;
+
+/// Length of the attitude lines, as a multiple of the craft's radius.
+const AXIS_LENGTH: f64 = 4.0;
+const NOSE_WIDTH: f32 = 1.5;
+const NOSE_COLOR: [f32; 4] = [1.0, 1.0, 0.3, 1.0];
+const SAS_WIDTH: f32 = 1.0;
+const SAS_COLOR: [f32; 4] = [0.2, 0.9, 0.4, 0.75];
+const SAS_DASHES: f32 = 8.0;
+
+pub fn draw(world: &World, hud: &mut dyn HudRenderer) {
+    let craft = match world.craft() {
+        Some(craft) => craft,
+        None => return,
+    };
+    let body = world.body(&craft.body);
+    let length = AXIS_LENGTH * body.radius();
+    let position = body.position();
+
+    hud.line(
+        position.as_vec3(),
+        (position + craft.forward() * length).as_vec3(),
+        Stroke::solid(NOSE_WIDTH, NOSE_COLOR),
+    );
+    if let Some(direction) = craft.sas_direction(world) {
+        hud.line(
+            position.as_vec3(),
+            (position + direction * length).as_vec3(),
+            Stroke::dashed(SAS_WIDTH, SAS_COLOR, SAS_DASHES),
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Overlays drawn on top of the scene: orbits, markers and text.
+//!
+//! There are two interchangeable backends behind [`HudRenderer`]: the GPU
+//! compute rasterizer in [`crate::compute_hud`], and a CPU rasterizer using
+//! tiny-skia in [`skia`]. The CPU rasterizer itself, [`canvas::Canvas`], can
+//! run without a window, which makes it useful as a reference for the GPU
+//! one.
+
+pub mod canvas;
+pub mod craft;
+pub mod orbits;
+pub mod picking;
+pub mod skia;
+mod view;
+
+use std::{f32::consts::TAU, ops::Range};
+
+use glam::{Vec2, Vec3};
+
+pub use self::view::View;
+use crate::{compute_hud, viewport::Viewport, GraphicsContext};
+
+/// Open trajectories drawn with [`HudRenderer::conic`] go out to this
+/// multiple of their semi-latus rectum.
+const OPEN_CONIC_EXTENT: f32 = 100.0;
+
+/// How a line or curve is outlined.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stroke {
+    /// Half of the stroke width, in logical pixels.
+    pub size: f32,
+    pub color: [f32; 4],
+    /// Number of dashes along the whole primitive (or one revolution, for
+    /// curves). Zero means solid.
+    pub dashes: f32,
+}
+
+impl Stroke {
+    pub fn solid(size: f32, color: [f32; 4]) -> Self {
+        Self {
+            size,
+            color,
+            dashes: 0.0,
+        }
+    }
+
+    pub fn dashed(size: f32, color: [f32; 4], dashes: f32) -> Self {
+        Self {
+            size,
+            color,
+            dashes,
+        }
+    }
+
+    /// The same stroke with its size converted from logical to physical
+    /// pixels.
+    pub fn scaled(self, scale_factor: f32) -> Self {
+        Self {
+            size: self.size * scale_factor,
+            ..self
+        }
+    }
+}
+
+/// Immediate-mode drawing API shared by the HUD backends.
+///
+/// Primitives are collected over the course of a frame, then drawn over the
+/// scene and cleared by [`HudRenderer::draw`]. Positions are in world space.
+pub trait HudRenderer {
+    /// Draws a dot with a radius of `size` logical pixels.
+    fn point(&mut self, position: Vec3, size: f32, color: [f32; 4]);
+
+    /// Draws a line segment.
+    fn line(&mut self, start: Vec3, end: Vec3, stroke: Stroke);
+
+    /// Draws the ellipse `center + axis_1 * cos(t) + axis_2 * sin(t)`.
+    fn ellipse(&mut self, center: Vec3, axis_1: Vec3, axis_2: Vec3, stroke: Stroke);
+
+    /// Draws the part of a conic section between two true anomalies, which
+    /// must be within `-pi..=pi`. The conic has the given focus,
+    /// eccentricity `e` and semi-latus rectum `p`.
+    ///
+    /// `e_dir` points from the focus towards periapsis, and `p_dir` is
+    /// perpendicular to it, in the direction of travel. Dashes are spaced by
+    /// true anomaly.
+    #[allow(clippy::too_many_arguments)]
+    fn conic_arc(
+        &mut self,
+        focus: Vec3,
+        e_dir: Vec3,
+        e: f32,
+        p_dir: Vec3,
+        p: f32,
+        angles: Range<f32>,
+        stroke: Stroke,
+    );
+
+    /// Draws a whole conic section. See [`HudRenderer::conic_arc`].
+    ///
+    /// Open trajectories are infinitely long, so they are cut off at some
+    /// distance from the focus.
+    fn conic(&mut self, focus: Vec3, e_dir: Vec3, e: f32, p_dir: Vec3, p: f32, stroke: Stroke) {
+        let max_angle = if e < 1.0 {
+            TAU / 2.0
+        } else {
+            ((1.0 / OPEN_CONIC_EXTENT - 1.0) / e).acos()
+        };
+        self.conic_arc(focus, e_dir, e, p_dir, p, -max_angle..max_angle, stroke);
+    }
+
+    /// Draws text with its top left corner at the given pixel.
+    fn text(&mut self, position: Vec2, text: &str, color: [f32; 4]);
+
+    /// Draws text next to a point in the world. It is hidden while the point
+    /// is behind the camera.
+    fn label(&mut self, position: Vec3, text: &str, color: [f32; 4]);
+
+    /// Called when the window's size or scale factor changes.
+    fn resized(&mut self) {}
+
+    /// Draws all of the primitives added since the last call over the frame,
+    /// then clears them.
+    ///
+    /// `depth_view` is the depth buffer that the scene was rendered with.
+    fn draw(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        frame_view: &wgpu::TextureView,
+        depth_view: &wgpu::TextureView,
+        viewport: &Viewport,
+    );
+}
+
+/// Which implementation of [`HudRenderer`] to use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Compute,
+    Skia,
+}
+
+impl Backend {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Compute => Self::Skia,
+            Self::Skia => Self::Compute,
+        }
+    }
+
+    pub fn create(self, gfx: &GraphicsContext, viewport: &Viewport) -> Box<dyn HudRenderer> {
+        match self {
+            Self::Compute => Box::new(compute_hud::Hud::new(gfx, viewport)),
+            Self::Skia => Box::new(skia::Hud::new(gfx)),
+        }
+    }
+}
@@ -0,0 +1,303 @@
+//! Draws the bodies of the world with their orbits, and markers for their
+//! apsides, nodes and planned maneuvers.
+
+use std::f64::consts::TAU;
+
+use glam::DVec3;
+use valet::Tag;
+
+use super::{picking::OrbitPick, HudRenderer, Stroke, OPEN_CONIC_EXTENT};
+use crate::{
+    orbit::Orbit3D,
+    telemetry,
+    world::{Body, World},
+};
+
+const ORBIT_WIDTH: f32 = 1.5;
+const ORBIT_COLOR: [f32; 4] = [0.4, 0.6, 0.9, 1.0];
+/// Orbits other than the focus's are dashed, so that crossing orbits can be
+/// told apart.
+const ORBIT_DASHES: f32 = 48.0;
+const FOCUS_ORBIT_COLOR: [f32; 4] = [1.0, 0.8, 0.3, 1.0];
+
+const BODY_SIZE: f32 = 5.0;
+const BODY_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const FOCUS_BODY_SIZE: f32 = 7.0;
+const FOCUS_BODY_COLOR: [f32; 4] = [1.0, 0.8, 0.3, 1.0];
+/// The selected body is drawn over a larger halo.
+const SELECTION_SIZE: f32 = 11.0;
+const SELECTION_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.4];
+
+const MARKER_SIZE: f32 = 4.0;
+const PERIAPSIS_COLOR: [f32; 4] = [1.0, 0.0, 0.0, 0.75];
+const APOAPSIS_COLOR: [f32; 4] = [0.0, 0.0, 1.0, 0.75];
+const NODE_COLOR: [f32; 4] = [0.5, 0.5, 0.5, 0.5];
+
+const ASYMPTOTE_WIDTH: f32 = 1.0;
+const ASYMPTOTE_COLOR: [f32; 4] = [0.6, 0.6, 0.6, 0.5];
+const ASYMPTOTE_DASHES: f32 = 16.0;
+
+const MANEUVER_COLOR: [f32; 4] = [0.2, 0.9, 0.4, 1.0];
+const PICK_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.5];
+
+const LABEL_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.9];
+const APSIS_LABEL_COLOR: [f32; 4] = [0.8, 0.8, 0.8, 0.75];
+const TOOLTIP_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+/// A point of interest on an orbit, which can be hovered over for details.
+#[derive(Debug, Clone, Copy)]
+pub struct Marker {
+    pub kind: MarkerKind,
+    /// Body whose orbit the marker is on.
+    pub body: Tag<Body>,
+    /// True anomaly of the marker on the orbit.
+    pub angle: f64,
+    /// Position in the world.
+    pub position: DVec3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerKind {
+    Periapsis,
+    Apoapsis,
+    AscendingNode,
+    DescendingNode,
+    Maneuver,
+}
+
+impl MarkerKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Periapsis => "Periapsis",
+            Self::Apoapsis => "Apoapsis",
+            Self::AscendingNode => "Ascending node",
+            Self::DescendingNode => "Descending node",
+            Self::Maneuver => "Maneuver",
+        }
+    }
+
+    fn color(self) -> [f32; 4] {
+        match self {
+            Self::Periapsis => PERIAPSIS_COLOR,
+            Self::Apoapsis => APOAPSIS_COLOR,
+            Self::AscendingNode | Self::DescendingNode => NODE_COLOR,
+            Self::Maneuver => MANEUVER_COLOR,
+        }
+    }
+
+    /// Short label shown next to the marker on the focused orbit.
+    fn label(self) -> Option<&'static str> {
+        match self {
+            Self::Periapsis => Some("Pe"),
+            Self::Apoapsis => Some("Ap"),
+            _ => None,
+        }
+    }
+}
+
+/// Draws every body and its orbit onto the HUD, highlighting `focus` and
+/// `selection`.
+///
+/// Apsides are only labelled on the focus's orbit.
+///
+/// `markers` are usually the result of [`markers`].
+pub fn draw(
+    world: &World,
+    focus: Option<&Tag<Body>>,
+    selection: Option<&Tag<Body>>,
+    markers: &[Marker],
+    hud: &mut dyn HudRenderer,
+) {
+    for tag in &world.body_tags {
+        let body = world.body(tag);
+        let is_focus = focus == Some(tag);
+
+        if selection == Some(tag) {
+            hud.point(body.position().as_vec3(), SELECTION_SIZE, SELECTION_COLOR);
+        }
+        if is_focus {
+            hud.point(body.position().as_vec3(), FOCUS_BODY_SIZE, FOCUS_BODY_COLOR);
+        } else {
+            hud.point(body.position().as_vec3(), BODY_SIZE, BODY_COLOR);
+        }
+        hud.label(body.position().as_vec3(), body.name(), LABEL_COLOR);
+
+        let (parent_tag, orbit) = match (body.parent(), body.orbit()) {
+            (Some(parent), Some(orbit)) => (parent, orbit),
+            _ => continue,
+        };
+        let focus_position = world.body(parent_tag).position();
+        let orientation = orbit.orientation();
+        let shape = orbit.shape();
+        let max_angle = max_angle(world, tag).unwrap_or(TAU / 2.0);
+
+        hud.conic_arc(
+            focus_position.as_vec3(),
+            (orientation * DVec3::X).as_vec3(),
+            shape.e() as f32,
+            (orientation * DVec3::Y).as_vec3(),
+            shape.p() as f32,
+            -max_angle as f32..max_angle as f32,
+            if is_focus {
+                Stroke::solid(ORBIT_WIDTH, FOCUS_ORBIT_COLOR)
+            } else {
+                Stroke::dashed(ORBIT_WIDTH, ORBIT_COLOR, ORBIT_DASHES)
+            },
+        );
+        if let Some(exit_angle) = soi_exit_angle(world, tag) {
+            draw_escape(hud, focus_position, orbit, exit_angle, is_focus);
+        }
+    }
+
+    for marker in markers {
+        let position = marker.position.as_vec3();
+        hud.point(position, MARKER_SIZE, marker.kind.color());
+        let label = marker.kind.label().filter(|_| focus == Some(&marker.body));
+        if let Some(label) = label {
+            let altitude = telemetry::format_distance(altitude(world, marker));
+            hud.label(
+                position,
+                &format!("{} {}", label, altitude),
+                APSIS_LABEL_COLOR,
+            );
+        }
+    }
+}
+
+/// Draws the details of a marker that the cursor is over.
+pub fn draw_tooltip(world: &World, marker: &Marker, hud: &mut dyn HudRenderer) {
+    hud.label(
+        marker.position.as_vec3(),
+        &telemetry::marker_tooltip(world, marker),
+        TOOLTIP_COLOR,
+    );
+}
+
+/// Highlights a point on an orbit that is under the cursor.
+pub fn draw_pick(world: &World, pick: &OrbitPick, hud: &mut dyn HudRenderer) {
+    let body = world.body(&pick.body);
+    if let (Some(parent), Some(orbit)) = (body.parent(), body.orbit()) {
+        let position = world.body(parent).position() + orbit.position_at(pick.angle);
+        hud.point(position.as_vec3(), MARKER_SIZE, PICK_COLOR);
+    }
+}
+
+/// The apsides and nodes of every orbit, and the planned maneuvers, that lie
+/// on the drawn part of their trajectory.
+pub fn markers(world: &World) -> Vec<Marker> {
+    let mut markers = Vec::new();
+    let mut add = |world: &World, tag: &Tag<Body>, kind, angle: f64| {
+        let (focus_position, orbit) = match (world.body(tag).parent(), world.body(tag).orbit()) {
+            (Some(parent), Some(orbit)) => (world.body(parent).position(), orbit),
+            _ => return,
+        };
+        // Skip points that are not on the drawn part of the trajectory.
+        let angle = (angle + TAU / 2.0).rem_euclid(TAU) - TAU / 2.0;
+        let max_angle = max_angle(world, tag).unwrap_or(TAU / 2.0);
+        if angle.abs() <= max_angle && orbit.shape().radius_at(angle) > 0.0 {
+            markers.push(Marker {
+                kind,
+                body: *tag,
+                angle,
+                position: focus_position + orbit.position_at(angle),
+            });
+        }
+    };
+
+    for tag in &world.body_tags {
+        let orbit = match world.body(tag).orbit() {
+            Some(orbit) => orbit,
+            None => continue,
+        };
+        add(world, tag, MarkerKind::Periapsis, 0.0);
+        if orbit.shape().is_elliptic() {
+            add(world, tag, MarkerKind::Apoapsis, TAU / 2.0);
+        }
+        add(world, tag, MarkerKind::AscendingNode, -orbit.arg_pe());
+        add(
+            world,
+            tag,
+            MarkerKind::DescendingNode,
+            TAU / 2.0 - orbit.arg_pe(),
+        );
+    }
+    for node in world.maneuver_nodes() {
+        add(world, &node.body, MarkerKind::Maneuver, node.angle);
+    }
+    markers
+}
+
+/// Altitude of a marker above the surface of the body being orbited.
+pub fn altitude(world: &World, marker: &Marker) -> f64 {
+    let body = world.body(&marker.body);
+    let parent = body.parent().map(|parent| world.body(parent));
+    let radius = body
+        .orbit()
+        .map_or(0.0, |orbit| orbit.shape().radius_at(marker.angle));
+    radius - parent.map_or(0.0, |parent| parent.radius())
+}
+
+/// Largest true anomaly on the drawn part of the body's trajectory, or
+/// `None` if it isn't orbiting anything.
+///
+/// Closed orbits are drawn all the way around. Open trajectories are only
+/// drawn up to where they leave the parent's sphere of influence, or cut off
+/// far from the focus if the parent doesn't have one.
+pub fn max_angle(world: &World, tag: &Tag<Body>) -> Option<f64> {
+    let shape = world.body(tag).orbit()?.shape();
+    if shape.is_elliptic() {
+        return Some(TAU / 2.0);
+    }
+    soi_exit_angle(world, tag)
+        .or_else(|| shape.angle_at_radius(shape.p() * OPEN_CONIC_EXTENT as f64))
+}
+
+/// True anomaly at which an open trajectory leaves the sphere of influence
+/// of the body being orbited.
+fn soi_exit_angle(world: &World, tag: &Tag<Body>) -> Option<f64> {
+    let body = world.body(tag);
+    let shape = body.orbit()?.shape();
+    if shape.is_elliptic() {
+        return None;
+    }
+    let soi = world.soi_radius(body.parent()?)?;
+    shape.angle_at_radius(soi)
+}
+
+/// Draws the asymptotes of an open trajectory, out to where it leaves the
+/// sphere of influence, and labels the exit with the excess velocity.
+fn draw_escape(
+    hud: &mut dyn HudRenderer,
+    focus_position: DVec3,
+    orbit: &Orbit3D,
+    exit_angle: f64,
+    is_focus: bool,
+) {
+    let exit = focus_position + orbit.position_at(exit_angle);
+    let stroke = Stroke::dashed(ASYMPTOTE_WIDTH, ASYMPTOTE_COLOR, ASYMPTOTE_DASHES);
+
+    // Parabolas have no asymptotes; their center is at infinity.
+    if let Some(angle) = orbit
+        .asymptote_angle()
+        .filter(|_| orbit.shape().is_hyperbolic())
+    {
+        let center = focus_position + orbit.center();
+        let length = (exit - center).length();
+        for angle in [angle, -angle] {
+            let direction = orbit.orientation() * DVec3::new(angle.cos(), angle.sin(), 0.0);
+            hud.line(
+                center.as_vec3(),
+                (center + direction * length).as_vec3(),
+                stroke,
+            );
+        }
+    }
+
+    if let Some(v_inf) = orbit.v_inf().filter(|_| is_focus) {
+        hud.label(
+            exit.as_vec3(),
+            &format!("Vinf {}", telemetry::format_speed(v_inf)),
+            APSIS_LABEL_COLOR,
+        );
+    }
+}
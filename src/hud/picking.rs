@@ -0,0 +1,250 @@
+//! Finds what is under the cursor: orbit markers, points on orbits, or
+//! bodies.
+//!
+//! Markers and orbits are picked in screen space, using the same
+//! trajectories that [`orbits::draw`] draws, so it doesn't depend on which
+//! HUD backend is in use. Bodies are picked by casting a ray against their
+//! spheres.
+
+use std::f64::consts::TAU;
+
+use glam::Vec2;
+use valet::Tag;
+
+use super::{
+    orbits::{self, Marker},
+    View,
+};
+use crate::{
+    math::Ray,
+    world::{Body, World},
+};
+
+/// Number of straight segments that each orbit is split into when searching
+/// for the closest point.
+const PICK_SEGMENTS: usize = 512;
+
+/// A point on a body's orbit.
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitPick {
+    pub body: Tag<Body>,
+    /// True anomaly of the point on the orbit.
+    pub angle: f64,
+    /// Distance from the picked position on the screen, in pixels.
+    pub distance: f32,
+}
+
+/// The marker closest to `cursor`, if any are within `max_distance` pixels
+/// of it.
+pub fn pick_marker<'a>(
+    markers: &'a [Marker],
+    view: &View,
+    cursor: Vec2,
+    max_distance: f32,
+) -> Option<&'a Marker> {
+    markers
+        .iter()
+        .filter_map(|marker| {
+            let pixel = view.to_pixel(marker.position.as_vec3())?;
+            Some((marker, pixel.distance(cursor)))
+        })
+        .filter(|&(_, distance)| distance <= max_distance)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(marker, _)| marker)
+}
+
+/// The point on any orbit that is closest to `cursor` on the screen, if one
+/// is within `max_distance` pixels of it.
+pub fn pick_orbit(
+    world: &World,
+    view: &View,
+    cursor: Vec2,
+    max_distance: f32,
+) -> Option<OrbitPick> {
+    let mut best: Option<OrbitPick> = None;
+    for tag in &world.body_tags {
+        let body = world.body(tag);
+        let (focus_position, orbit) = match (body.parent(), body.orbit()) {
+            (Some(parent), Some(orbit)) => (world.body(parent).position(), orbit),
+            _ => continue,
+        };
+        let max_angle = orbits::max_angle(world, tag).unwrap_or(TAU / 2.0);
+        let angle_at = |i: usize| -max_angle + 2.0 * max_angle * i as f64 / PICK_SEGMENTS as f64;
+        let pixel_at =
+            |angle: f64| view.to_pixel((focus_position + orbit.position_at(angle)).as_vec3());
+
+        let mut previous = pixel_at(angle_at(0));
+        for i in 1..=PICK_SEGMENTS {
+            let current = pixel_at(angle_at(i));
+            if let (Some(a), Some(b)) = (previous, current) {
+                let (t, distance) = closest_on_segment(a, b, cursor);
+                if distance <= best.map_or(max_distance, |best| best.distance) {
+                    let angle = angle_at(i - 1) + (angle_at(i) - angle_at(i - 1)) * t as f64;
+                    best = Some(OrbitPick {
+                        body: *tag,
+                        angle,
+                        distance,
+                    });
+                }
+            }
+            previous = current;
+        }
+    }
+    best
+}
+
+/// The body whose surface the ray hits first, if any.
+pub fn pick_body(world: &World, ray: &Ray) -> Option<Tag<Body>> {
+    world
+        .body_tags
+        .iter()
+        .filter_map(|tag| {
+            let body = world.body(tag);
+            let distance = ray.intersect_sphere(body.position(), body.radius())?;
+            Some((*tag, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(tag, _)| tag)
+}
+
+/// Fraction of the way along the segment from `a` to `b` of the point
+/// closest to `point`, and the distance to it.
+fn closest_on_segment(a: Vec2, b: Vec2, point: Vec2) -> (f32, f32) {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    let t = if length_squared > 0.0 {
+        ((point - a).dot(ab) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (t, (a + ab * t).distance(point))
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{DVec3, Mat4, UVec2, Vec3};
+
+    use super::*;
+    use crate::hud::orbits::MarkerKind;
+
+    /// A 200×200 view with a 90° field of view, from `eye` towards
+    /// `target`.
+    fn view(eye: DVec3, target: DVec3, up: DVec3) -> View {
+        let fov = (TAU / 4.0) as f32;
+        let projection = Mat4::perspective_rh(fov, 1.0, 1.0, 1.0e12);
+        let camera = Mat4::look_at_rh(eye.as_vec3(), target.as_vec3(), up.as_vec3());
+        View {
+            view_proj: projection * camera,
+            eye: eye.as_vec3(),
+            forward: (target - eye).normalize().as_vec3(),
+            up: up.as_vec3(),
+            x_fov: fov,
+            y_fov: fov,
+            dims: UVec2::splat(200),
+        }
+    }
+
+    fn named(world: &World, name: &str) -> Tag<Body> {
+        *world
+            .body_tags
+            .iter()
+            .find(|tag| world.body(tag).name() == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn picks_the_closest_marker_in_range() {
+        let world = World::new();
+        let body = world.body_tags[0];
+        let marker = |kind, position: Vec3| Marker {
+            kind,
+            body,
+            angle: 0.0,
+            position: position.as_dvec3(),
+        };
+        // From the origin along +Y, a unit offset at distance 10 is 10
+        // pixels on screen.
+        let markers = [
+            marker(MarkerKind::Periapsis, Vec3::new(0.0, 10.0, 0.0)),
+            marker(MarkerKind::Apoapsis, Vec3::new(1.0, 10.0, 0.0)),
+            marker(MarkerKind::Maneuver, Vec3::new(0.0, -10.0, 0.0)),
+        ];
+        let view = view(DVec3::ZERO, DVec3::Y, DVec3::Z);
+
+        let pick = |cursor: Vec2, max_distance| {
+            pick_marker(&markers, &view, cursor, max_distance).map(|marker| marker.kind)
+        };
+        assert_eq!(
+            pick(Vec2::new(101.0, 100.0), 5.0),
+            Some(MarkerKind::Periapsis)
+        );
+        assert_eq!(
+            pick(Vec2::new(108.0, 102.0), 5.0),
+            Some(MarkerKind::Apoapsis)
+        );
+        assert_eq!(pick(Vec2::new(100.0, 110.0), 5.0), None);
+        assert_eq!(
+            pick(Vec2::new(100.0, 110.0), 11.0),
+            Some(MarkerKind::Periapsis)
+        );
+        // The marker behind the eye can't be picked at all.
+        assert!(pick_marker(&markers[2..], &view, Vec2::new(100.0, 100.0), 1000.0).is_none());
+    }
+
+    #[test]
+    fn picks_points_on_orbits() {
+        // Looking down on a point of the Moon's orbit from close by, so that
+        // no other orbit is on the screen.
+        let world = World::new();
+        let moon = named(&world, "Moon");
+        let orbit = world.body(&moon).orbit().unwrap();
+        let earth = world.body(world.body(&moon).parent().unwrap()).position();
+        let angle = 1.0;
+        let target = earth + orbit.position_at(angle);
+        let normal = orbit.orientation() * DVec3::Z;
+        let view = view(target + normal * 1.0e7, target, orbit.position_at(angle));
+
+        let center = Vec2::splat(100.0);
+        let pick = pick_orbit(&world, &view, center, 5.0).unwrap();
+        assert_eq!(pick.body, moon);
+        assert!((pick.angle - angle).abs() < 1e-3, "picked {}", pick.angle);
+        assert!(pick.distance < 1.0);
+
+        // The orbit crosses the screen from side to side, since `up` points
+        // away from its focus.
+        let off_orbit = center + Vec2::new(0.0, 20.0);
+        assert!(pick_orbit(&world, &view, off_orbit, 10.0).is_none());
+        let pick = pick_orbit(&world, &view, off_orbit, 30.0).unwrap();
+        assert_eq!(pick.body, moon);
+        assert!((pick.distance - 20.0).abs() < 1.0, "{}", pick.distance);
+    }
+
+    #[test]
+    fn picks_the_nearest_body_along_the_ray() {
+        let world = World::new();
+        let sun = named(&world, "Sun");
+        let earth = named(&world, "Earth");
+        let sun_position = world.body(&sun).position();
+        let earth_position = world.body(&earth).position();
+        let direction = (sun_position - earth_position).normalize();
+
+        // From beyond the Earth, looking through it towards the Sun.
+        let ray = Ray {
+            origin: earth_position - direction * 2.0e10,
+            direction,
+        };
+        assert_eq!(pick_body(&world, &ray), Some(earth));
+        // Between them, looking at the Sun with the Earth behind.
+        let ray = Ray {
+            origin: 0.5 * (earth_position + sun_position),
+            direction,
+        };
+        assert_eq!(pick_body(&world, &ray), Some(sun));
+        // Looking out of the plane of the system.
+        let ray = Ray {
+            origin: ray.origin,
+            direction: DVec3::Z,
+        };
+        assert_eq!(pick_body(&world, &ray), None);
+    }
+}
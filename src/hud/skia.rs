@@ -1,48 +1,37 @@
-use std::{
-    f32::consts as f32,
-    f64::consts::{self as f64, TAU},
-    num::NonZeroU32,
-};
+//! HUD backend that rasterizes on the CPU with [`Canvas`], then draws the
+//! result over the frame as a texture.
 
-use glam::{DVec3, Vec2, Vec4Swizzles};
-use tiny_skia::{Color, Paint, PathBuilder, Pixmap, Stroke, Transform};
+use std::{num::NonZeroU32, ops::Range};
 
-use crate::{
-    orbit::{Orbit2D, Orbit3D, State3D},
-    time::SimInstant,
-    viewport::Viewport,
-    GraphicsContext,
-};
+use glam::{UVec2, Vec2, Vec3};
+
+use super::{canvas::Canvas, HudRenderer, Stroke, View};
+use crate::{viewport::Viewport, GraphicsContext};
 
 pub struct Hud {
     gfx: GraphicsContext,
-    pixmap: Pixmap,
+    canvas: Canvas,
     texture: wgpu::Texture,
     view: wgpu::TextureView,
     sampler: wgpu::Sampler,
     bind_group_layout: wgpu::BindGroupLayout,
     bind_group: wgpu::BindGroup,
     pipeline: wgpu::RenderPipeline,
-    pub orbit: Orbit3D,
-    pub state: Option<State3D>,
 }
 
 impl Hud {
     pub fn new(gfx: &GraphicsContext) -> Self {
-        let orbit = Orbit3D::new(
-            Orbit2D::new(0.5, 2.0, SimInstant::epoch(), 3.0),
-            f64::TAU / 4.0,
-            f64::TAU / 8.0,
-            0.0,
-        );
-
-        let size = gfx.window.inner_size();
-        let pixmap = Pixmap::new(size.width, size.height).unwrap();
+        let size = gfx.size();
+        // The window has no size while it is minimized, so start with a
+        // single pixel until it is resized.
+        let size = UVec2::new(size.width, size.height).max(UVec2::ONE);
+        let mut canvas = Canvas::new(size).unwrap();
+        canvas.set_scale_factor(gfx.scale_factor() as f32);
         let texture = gfx.device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Hud::texture"),
             size: wgpu::Extent3d {
-                width: size.width,
-                height: size.height,
+                width: size.x,
+                height: size.y,
                 ..Default::default()
             },
             mip_level_count: 1,
@@ -98,7 +87,7 @@ impl Hud {
 
         let shader_module = gfx
             .device
-            .create_shader_module(&wgpu::include_wgsl!("hud.wgsl"));
+            .create_shader_module(&wgpu::include_wgsl!("skia.wgsl"));
 
         let pipeline_layout = gfx
             .device
@@ -126,7 +115,7 @@ impl Hud {
                     entry_point: "fs_main",
                     targets: &[wgpu::ColorTargetState {
                         format: gfx.render_format,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
                         write_mask: wgpu::ColorWrites::all(),
                     }],
                 }),
@@ -135,21 +124,61 @@ impl Hud {
 
         Self {
             gfx: gfx.clone(),
-            pixmap,
+            canvas,
             texture,
             view,
             sampler,
             bind_group_layout,
             bind_group,
             pipeline,
-            orbit,
-            state: None,
         }
     }
+}
+
+impl HudRenderer for Hud {
+    fn point(&mut self, position: Vec3, size: f32, color: [f32; 4]) {
+        self.canvas.point(position, size, color);
+    }
+
+    fn line(&mut self, start: Vec3, end: Vec3, stroke: Stroke) {
+        self.canvas.line(start, end, stroke);
+    }
+
+    fn ellipse(&mut self, center: Vec3, axis_1: Vec3, axis_2: Vec3, stroke: Stroke) {
+        self.canvas.ellipse(center, axis_1, axis_2, stroke);
+    }
 
-    pub fn resized(&mut self) {
-        let size = self.gfx.window.inner_size();
-        self.pixmap = Pixmap::new(size.width, size.height).unwrap();
+    fn conic_arc(
+        &mut self,
+        focus: Vec3,
+        e_dir: Vec3,
+        e: f32,
+        p_dir: Vec3,
+        p: f32,
+        angles: Range<f32>,
+        stroke: Stroke,
+    ) {
+        self.canvas
+            .conic_arc(focus, e_dir, e, p_dir, p, angles, stroke);
+    }
+
+    fn text(&mut self, position: Vec2, text: &str, color: [f32; 4]) {
+        self.canvas.text(position, text, color);
+    }
+
+    fn label(&mut self, position: Vec3, text: &str, color: [f32; 4]) {
+        self.canvas.label(position, text, color);
+    }
+
+    fn resized(&mut self) {
+        let size = self.gfx.size();
+        // Keep the old canvas while the window is minimized.
+        let mut canvas = match Canvas::new(UVec2::new(size.width, size.height)) {
+            Some(canvas) => canvas,
+            None => return,
+        };
+        canvas.set_scale_factor(self.gfx.scale_factor() as f32);
+        self.canvas = canvas;
         self.texture = self.gfx.device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Hud::texture"),
             size: wgpu::Extent3d {
@@ -184,147 +213,16 @@ impl Hud {
             });
     }
 
-    pub fn draw(
+    fn draw(
         &mut self,
         encoder: &mut wgpu::CommandEncoder,
         frame_view: &wgpu::TextureView,
+        _depth_view: &wgpu::TextureView,
         viewport: &Viewport,
     ) {
-        let view_proj = viewport.view_proj();
-
-        let width = self.pixmap.width() as f32;
-        let height = self.pixmap.height() as f32;
-        let scale = 0.5 * Vec2::new(width, -height);
-        let translate = 0.5 * Vec2::new(width, height);
-
-        let map_3d = |point: DVec3| {
-            let clip = view_proj * point.as_vec3().extend(1.0);
-            let normalized = clip.xy() / clip.w;
-            let screen = normalized * scale + translate;
-            screen
-        };
-
-        self.pixmap.fill(Color::TRANSPARENT);
-
-        let mut stroke = Stroke::default();
-        let mut paint = Paint::default();
-        stroke.width = 2.0;
-        paint.anti_alias = true;
-
-        let focus = DVec3::new(0.0, 0.0, 1.5);
-        let center = (self.orbit.a_vector()
-            * (self.orbit.shape().rp() / self.orbit.shape().a() - 1.0))
-            + focus;
-        let a = self.orbit.a_vector();
-        let b = self.orbit.b_vector();
-
-        dbg!(&self.orbit);
-
-        let mut orbit_path = PathBuilder::new();
-        for i in 0..100 {
-            let k = (i as f64) / 100.0 * TAU;
-            let point = map_3d(center + a * k.cos() + b * k.sin());
-
-            if i == 0 {
-                orbit_path.move_to(point.x, point.y);
-            } else {
-                orbit_path.line_to(point.x, point.y);
-            }
-        }
-        orbit_path.close();
-
-        if let Some(path) = orbit_path.finish() {
-            paint.set_color_rgba8(127, 96, 64, 192);
-            self.pixmap
-                .stroke_path(&path, &paint, &stroke, Transform::identity(), None);
-        }
-
-        let node_a = map_3d(focus + self.orbit.position_at(-self.orbit.arg_pe()));
-        let node_b = map_3d(focus + self.orbit.position_at(TAU / 2.0 - self.orbit.arg_pe()));
-        let mut node_path = PathBuilder::new();
-        node_path.move_to(node_a.x, node_a.y);
-        node_path.line_to(node_b.x, node_b.y);
-
-        paint.set_color_rgba8(127, 127, 127, 127);
-        self.pixmap.stroke_path(
-            &node_path.finish().unwrap(),
-            &paint,
-            &stroke,
-            Transform::identity(),
-            None,
-        );
-
-        let peri = map_3d(focus + self.orbit.position_at(0.0));
-        let peri_path = PathBuilder::from_circle(peri.x, peri.y, 5.0).unwrap();
-        paint.set_color_rgba8(255, 0, 0, 192);
-        self.pixmap.fill_path(
-            &peri_path,
-            &paint,
-            Default::default(),
-            Transform::identity(),
-            None,
-        );
-
-        let apo = map_3d(focus + self.orbit.position_at(TAU / 2.0));
-        let apo_path = PathBuilder::from_circle(apo.x, apo.y, 5.0).unwrap();
-        paint.set_color_rgba8(0, 0, 255, 192);
-        self.pixmap.fill_path(
-            &apo_path,
-            &paint,
-            Default::default(),
-            Transform::identity(),
-            None,
-        );
-
-        if let Some(state) = &self.state {
-            let position = focus + state.position;
-
-            let base = map_3d(position);
-            let tip = map_3d(position + state.velocity);
-            let mut vel_path = PathBuilder::new();
-            vel_path.move_to(base.x, base.y);
-            vel_path.line_to(tip.x, tip.y);
-
-            paint.set_color_rgba8(0, 255, 0, 255);
-            self.pixmap.stroke_path(
-                &vel_path.finish().unwrap(),
-                &paint,
-                &stroke,
-                Transform::identity(),
-                None,
-            );
-
-            let accel = state.position.cross(state.velocity).normalize();
-            let base = map_3d(position);
-            let tip = map_3d(position + accel);
-            let mut accel_path = PathBuilder::new();
-            accel_path.move_to(base.x, base.y);
-            accel_path.line_to(tip.x, tip.y);
-
-            paint.set_color_rgba8(0, 0, 255, 255);
-            self.pixmap.stroke_path(
-                &accel_path.finish().unwrap(),
-                &paint,
-                &stroke,
-                Transform::identity(),
-                None,
-            );
-        }
-
-        // let center = map_3d(DVec3::ZERO);
-        // let radius = map_3d(DVec3::new(1.5e11, 0.0, 0.0));
-        // let mut radius_path = PathBuilder::new();
-        // radius_path.move_to(center.x, center.y);
-        // radius_path.line_to(radius.x, radius.y);
-
-        // paint.set_color_rgba8(127, 127, 127, 127);
-        // self.pixmap.stroke_path(
-        //     &radius_path.finish().unwrap(),
-        //     &paint,
-        //     &stroke,
-        //     Transform::identity(),
-        //     None,
-        // );
+        let size = self.canvas.size();
+        self.canvas.rasterize(&View::new(viewport, size));
+        let pixmap = self.canvas.pixmap();
 
         self.gfx.queue.write_texture(
             wgpu::ImageCopyTexture {
@@ -333,15 +231,15 @@ impl Hud {
                 origin: wgpu::Origin3d::ZERO,
                 aspect: wgpu::TextureAspect::All,
             },
-            self.pixmap.data(),
+            pixmap.data(),
             wgpu::ImageDataLayout {
                 offset: 0,
-                bytes_per_row: NonZeroU32::new(self.pixmap.width() * 4),
-                rows_per_image: NonZeroU32::new(self.pixmap.height()),
+                bytes_per_row: NonZeroU32::new(size.x * 4),
+                rows_per_image: NonZeroU32::new(size.y),
             },
             wgpu::Extent3d {
-                width: self.pixmap.width(),
-                height: self.pixmap.height(),
+                width: size.x,
+                height: size.y,
                 ..Default::default()
             },
         );
@@ -0,0 +1,78 @@
+//! Projection between the world and HUD pixels.
+
+use glam::{Mat4, UVec2, Vec2, Vec3, Vec4, Vec4Swizzles};
+
+use crate::viewport::Viewport;
+
+/// Camera parameters as seen by the HUD: the `Viewport` uniform, plus the
+/// dimensions of the output texture.
+#[derive(Debug, Clone, Copy)]
+pub struct View {
+    pub view_proj: Mat4,
+    pub eye: Vec3,
+    pub forward: Vec3,
+    pub up: Vec3,
+    pub x_fov: f32,
+    pub y_fov: f32,
+    pub dims: UVec2,
+}
+
+impl View {
+    pub fn new(viewport: &Viewport, dims: UVec2) -> Self {
+        Self {
+            view_proj: viewport.view_proj(),
+            eye: viewport.camera_pos(),
+            forward: viewport.forward(),
+            up: viewport.up(),
+            x_fov: viewport.x_fov(),
+            y_fov: viewport.y_fov(),
+            dims,
+        }
+    }
+
+    /// Direction of the ray from the eye through the center of the given
+    /// pixel.
+    pub fn eye_ray(&self, pixel: UVec2) -> Vec3 {
+        let forward = self.forward.normalize();
+        let up = self.up.normalize();
+        let right = forward.cross(up).normalize();
+        let dims = self.dims.as_vec2();
+        let dx = (self.x_fov * 0.5).tan() * 2.0 / dims.x;
+        let dy = (self.y_fov * 0.5).tan() * 2.0 / dims.y;
+
+        // Through the center of the pixel, matching the projection.
+        (forward + dx * (pixel.x as f32 + 0.5 - dims.x * 0.5) * right
+            - dy * (pixel.y as f32 + 0.5 - dims.y * 0.5) * up)
+            .normalize()
+    }
+
+    pub fn clip(&self, point: Vec3) -> Vec4 {
+        self.view_proj * point.extend(1.0)
+    }
+
+    /// Converts clip coordinates to pixel coordinates, with the origin in the
+    /// top left corner.
+    pub fn clip_to_pixel(&self, clip: Vec4) -> Vec2 {
+        let normalized = clip.xy() / clip.w;
+        Vec2::new(normalized.x + 1.0, 1.0 - normalized.y) * 0.5 * self.dims.as_vec2()
+    }
+
+    /// Pixel coordinates of a point, or `None` if it is behind the eye.
+    pub fn to_pixel(&self, point: Vec3) -> Option<Vec2> {
+        let clip = self.clip(point);
+        if clip.w > 0.0 {
+            Some(self.clip_to_pixel(clip))
+        } else {
+            None
+        }
+    }
+
+    /// Distance between two points on the screen, in pixels.
+    pub fn screen_distance(&self, a: Vec3, b: Vec3) -> f32 {
+        let p_a = self.clip(a);
+        let p_b = self.clip(b);
+        let n_a = p_a.xy() / p_a.w;
+        let n_b = p_b.xy() / p_b.w;
+        ((n_a - n_b) * self.dims.as_vec2() * 0.5).length()
+    }
+}
@@ -0,0 +1,540 @@
+//! glTF 2.0 meshes, from `.gltf` files with embedded or external buffers and
+//! from binary `.glb` files.
+//!
+//! The triangles of every mesh in the default scene are merged into one
+//! mesh, with the transforms of their nodes applied. Vertex colors are
+//! multiplied by the base color factor of each primitive's material, but
+//! textures are ignored.
+//!
+//! The document itself is parsed and validated by the `gltf` crate. Buffer
+//! data comes from untrusted files, so accessors are read here, with every
+//! range checked.
+
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, bail, ensure, Context};
+use glam::{Mat3, Mat4, Vec2, Vec3, Vec4};
+use gltf::{
+    accessor::{DataType, Dimensions},
+    buffer::Source,
+    mesh::Mode,
+    Accessor, Gltf, Node, Primitive, Semantic,
+};
+
+use crate::model::MeshData;
+
+/// How deep node hierarchies may go, which also stops cycles.
+const MAX_DEPTH: usize = 64;
+
+/// Most elements that an accessor may have, which keeps indices exact as
+/// `f32`s.
+const MAX_COUNT: usize = 1 << 24;
+
+/// Parses a `.gltf` file, loading external buffers relative to `directory`.
+pub fn parse(text: &str, directory: &Path) -> anyhow::Result<MeshData> {
+    Document::new(from_slice(text.as_bytes())?, directory)?.mesh()
+}
+
+/// Parses a `.glb` file, loading external buffers relative to `directory`.
+pub fn parse_binary(bytes: &[u8], directory: &Path) -> anyhow::Result<MeshData> {
+    Document::new(from_slice(bytes)?, directory)?.mesh()
+}
+
+fn from_slice(bytes: &[u8]) -> anyhow::Result<Gltf> {
+    // The `gltf` crate panics on a GLB length that doesn't cover the header.
+    if let Some(length) = bytes.strip_prefix(b"glTF").and_then(|rest| rest.get(4..8)) {
+        let length = u32::from_le_bytes(length.try_into().unwrap());
+        ensure!(length >= 12, "truncated GLB header");
+    }
+    Ok(Gltf::from_slice(bytes)?)
+}
+
+struct Document {
+    gltf: gltf::Document,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl Document {
+    fn new(gltf: Gltf, directory: &Path) -> anyhow::Result<Self> {
+        let Gltf { document, mut blob } = gltf;
+        let buffers = document
+            .buffers()
+            .map(|buffer| {
+                let i = buffer.index();
+                let data = match buffer.source() {
+                    Source::Uri(uri) if uri.starts_with("data:") => {
+                        let (_, data) = uri
+                            .split_once(";base64,")
+                            .ok_or_else(|| anyhow!("data URI isn't base64"))?;
+                        base64::decode(data).context("invalid base64 data")?
+                    }
+                    Source::Uri(uri) => {
+                        let path = directory.join(uri);
+                        fs::read(&path)
+                            .with_context(|| format!("failed to read {}", path.display()))?
+                    }
+                    // Only the first buffer may refer to the GLB binary chunk.
+                    Source::Bin if i == 0 => blob
+                        .take()
+                        .ok_or_else(|| anyhow!("missing GLB binary chunk"))?,
+                    Source::Bin => bail!("buffer {} has no URI", i),
+                };
+                ensure!(data.len() >= buffer.length(), "buffer {} is too short", i);
+                Ok(data)
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            gltf: document,
+            buffers,
+        })
+    }
+
+    fn mesh(&self) -> anyhow::Result<MeshData> {
+        let mut mesh = MeshData::default();
+        match self
+            .gltf
+            .default_scene()
+            .or_else(|| self.gltf.scenes().next())
+        {
+            Some(scene) => {
+                for node in scene.nodes() {
+                    self.add_node(&mut mesh, node, Mat4::IDENTITY, 0)?;
+                }
+            }
+            // Without a scene, there's nothing to say where meshes go.
+            None => {
+                for primitive in self.gltf.meshes().flat_map(|mesh| mesh.primitives()) {
+                    self.add_primitive(&mut mesh, primitive, Mat4::IDENTITY)?;
+                }
+            }
+        }
+        Ok(mesh)
+    }
+
+    fn add_node(
+        &self,
+        mesh: &mut MeshData,
+        node: Node,
+        parent: Mat4,
+        depth: usize,
+    ) -> anyhow::Result<()> {
+        ensure!(depth < MAX_DEPTH, "node hierarchy is too deep");
+        let transform = parent * Mat4::from_cols_array_2d(&node.transform().matrix());
+        if let Some(node_mesh) = node.mesh() {
+            for primitive in node_mesh.primitives() {
+                self.add_primitive(mesh, primitive, transform)?;
+            }
+        }
+        for child in node.children() {
+            self.add_node(mesh, child, transform, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    fn add_primitive(
+        &self,
+        mesh: &mut MeshData,
+        primitive: Primitive,
+        transform: Mat4,
+    ) -> anyhow::Result<()> {
+        if primitive.mode() != Mode::Triangles {
+            return Ok(());
+        }
+        let normal_matrix = Mat3::from_mat4(transform).inverse().transpose();
+        // Mirroring transforms turn the triangles inside out.
+        let mirrored = transform.determinant() < 0.0;
+
+        let positions = match primitive.get(&Semantic::Positions) {
+            Some(accessor) => self.accessor(accessor)?,
+            None => bail!("primitive without positions"),
+        };
+        let count = positions.len();
+        let attribute = |semantic, name| {
+            primitive
+                .get(&semantic)
+                .map(|accessor| {
+                    let elements = self.accessor(accessor)?;
+                    ensure!(
+                        elements.len() == count,
+                        "{} has {} elements, but POSITION has {}",
+                        name,
+                        elements.len(),
+                        count
+                    );
+                    Ok(elements)
+                })
+                .transpose()
+        };
+        let normals = attribute(Semantic::Normals, "NORMAL")?;
+        let colors = attribute(Semantic::Colors(0), "COLOR_0")?;
+        let uvs = attribute(Semantic::TexCoords(0), "TEXCOORD_0")?;
+        let [r, g, b, _] = primitive
+            .material()
+            .pbr_metallic_roughness()
+            .base_color_factor();
+        let base_color = Vec3::new(r, g, b);
+
+        let start = mesh.positions.len() as u32;
+        for i in 0..count {
+            mesh.positions
+                .push(transform.transform_point3(positions[i].truncate()));
+            mesh.normals.push(match &normals {
+                Some(normals) => (normal_matrix * normals[i].truncate()).normalize_or_zero(),
+                None => Vec3::ZERO,
+            });
+            let color = colors.as_ref().map_or(Vec4::ONE, |colors| colors[i]);
+            mesh.colors.push(color.truncate() * base_color);
+            mesh.uvs.push(
+                uvs.as_ref()
+                    .map_or(Vec2::ZERO, |uvs| uvs[i].truncate().truncate()),
+            );
+        }
+
+        let indices: Vec<u32> = match primitive.indices() {
+            Some(accessor) => self
+                .accessor(accessor)?
+                .iter()
+                .map(|value| value.x as u32)
+                .collect(),
+            None => (0..count as u32).collect(),
+        };
+        ensure!(
+            indices.iter().all(|&index| (index as usize) < count),
+            "vertex index out of range"
+        );
+        for tri in indices.chunks_exact(3) {
+            let (a, b, c) = (start + tri[0], start + tri[1], start + tri[2]);
+            if mirrored {
+                mesh.indices.extend([a, c, b]);
+            } else {
+                mesh.indices.extend([a, b, c]);
+            }
+        }
+        Ok(())
+    }
+
+    /// The elements of an accessor, with missing components left at zero.
+    /// Integers are kept as they are unless the accessor is normalized.
+    /// Indices are read through this too, which is exact up to 2^24.
+    fn accessor(&self, accessor: Accessor) -> anyhow::Result<Vec<Vec4>> {
+        let index = accessor.index();
+        ensure!(
+            accessor.sparse().is_none(),
+            "sparse accessors aren't supported"
+        );
+        let count = accessor.count();
+        ensure!(count <= MAX_COUNT, "accessor {} is too large", index);
+        let components = match accessor.dimensions() {
+            Dimensions::Scalar => 1,
+            Dimensions::Vec2 => 2,
+            Dimensions::Vec3 => 3,
+            Dimensions::Vec4 => 4,
+            other => bail!("unsupported accessor type {:?}", other),
+        };
+        let component_type = accessor.data_type();
+        let size = component_type.size();
+        let normalized = accessor.normalized();
+
+        let view = accessor
+            .view()
+            .ok_or_else(|| anyhow!("accessor {} has no buffer view", index))?;
+        let buffer = &self.buffers[view.buffer().index()];
+        let data = view
+            .offset()
+            .checked_add(view.length())
+            .and_then(|end| buffer.get(view.offset()..end))
+            .ok_or_else(|| anyhow!("buffer view {} out of range", view.index()))?;
+        let offset = accessor.offset();
+        let element_size = components * size;
+        let stride = view.stride().unwrap_or(element_size);
+        if count > 0 {
+            let end = (count - 1)
+                .checked_mul(stride)
+                .and_then(|last| last.checked_add(offset))
+                .and_then(|last| last.checked_add(element_size));
+            ensure!(
+                end.is_some_and(|end| end <= data.len()),
+                "accessor {} out of range",
+                index
+            );
+        }
+
+        let read = |at: usize| -> f32 {
+            let bytes = &data[at..at + size];
+            match component_type {
+                DataType::I8 => {
+                    let value = bytes[0] as i8 as f32;
+                    if normalized {
+                        (value / 127.0).max(-1.0)
+                    } else {
+                        value
+                    }
+                }
+                DataType::U8 => {
+                    let value = bytes[0] as f32;
+                    if normalized {
+                        value / 255.0
+                    } else {
+                        value
+                    }
+                }
+                DataType::I16 => {
+                    let value = i16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+                    if normalized {
+                        (value / 32767.0).max(-1.0)
+                    } else {
+                        value
+                    }
+                }
+                DataType::U16 => {
+                    let value = u16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+                    if normalized {
+                        value / 65535.0
+                    } else {
+                        value
+                    }
+                }
+                DataType::U32 => u32::from_le_bytes(bytes.try_into().unwrap()) as f32,
+                DataType::F32 => f32::from_le_bytes(bytes.try_into().unwrap()),
+            }
+        };
+        Ok((0..count)
+            .map(|i| {
+                let start = offset + i * stride;
+                let mut element = Vec4::ZERO;
+                for c in 0..components {
+                    element[c] = read(start + c * size);
+                }
+                // Colors without alpha are opaque.
+                if components == 3 {
+                    element.w = 1.0;
+                }
+                element
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A triangle with a material, moved up by its node. `BUFFER` is
+    /// replaced by the buffer's URI member, if any.
+    const DOCUMENT: &str = r#"{
+        "asset": {"version": "2.0"},
+        "scene": 0,
+        "scenes": [{"nodes": [0]}],
+        "nodes": [{"mesh": 0, "translation": [0, 0, 2]}],
+        "meshes": [{"primitives": [
+            {"attributes": {"POSITION": 0}, "indices": 1, "material": 0}
+        ]}],
+        "materials": [{"pbrMetallicRoughness": {"baseColorFactor": [1, 0.5, 0.25, 1]}}],
+        "buffers": [{BUFFER "byteLength": 44}],
+        "bufferViews": [
+            {"buffer": 0, "byteLength": 36},
+            {"buffer": 0, "byteOffset": 36, "byteLength": 6}
+        ],
+        "accessors": [
+            {
+                "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                "min": [0, 0, 0], "max": [1, 1, 0]
+            },
+            {"bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR"}
+        ]
+    }"#;
+
+    fn buffer(indices: [u16; 3]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for position in [Vec3::ZERO, Vec3::X, Vec3::Y] {
+            for component in position.to_array() {
+                bytes.extend(component.to_le_bytes());
+            }
+        }
+        for index in indices {
+            bytes.extend(index.to_le_bytes());
+        }
+        bytes.extend([0, 0]);
+        bytes
+    }
+
+    fn embedded(buffer: &[u8]) -> String {
+        let uri = format!(
+            r#""uri": "data:application/octet-stream;base64,{}","#,
+            base64::encode(buffer)
+        );
+        DOCUMENT.replace("BUFFER", &uri)
+    }
+
+    fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+        const JSON_CHUNK: u32 = 0x4e4f_534a;
+        const BIN_CHUNK: u32 = 0x004e_4942;
+
+        let mut json = json.as_bytes().to_vec();
+        json.resize(json.len().div_ceil(4) * 4, b' ');
+        let mut bytes = b"glTF".to_vec();
+        bytes.extend(2u32.to_le_bytes());
+        bytes.extend(((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+        for (chunk_type, data) in [(JSON_CHUNK, &json[..]), (BIN_CHUNK, bin)] {
+            bytes.extend((data.len() as u32).to_le_bytes());
+            bytes.extend(chunk_type.to_le_bytes());
+            bytes.extend(data);
+        }
+        bytes
+    }
+
+    fn error(text: &str) -> String {
+        format!("{:#}", parse(text, Path::new("")).unwrap_err())
+    }
+
+    fn assert_triangle(mesh: &MeshData) {
+        let offset = Vec3::new(0.0, 0.0, 2.0);
+        assert_eq!(mesh.positions, [offset, Vec3::X + offset, Vec3::Y + offset]);
+        assert_eq!(mesh.indices, [0, 1, 2]);
+        assert_eq!(mesh.colors, [Vec3::new(1.0, 0.5, 0.25); 3]);
+        assert_eq!(mesh.normals, [Vec3::ZERO; 3]);
+        assert_eq!(mesh.uvs, [Vec2::ZERO; 3]);
+    }
+
+    #[test]
+    fn parses_embedded_buffers() {
+        let mesh = parse(&embedded(&buffer([0, 1, 2])), Path::new("")).unwrap();
+        assert_triangle(&mesh);
+    }
+
+    #[test]
+    fn parses_external_buffers() {
+        let directory = std::env::temp_dir().join(format!("gltf-test-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("triangle.bin"), buffer([0, 1, 2])).unwrap();
+        let text = DOCUMENT.replace("BUFFER", r#""uri": "triangle.bin","#);
+        let mesh = parse(&text, &directory);
+        fs::remove_dir_all(&directory).unwrap();
+        assert_triangle(&mesh.unwrap());
+
+        let error = format!("{:#}", parse(&text, Path::new("missing")).unwrap_err());
+        assert!(
+            error.starts_with("failed to read missing/triangle.bin"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn parses_binary_files() {
+        let bytes = glb(&DOCUMENT.replace("BUFFER", ""), &buffer([0, 1, 2]));
+        let mesh = parse_binary(&bytes, Path::new("")).unwrap();
+        assert_triangle(&mesh);
+    }
+
+    #[test]
+    fn mirrored_nodes_keep_their_winding() {
+        let text = embedded(&buffer([0, 1, 2])).replace(
+            r#""translation": [0, 0, 2]"#,
+            r#""children": [1]}, {"mesh": 0, "scale": [-1, 1, 1]"#,
+        );
+        let text = text.replace(r#"{"mesh": 0, "children""#, r#"{"children""#);
+        let mesh = parse(&text, Path::new("")).unwrap();
+        assert_eq!(mesh.positions, [Vec3::ZERO, -Vec3::X, Vec3::Y]);
+        assert_eq!(mesh.indices, [0, 2, 1]);
+    }
+
+    #[test]
+    fn rejects_malformed_binary_files() {
+        let json = DOCUMENT.replace("BUFFER", "");
+        let valid = glb(&json, &buffer([0, 1, 2]));
+        let mut truncated = valid.clone();
+        truncated.truncate(valid.len() - 4);
+        let binary_error =
+            |bytes: &[u8]| format!("{:#}", parse_binary(bytes, Path::new("")).unwrap_err());
+        assert_eq!(
+            binary_error(b"glTF\x02\0\0\0\0\0\0\0"),
+            "truncated GLB header"
+        );
+        let malformed: [&[u8]; 5] = [
+            b"glTF",
+            b"glTX\x02\0\0\0\0\0\0\0",
+            b"glTF\x01\0\0\0\0\0\0\0",
+            b"glTF\x02\0\0\0\x0c\0\0\0",
+            &truncated,
+        ];
+        for bytes in malformed {
+            assert!(parse_binary(bytes, Path::new("")).is_err());
+        }
+
+        // Without the binary chunk, the buffer has no data.
+        let mut bytes = glb(&json, &[]);
+        bytes.truncate(bytes.len() - 8);
+        let length = (bytes.len() as u32).to_le_bytes();
+        bytes[8..12].copy_from_slice(&length);
+        assert_eq!(binary_error(&bytes), "missing GLB binary chunk");
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        let valid = embedded(&buffer([0, 1, 2]));
+        assert_eq!(
+            error(&embedded(&buffer([0, 1, 3]))),
+            "vertex index out of range"
+        );
+        assert_eq!(
+            error(&embedded(&buffer([0, 1, 2])[..40])),
+            "buffer 0 is too short"
+        );
+        assert_eq!(
+            error(&valid.replace(
+                r#""count": 3, "type": "VEC3""#,
+                r#""count": 4, "type": "VEC3""#
+            )),
+            "accessor 0 out of range"
+        );
+        assert_eq!(
+            error(&valid.replace(
+                r#""byteLength": 36}"#,
+                r#""byteLength": 36, "byteStride": 252}"#
+            )),
+            "accessor 0 out of range"
+        );
+        assert_eq!(
+            error(&valid.replace(r#""type": "SCALAR""#, r#""type": "MAT4""#)),
+            "unsupported accessor type Mat4"
+        );
+        assert!(error(&valid.replace("base64,", "base64,!")).starts_with("invalid base64 data"));
+        assert_eq!(
+            error(&valid.replace(r#""mesh": 0,"#, r#""mesh": 0, "children": [0],"#)),
+            "node hierarchy is too deep"
+        );
+
+        // The document itself is checked by the `gltf` crate.
+        for malformed in [
+            valid.replace(r#""POSITION": 0"#, r#""NORMAL": 0"#),
+            valid.replace(r#""indices": 1"#, r#""indices": 5"#),
+            valid.replace(r#""translation": [0, 0, 2]"#, r#""translation": [0, 2]"#),
+            valid[1..].to_owned(),
+        ] {
+            assert!(parse(&malformed, Path::new("")).is_err(), "{}", malformed);
+        }
+    }
+
+    #[test]
+    fn rejects_attributes_of_the_wrong_length() {
+        // A NORMAL accessor with fewer elements than POSITION.
+        let text = embedded(&buffer([0, 1, 2]))
+            .replace(r#""POSITION": 0}"#, r#""POSITION": 0, "NORMAL": 2}"#)
+            .replace(
+                r#""type": "SCALAR"}"#,
+                r#""type": "SCALAR"},
+                {"bufferView": 0, "componentType": 5126, "count": 2, "type": "VEC3"}"#,
+            );
+        assert_eq!(error(&text), "NORMAL has 2 elements, but POSITION has 3");
+    }
+
+    #[test]
+    fn rejects_huge_accessors() {
+        let text = embedded(&buffer([0, 1, 2])).replace(
+            r#""count": 3, "type": "SCALAR""#,
+            r#""count": 4294967295, "type": "SCALAR""#,
+        );
+        assert_eq!(error(&text), "accessor 1 is too large");
+    }
+}
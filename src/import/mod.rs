@@ -0,0 +1,97 @@
+//! Loading meshes made in other programs, from glTF 2.0 and Wavefront OBJ
+//! files.
+//!
+//! Both formats put +Y up, +Z forward and wind triangles counterclockwise,
+//! so imported meshes are turned to face +Y with +Z up and rewound to match
+//! the rest of the app. Vertices that come without normals get smooth ones
+//! computed from their triangles.
+
+mod gltf;
+mod obj;
+
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context};
+use glam::Vec3;
+
+use crate::model::MeshData;
+
+/// Loads a `.gltf`, `.glb` or `.obj` file.
+pub fn load(path: impl AsRef<Path>) -> anyhow::Result<MeshData> {
+    let path = path.as_ref();
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    let read_text =
+        || fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()));
+    let mesh = match extension.as_deref() {
+        Some("gltf") => gltf::parse(&read_text()?, directory),
+        Some("glb") => {
+            let bytes =
+                fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+            gltf::parse_binary(&bytes, directory)
+        }
+        Some("obj") => obj::parse(&read_text()?),
+        _ => bail!("unknown mesh format: {}", path.display()),
+    }
+    .with_context(|| format!("failed to load {}", path.display()))?;
+    Ok(into_app_frame(mesh))
+}
+
+/// Parses the text of an OBJ file, such as one included in the binary.
+pub fn parse_obj(text: &str) -> anyhow::Result<MeshData> {
+    obj::parse(text).map(into_app_frame)
+}
+
+/// Turns a mesh from the convention of the file formats into the app's, and
+/// fills in missing normals.
+fn into_app_frame(mut mesh: MeshData) -> MeshData {
+    // A rotation, so it keeps the handedness of the mesh.
+    let convert = |v: Vec3| Vec3::new(-v.x, v.z, v.y);
+    for position in &mut mesh.positions {
+        *position = convert(*position);
+    }
+    for normal in &mut mesh.normals {
+        *normal = convert(*normal);
+    }
+    for tri in mesh.indices.chunks_exact_mut(3) {
+        tri.swap(1, 2);
+    }
+
+    if mesh.normals.contains(&Vec3::ZERO) {
+        let given = mesh.normals.clone();
+        mesh.compute_normals();
+        for (normal, given) in mesh.normals.iter_mut().zip(given) {
+            if given != Vec3::ZERO {
+                *normal = given;
+            }
+        }
+    }
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meshes_are_turned_into_the_app_frame() {
+        // A counterclockwise triangle facing +Z, the file formats' forward.
+        let mesh = parse_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+        assert_eq!(mesh.positions, [Vec3::ZERO, -Vec3::X, Vec3::Z]);
+        assert_eq!(mesh.indices, [0, 2, 1]);
+        // Missing normals are computed, facing +Y, the app's forward.
+        assert_eq!(mesh.normals, [Vec3::Y; 3]);
+
+        let mesh = parse_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 -1\nf 1//1 2//1 3//1\n").unwrap();
+        assert_eq!(mesh.normals, [-Vec3::Y; 3]);
+    }
+
+    #[test]
+    fn unknown_formats_are_rejected() {
+        let error = format!("{:#}", load("mesh.stl").unwrap_err());
+        assert_eq!(error, "unknown mesh format: mesh.stl");
+    }
+}
@@ -0,0 +1,231 @@
+//! Wavefront OBJ meshes.
+//!
+//! Supports vertex positions with optional colors (`v x y z r g b`), texture
+//! coordinates, normals and polygon faces, which are split into triangle
+//! fans. Groups, smoothing and materials are ignored.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Context};
+use glam::{Vec2, Vec3};
+
+use crate::model::MeshData;
+
+/// Parses an OBJ file into a mesh in the file's own frame, with
+/// counterclockwise triangles. Vertices without a normal get a zero normal.
+pub fn parse(text: &str) -> anyhow::Result<MeshData> {
+    let mut positions = Vec::new();
+    let mut colors = Vec::new();
+    let mut uvs = Vec::new();
+    let mut normals = Vec::new();
+    let mut mesh = MeshData::default();
+    // Each distinct combination of position, texture coordinate and normal
+    // becomes a vertex.
+    let mut vertices = HashMap::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words = line.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let floats = || -> anyhow::Result<Vec<f32>> {
+            words
+                .clone()
+                .map(|word| {
+                    word.parse()
+                        .with_context(|| format!("invalid number `{}`", word))
+                })
+                .collect()
+        };
+        let context = || format!("line {}", number + 1);
+        match keyword {
+            "v" => {
+                let values = floats().with_context(context)?;
+                match values.len() {
+                    3 | 4 => colors.push(Vec3::ONE),
+                    6 | 7 => colors.push(Vec3::new(values[3], values[4], values[5])),
+                    _ => bail!("{}: expected 3 or 6 values for `v`", context()),
+                }
+                positions.push(Vec3::new(values[0], values[1], values[2]));
+            }
+            "vt" => {
+                let values = floats().with_context(context)?;
+                let (u, v) = match values[..] {
+                    [u] => (u, 0.0),
+                    [u, v, ..] => (u, v),
+                    [] => bail!("{}: expected values for `vt`", context()),
+                };
+                // OBJ puts the origin of textures at the bottom left.
+                uvs.push(Vec2::new(u, 1.0 - v));
+            }
+            "vn" => match floats().with_context(context)?[..] {
+                [x, y, z] => normals.push(Vec3::new(x, y, z).normalize_or_zero()),
+                _ => bail!("{}: expected 3 values for `vn`", context()),
+            },
+            "f" => {
+                let mut face = Vec::new();
+                for word in words {
+                    let key = parse_face_vertex(word, positions.len(), uvs.len(), normals.len())
+                        .with_context(context)?;
+                    let index = *vertices.entry(key).or_insert_with(|| {
+                        let (position, uv, normal) = key;
+                        mesh.positions.push(positions[position]);
+                        mesh.colors.push(colors[position]);
+                        mesh.uvs.push(uv.map_or(Vec2::ZERO, |uv| uvs[uv]));
+                        mesh.normals
+                            .push(normal.map_or(Vec3::ZERO, |normal| normals[normal]));
+                        mesh.positions.len() as u32 - 1
+                    });
+                    face.push(index);
+                }
+                if face.len() < 3 {
+                    bail!("{}: face with fewer than 3 vertices", context());
+                }
+                for i in 1..face.len() - 1 {
+                    mesh.indices.extend([face[0], face[i], face[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(mesh)
+}
+
+/// The position, texture coordinate and normal indices of a face vertex such
+/// as `1/2/3`, `1//3` or `-1`, counted from zero.
+fn parse_face_vertex(
+    word: &str,
+    positions: usize,
+    uvs: usize,
+    normals: usize,
+) -> anyhow::Result<(usize, Option<usize>, Option<usize>)> {
+    let mut parts = word.split('/');
+    let position = resolve_index(parts.next().unwrap_or_default(), positions)?
+        .ok_or_else(|| anyhow!("missing position in `{}`", word))?;
+    let uv = resolve_index(parts.next().unwrap_or_default(), uvs)?;
+    let normal = resolve_index(parts.next().unwrap_or_default(), normals)?;
+    Ok((position, uv, normal))
+}
+
+/// Turns a one-based index, or a negative one counting back from the end,
+/// into a zero-based one.
+fn resolve_index(text: &str, count: usize) -> anyhow::Result<Option<usize>> {
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let index: i64 = text.parse()?;
+    let resolved = if index < 0 {
+        count as i64 + index
+    } else {
+        index - 1
+    };
+    if !(0..count as i64).contains(&resolved) {
+        bail!("index {} out of range", index);
+    }
+    Ok(Some(resolved as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        format!("{:#}", parse(text).unwrap_err())
+    }
+
+    #[test]
+    fn parses_triangles() {
+        let mesh = parse(
+            "# a triangle\n\
+             v 0 0 0\n\
+             v 1 0 0 # trailing comment\n\
+             v 0 1 0\n\
+             \n\
+             f 1 2 3\n",
+        )
+        .unwrap();
+        assert_eq!(mesh.positions, [Vec3::ZERO, Vec3::X, Vec3::Y]);
+        assert_eq!(mesh.indices, [0, 1, 2]);
+        assert_eq!(mesh.colors, [Vec3::ONE; 3]);
+        assert_eq!(mesh.normals, [Vec3::ZERO; 3]);
+        assert_eq!(mesh.uvs, [Vec2::ZERO; 3]);
+    }
+
+    #[test]
+    fn splits_polygons_into_fans() {
+        let mesh = parse(
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+             f 1 2 3 4\n",
+        )
+        .unwrap();
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.indices, [0, 1, 2, 0, 2, 3]);
+    }
+
+    #[test]
+    fn parses_attributes() {
+        let mesh = parse(
+            "v 0 0 0 1 0 0\nv 1 0 0\nv 0 1 0\n\
+             vt 0.25 0.75\nvt 1\n\
+             vn 0 0 2\n\
+             f 1/1/1 2//1 -1/-1/-1\n",
+        )
+        .unwrap();
+        assert_eq!(mesh.colors[0], Vec3::X);
+        assert_eq!(mesh.colors[1], Vec3::ONE);
+        // Texture coordinates are flipped to start at the top.
+        assert_eq!(
+            mesh.uvs,
+            [Vec2::new(0.25, 0.25), Vec2::ZERO, Vec2::new(1.0, 1.0)]
+        );
+        assert_eq!(mesh.normals, [Vec3::Z; 3]);
+    }
+
+    #[test]
+    fn shares_identical_vertices() {
+        let mesh = parse(
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\nvn 0 0 1\n\
+             f 1//1 2//1 3//1\n\
+             f 2//1 4//1 3//1\n\
+             f 2 4 3\n",
+        )
+        .unwrap();
+        // The last face has no normals, so its vertices are new.
+        assert_eq!(mesh.positions.len(), 7);
+        assert_eq!(&mesh.indices[..6], [0, 1, 2, 1, 3, 2]);
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        assert_eq!(
+            error("v 0 0 0\nv 1 x 0\n"),
+            "line 2: invalid number `x`: invalid float literal"
+        );
+        assert_eq!(error("v 0 0\n"), "line 1: expected 3 or 6 values for `v`");
+        assert_eq!(error("vt\n"), "line 1: expected values for `vt`");
+        assert_eq!(error("vn 0 1\n"), "line 1: expected 3 values for `vn`");
+        assert_eq!(
+            error("v 0 0 0\nv 1 0 0\nf 1 2\n"),
+            "line 3: face with fewer than 3 vertices"
+        );
+        assert_eq!(
+            error("v 0 0 0\nv 1 0 0\nf 1 2 3\n"),
+            "line 3: index 3 out of range"
+        );
+        assert_eq!(
+            error("v 0 0 0\nf 1 -2 1\n"),
+            "line 2: index -2 out of range"
+        );
+        assert_eq!(
+            error("v 0 0 0\nf 1/5 1 1\n"),
+            "line 2: index 5 out of range"
+        );
+        assert_eq!(
+            error("v 0 0 0\nf /1 1 1\n"),
+            "line 2: missing position in `/1`"
+        );
+        assert!(error("v 0 0 0\nf 1 one 1\n").starts_with("line 2: invalid digit"));
+    }
+}
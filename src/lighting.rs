@@ -0,0 +1,232 @@
+use bytemuck::{Pod, Zeroable};
+use glam::{DVec3, Vec3};
+use wgpu::util::DeviceExt;
+
+use crate::{eclipse::Sphere, world::World, GraphicsContext};
+
+/// Color of the light from the root body.
+const LIGHT_COLOR: Vec3 = glam::const_vec3!([1.0, 0.98, 0.92]);
+
+/// Light reaching surfaces that face away from the light, so that the night
+/// side isn't completely black.
+const AMBIENT: f32 = 0.03;
+
+/// Most bodies casting shadows at once. The nearest ones to the camera are
+/// kept.
+const MAX_OCCLUDERS: usize = 8;
+
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct Uniforms {
+    // vec4<f32>
+    position: [f32; 3],
+    radius: f32,
+
+    // vec4<f32>
+    color: [f32; 3],
+    ambient: f32,
+
+    // u32
+    occluder_count: u32,
+    _padding: [u8; 12],
+
+    /// Center and radius of each body casting shadows.
+    // array<vec4<f32>, MAX_OCCLUDERS>
+    occluders: [[f32; 4]; MAX_OCCLUDERS],
+}
+
+impl Uniforms {
+    fn new(position: Vec3, radius: f32, color: Vec3, ambient: f32, occluders: &[Sphere]) -> Self {
+        let mut packed = [[0.0; 4]; MAX_OCCLUDERS];
+        for (uniform, occluder) in packed.iter_mut().zip(occluders) {
+            *uniform = occluder
+                .center
+                .as_vec3()
+                .extend(occluder.radius as f32)
+                .into();
+        }
+        Self {
+            position: position.into(),
+            radius,
+            color: color.into(),
+            ambient,
+            occluder_count: occluders.len().min(MAX_OCCLUDERS) as u32,
+            _padding: Default::default(),
+            occluders: packed,
+        }
+    }
+}
+
+/// The light of the world, which is its root body, or `None` if there isn't
+/// one.
+fn light(world: &World) -> Option<Sphere> {
+    world.root().map(|root| world.body(root).sphere())
+}
+
+/// The bodies casting shadows that are nearest to `camera`, nearest first.
+fn nearest_occluders(world: &World, camera: DVec3) -> Vec<Sphere> {
+    let mut occluders: Vec<_> = world.occluders().collect();
+    occluders.sort_by(|a, b| {
+        let distance = |sphere: &Sphere| sphere.center.distance(camera) - sphere.radius;
+        distance(a).total_cmp(&distance(b))
+    });
+    occluders.truncate(MAX_OCCLUDERS);
+    occluders
+}
+
+/// A spherical light at the root body of the world, which is the star that
+/// everything else orbits, and the bodies that cast shadows in it.
+///
+/// Bound as a uniform alongside the [`Viewport`](crate::viewport::Viewport)
+/// in the scene shader.
+pub struct Lighting {
+    gfx: GraphicsContext,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+    pub position: Vec3,
+    pub radius: f32,
+    pub color: Vec3,
+    pub ambient: f32,
+    /// Bodies casting shadows, from [`World::occluders`].
+    pub occluders: Vec<Sphere>,
+}
+
+impl Lighting {
+    pub fn new(gfx: &GraphicsContext) -> Self {
+        let uniform_buffer = gfx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Lighting::uniform_buffer"),
+                contents: bytemuck::bytes_of(&Uniforms::zeroed()),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+
+        let bind_group_layout =
+            gfx.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Lighting::bind_group_layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    }],
+                });
+
+        let bind_group = gfx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Lighting::bind_group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            gfx: gfx.clone(),
+            bind_group_layout,
+            bind_group,
+            uniform_buffer,
+            position: Vec3::ZERO,
+            radius: 0.0,
+            color: LIGHT_COLOR,
+            ambient: AMBIENT,
+            occluders: Vec::new(),
+        }
+    }
+
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    /// Moves the light to the world's root body, finds the bodies casting
+    /// shadows nearest to the camera, and uploads them.
+    pub fn update(&mut self, world: &World, camera: Vec3) {
+        if let Some(light) = light(world) {
+            self.position = light.center.as_vec3();
+            self.radius = light.radius as f32;
+        }
+        self.occluders = nearest_occluders(world, camera.as_dvec3());
+        self.gfx.queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::bytes_of(&Uniforms::new(
+                self.position,
+                self.radius,
+                self.color,
+                self.ambient,
+                &self.occluders,
+            )),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sphere(x: f64, radius: f64) -> Sphere {
+        Sphere {
+            center: DVec3::new(x, 0.0, 0.0),
+            radius,
+        }
+    }
+
+    #[test]
+    fn light_is_the_root_body() {
+        let world = World::new();
+        let root = world.body(world.root().unwrap());
+        assert_eq!(root.name(), "Sun");
+        assert_eq!(light(&world), Some(root.sphere()));
+    }
+
+    #[test]
+    fn occluders_are_the_nearest_bodies_other_than_the_light() {
+        let world = World::new();
+        let find = |name| {
+            world
+                .body_tags
+                .iter()
+                .map(|tag| world.body(tag))
+                .find(|body| body.name() == name)
+                .unwrap()
+                .sphere()
+        };
+        let (earth, moon) = (find("Earth"), find("Moon"));
+        // From the far side of the Moon, Earth's surface is still nearer,
+        // since it's so large.
+        let camera = moon.center + (moon.center - earth.center);
+        assert_eq!(nearest_occluders(&world, camera), vec![earth, moon]);
+    }
+
+    #[test]
+    fn uniforms_are_packed_for_the_shader() {
+        let occluders: Vec<_> = (0..10).map(|i| sphere(i as f64, 0.5)).collect();
+        let uniforms = Uniforms::new(
+            Vec3::new(1.0, 2.0, 3.0),
+            4.0,
+            Vec3::new(0.5, 0.25, 0.125),
+            AMBIENT,
+            &occluders,
+        );
+        let floats: &[f32] = bytemuck::cast_slice(bytemuck::bytes_of(&uniforms));
+        assert_eq!(floats.len(), 4 * (3 + MAX_OCCLUDERS));
+        assert_eq!(floats[0..4], [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(floats[4..8], [0.5, 0.25, 0.125, AMBIENT]);
+        // Only as many occluders as fit are passed on.
+        assert_eq!(floats[8].to_bits(), MAX_OCCLUDERS as u32);
+        for i in 0..MAX_OCCLUDERS {
+            let offset = 12 + 4 * i;
+            assert_eq!(floats[offset..offset + 4], [i as f32, 0.0, 0.0, 0.5]);
+        }
+    }
+}
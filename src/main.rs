@@ -1,30 +1,81 @@
+pub mod atmosphere;
+pub mod camera;
+pub mod capture;
 pub mod compute_hud;
 pub mod controls;
+pub mod craft;
+pub mod eclipse;
 pub mod geometry;
 pub mod hud;
+pub mod import;
+pub mod lighting;
+pub mod map;
+pub mod material;
 pub mod math;
 pub mod model;
 pub mod orbit;
+pub mod render_target;
 pub mod scene;
+pub mod starfield;
+pub mod telemetry;
+pub mod terrain;
+pub mod text;
+pub mod texture;
 pub mod time;
 pub mod viewport;
 pub mod world;
 
-use anyhow::Context;
-use controls::Controls;
-use glam::{Quat, Vec3, Vec3Swizzles};
-use hud::Hud;
+use anyhow::{bail, Context};
+use camera::{CameraMode, OrbitCamera};
+use capture::FrameExport;
+use controls::{Action, Bindings, Controls, Gamepad, InputEvent, Recorder, Replay, WindowSize};
+use craft::SasMode;
+use glam::{Quat, UVec2, Vec2, Vec3, Vec3Swizzles};
+use hud::{orbits::Marker, picking, HudRenderer, View};
+use map::MapView;
 use pollster::block_on;
+use render_target::{Presenter, RenderTarget};
 use scene::Scene;
 use std::f32::consts::TAU;
-use std::sync::Arc;
-use std::time::Instant;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use time::SimDuration;
+use valet::Tag;
 use viewport::Viewport;
-use winit::dpi::LogicalSize;
+use winit::dpi::{LogicalSize, PhysicalSize};
 use winit::event::WindowEvent;
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::{Window, WindowBuilder};
-use world::World;
+use world::{Body, World};
+
+/// Top left corner of the telemetry panel, in pixels.
+const TELEMETRY_POSITION: Vec2 = glam::const_vec2!([12.0, 12.0]);
+const TELEMETRY_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+/// Position of status messages from the bottom left corner of the window
+/// (logical pixels).
+const STATUS_OFFSET: Vec2 = glam::const_vec2!([12.0, 28.0]);
+const STATUS_COLOR: [f32; 4] = [0.9, 0.9, 0.6, 1.0];
+/// How long status messages are shown for.
+const STATUS_DURATION: Duration = Duration::from_secs(3);
+
+/// Config file that input bindings are loaded from, if it exists.
+const BINDINGS_PATH: &str = "bindings.cfg";
+
+/// Size of the window when it opens (logical pixels), and of offscreen
+/// frames until a replay sets it.
+const WINDOW_SIZE: (u32, u32) = (1280, 720);
+
+/// Factor that `warp_up` and `warp_down` change the time warp by.
+const WARP_STEP: f64 = 10.0;
+
+/// Simulated time between exported frames, unless set with `--frame-step`
+/// (s).
+const FRAME_STEP: f64 = 60.0;
+
+/// How close the cursor has to be to a marker or orbit to pick it, in
+/// pixels.
+const PICK_RADIUS: f32 = 8.0;
 
 pub type Event<'a> = winit::event::Event<'a, AppEvent>;
 
@@ -33,8 +84,13 @@ pub enum AppEvent {}
 pub type GraphicsContext = Arc<GraphicsContextInner>;
 
 pub struct GraphicsContextInner {
-    pub window: Window,
-    pub surface: wgpu::Surface,
+    /// The window that frames are presented to, or `None` for a context
+    /// that only renders offscreen.
+    pub window: Option<Window>,
+    pub surface: Option<wgpu::Surface>,
+    /// Size that frames are rendered at instead of the window's: always set
+    /// for offscreen contexts, and set from the recording while replaying.
+    fixed_size: Mutex<Option<WindowSize>>,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub render_format: wgpu::TextureFormat,
@@ -52,7 +108,38 @@ impl GraphicsContextInner {
             })
             .await
             .context("failed to create adapter")?;
+        Self::with_adapter(adapter, Some(window), Some(surface), None).await
+    }
 
+    /// A context without a window, for rendering frames of a fixed size
+    /// offscreen. Falls back to a software adapter if there is no other.
+    pub async fn offscreen(width: u32, height: u32) -> anyhow::Result<Self> {
+        // Software adapters may only be available on the secondary backends.
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let adapter = match instance.request_adapter(&Default::default()).await {
+            Some(adapter) => adapter,
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    force_fallback_adapter: true,
+                    ..Default::default()
+                })
+                .await
+                .context("failed to create adapter")?,
+        };
+        let size = WindowSize {
+            width,
+            height,
+            scale_factor: 1.0,
+        };
+        Self::with_adapter(adapter, None, None, Some(size)).await
+    }
+
+    async fn with_adapter(
+        adapter: wgpu::Adapter,
+        window: Option<Window>,
+        surface: Option<wgpu::Surface>,
+        fixed_size: Option<WindowSize>,
+    ) -> anyhow::Result<Self> {
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
@@ -73,6 +160,7 @@ impl GraphicsContextInner {
         Ok(Self {
             window,
             surface,
+            fixed_size: Mutex::new(fixed_size),
             device,
             queue,
             render_format,
@@ -80,94 +168,539 @@ impl GraphicsContextInner {
         })
     }
 
+    /// Size of the frames being rendered, and the scale factor they are
+    /// rendered at.
+    pub fn window_size(&self) -> WindowSize {
+        let fixed_size = *self.fixed_size.lock().unwrap();
+        match (fixed_size, &self.window) {
+            (Some(size), _) => size,
+            (None, Some(window)) => {
+                let size = window.inner_size();
+                WindowSize {
+                    width: size.width,
+                    height: size.height,
+                    scale_factor: window.scale_factor(),
+                }
+            }
+            (None, None) => unreachable!("offscreen contexts have a fixed size"),
+        }
+    }
+
+    /// Renders frames at `size` from now on, whatever the size of the
+    /// window. Frames are stretched to fit the window.
+    pub fn set_window_size(&self, size: WindowSize) {
+        *self.fixed_size.lock().unwrap() = Some(size);
+    }
+
+    /// Size of the frames being rendered (pixels).
+    pub fn size(&self) -> PhysicalSize<u32> {
+        let size = self.window_size();
+        PhysicalSize::new(size.width, size.height)
+    }
+
+    /// Physical pixels per logical pixel.
+    pub fn scale_factor(&self) -> f64 {
+        self.window_size().scale_factor
+    }
+
     fn reconfigure(&self) {
-        self.surface.configure(
+        let (window, surface) = match (&self.window, &self.surface) {
+            (Some(window), Some(surface)) => (window, surface),
+            _ => return,
+        };
+        let size = window.inner_size();
+        surface.configure(
             &self.device,
             &wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
-                    | wgpu::TextureUsages::STORAGE_BINDING,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
                 format: self.render_format,
-                width: self.window.inner_size().width,
-                height: self.window.inner_size().height,
+                width: size.width,
+                height: size.height,
                 present_mode: wgpu::PresentMode::Fifo,
             },
         );
     }
 }
 
+/// Command line options.
+#[derive(Debug, Default)]
+struct Options {
+    /// Record input to this file (`--record <path>`).
+    record: Option<PathBuf>,
+    /// Replay input from this file instead of taking it from the user, then
+    /// exit (`--replay <path>`).
+    replay: Option<PathBuf>,
+    /// Save every frame to this directory, advancing the simulation by a
+    /// fixed step between them (`--export-frames <dir>`).
+    export_frames: Option<PathBuf>,
+    /// Simulated seconds between exported frames (`--frame-step <seconds>`).
+    frame_step: Option<f64>,
+    /// Replay without opening a window, rendering offscreen at the recorded
+    /// window size (`--headless`).
+    headless: bool,
+    /// Directory of texture maps named after the bodies, e.g.
+    /// `earth_albedo.png` (`--textures <dir>`).
+    textures: Option<PathBuf>,
+}
+
+impl Options {
+    fn from_args() -> anyhow::Result<Self> {
+        let mut options = Self::default();
+        let mut args = std::env::args_os().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .with_context(|| format!("missing value after {:?}", arg))
+            };
+            match arg.to_str() {
+                Some("--record") => options.record = Some(value()?.into()),
+                Some("--replay") => options.replay = Some(value()?.into()),
+                Some("--export-frames") => options.export_frames = Some(value()?.into()),
+                Some("--headless") => options.headless = true,
+                Some("--textures") => options.textures = Some(value()?.into()),
+                Some("--frame-step") => {
+                    let step = value()?;
+                    let seconds = step
+                        .to_str()
+                        .and_then(|step| step.parse().ok())
+                        .filter(|&seconds: &f64| seconds > 0.0)
+                        .with_context(|| format!("invalid frame step {:?}", step))?;
+                    options.frame_step = Some(seconds);
+                }
+                _ => bail!("unknown argument {:?}", arg),
+            }
+        }
+        // A replay only reproduces the recording at the recorded time steps.
+        if options.replay.is_some() && options.export_frames.is_some() {
+            bail!("--export-frames can't be used with --replay");
+        }
+        // Exported frames advance by the frame step rather than the recorded
+        // frame time, so the recording wouldn't replay the same way.
+        if options.record.is_some() && options.export_frames.is_some() {
+            bail!("--export-frames can't be used with --record");
+        }
+        // Without a window, there is no other input.
+        if options.headless && options.replay.is_none() {
+            bail!("--headless needs --replay");
+        }
+        Ok(options)
+    }
+}
+
 struct App {
     gfx: GraphicsContext,
     controls: Controls,
     viewport: Viewport,
+    camera_mode: CameraMode,
+    orbit_camera: OrbitCamera,
+    map_mode: bool,
+    map_view: MapView,
+    /// Whether the cursor has been released from the window with Escape.
+    cursor_released: bool,
+    /// Body last clicked on, if any.
+    selection: Option<Tag<Body>>,
     world: World,
     scene: Scene,
-    // hud: Hud,
-    hud: compute_hud::Hud,
+    /// Frame being rendered, before it's presented or saved.
+    target: RenderTarget,
+    presenter: Presenter,
+    /// Whether to save the next frame as a screenshot.
+    screenshot: bool,
+    export: Option<FrameExport>,
+    /// Message shown at the bottom of the HUD, and how much longer to show
+    /// it for.
+    status: Option<(String, Duration)>,
+    hud_backend: hud::Backend,
+    hud: Box<dyn HudRenderer>,
+    recorder: Option<Recorder>,
+    replay: Option<Replay>,
     last_update: Instant,
 }
 
 impl App {
-    async fn new(window: Window) -> anyhow::Result<Self> {
-        let gfx = Arc::new(GraphicsContextInner::new(window).await?);
-        gfx.reconfigure();
-
-        let controls = Controls::new();
+    fn new(gfx: GraphicsContext, options: &Options) -> anyhow::Result<Self> {
+        let mut controls = Controls::new(Bindings::load(BINDINGS_PATH)?);
+        // While replaying, all input comes from the recording.
+        if options.replay.is_none() {
+            for gamepad in Gamepad::open_all() {
+                controls.add_source(Box::new(gamepad));
+            }
+        }
         let viewport = Viewport::new(&gfx);
-        let world = World::new();
-        let scene = Scene::new(&gfx, &viewport);
-        // let hud = Hud::new(&gfx);
-        let hud = compute_hud::Hud::new(&gfx, &viewport);
+        let mut world = World::new();
+        if let Some(dir) = &options.textures {
+            if world.find_textures(dir) == 0 {
+                bail!("no texture maps for any body in {}", dir.display());
+            }
+        }
+        let mut scene = Scene::new(&gfx, &viewport);
+        scene.load_textures(&world)?;
+        let hud_backend = hud::Backend::Compute;
+        let hud = hud_backend.create(&gfx, &viewport);
+        let recorder = options.record.as_ref().map(Recorder::create).transpose()?;
+        let replay = options.replay.as_ref().map(Replay::load).transpose()?;
+        let size = gfx.size();
+        let target = RenderTarget::new(&gfx, size.width, size.height);
+        let presenter = Presenter::new(&gfx);
+        let frame_step = SimDuration::from_secs_f64(options.frame_step.unwrap_or(FRAME_STEP));
+        let export = options
+            .export_frames
+            .as_ref()
+            .map(|dir| FrameExport::new(dir, frame_step))
+            .transpose()?;
 
         Ok(Self {
             gfx,
             controls,
             viewport,
+            camera_mode: CameraMode::FreeFly,
+            orbit_camera: OrbitCamera::new(),
+            map_mode: false,
+            map_view: MapView::new(),
+            cursor_released: false,
+            selection: None,
             world,
             scene,
+            target,
+            presenter,
+            screenshot: false,
+            export,
+            status: None,
+            hud_backend,
             hud,
+            recorder,
+            replay,
             last_update: Instant::now(),
         })
     }
 
-    fn event(&mut self, event: &Event) {
-        self.controls.event(event);
+    fn event(&mut self, event: &Event) -> anyhow::Result<()> {
+        // While replaying, all input comes from the recording.
+        if self.replay.is_some() {
+            return Ok(());
+        }
+        for event in InputEvent::from_winit(event) {
+            self.input(event)?;
+        }
+        Ok(())
+    }
+
+    /// Handles an input event from the user, recording it if asked to.
+    fn input(&mut self, event: InputEvent) -> anyhow::Result<()> {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.event(&event)?;
+        }
+        self.controls.input(event);
+        Ok(())
     }
 
-    fn update(&mut self) {
+    fn update(&mut self) -> anyhow::Result<()> {
         let now = Instant::now();
-        let dt = now - self.last_update;
+        let dt = match &mut self.replay {
+            Some(replay) => {
+                let frame = match replay.next_frame() {
+                    Some(frame) => frame,
+                    None => return Ok(()),
+                };
+                if frame.sim_time != self.world.time() {
+                    bail!(
+                        "replay diverged with {} frames left: sim time is {:?}, recorded {:?}",
+                        replay.remaining(),
+                        self.world.time(),
+                        frame.sim_time
+                    );
+                }
+                if let Some(size) = frame.window {
+                    self.gfx.set_window_size(size);
+                    self.window_resized();
+                }
+                for event in frame.events {
+                    self.controls.input(event);
+                }
+                frame.dt
+            }
+            None => {
+                for event in self.controls.poll_sources() {
+                    self.input(event)?;
+                }
+                now - self.last_update
+            }
+        };
+        if let Some(recorder) = &mut self.recorder {
+            recorder.frame(dt, self.world.time(), self.gfx.window_size())?;
+        }
+
+        match &self.export {
+            Some(export) => self.world.advance(export.step),
+            None => self.world.update(dt),
+        }
+
+        if self.controls.take_pressed(Action::Screenshot) {
+            self.screenshot = true;
+        }
+        for _ in 0..self.controls.take_presses(Action::WarpUp) {
+            self.world.set_warp(self.world.warp() * WARP_STEP);
+        }
+        for _ in 0..self.controls.take_presses(Action::WarpDown) {
+            self.world.set_warp(self.world.warp() / WARP_STEP);
+        }
+        self.update_craft(dt);
+        if self.controls.take_pressed(Action::ToggleHudBackend) {
+            self.hud_backend = self.hud_backend.next();
+            self.hud = self.hud_backend.create(&self.gfx, &self.viewport);
+        }
+        if self.controls.take_pressed(Action::ToggleCameraMode) {
+            self.camera_mode = match self.camera_mode {
+                CameraMode::FreeFly => CameraMode::Orbit,
+                CameraMode::Orbit => CameraMode::FreeFly,
+            };
+        }
+        let cycle = self.controls.take_presses(Action::CycleTarget) as i32
+            - self.controls.take_presses(Action::CycleTargetBack) as i32;
+        if cycle != 0 {
+            self.orbit_camera.cycle_target(&self.world, cycle);
+            self.camera_mode = CameraMode::Orbit;
+        }
+        if self.camera_mode == CameraMode::Orbit && self.orbit_camera.target().is_none() {
+            self.orbit_camera.cycle_target(&self.world, 0);
+        }
+        if self.controls.take_pressed(Action::ToggleCoRotate) {
+            self.orbit_camera.co_rotate = !self.orbit_camera.co_rotate;
+        }
+        if self.controls.take_pressed(Action::ToggleMap) {
+            self.map_mode = !self.map_mode;
+            if self.map_mode {
+                self.map_view.enter(self.viewport.pose());
+            } else if let Some(pose) = self.map_view.exit() {
+                self.viewport.set_pose(pose);
+            }
+            self.grab_cursor(!self.cursor_free());
+        }
+        if self.controls.take_pressed(Action::ToggleCursor) {
+            self.cursor_released = !self.cursor_released;
+            self.grab_cursor(!self.cursor_free());
+        }
 
-        self.viewport.pitch = (self.viewport.pitch + self.controls.take_pitch() as f32 * 0.001)
-            .clamp(-TAU / 4.0 + 0.001, TAU / 4.0 - 0.001);
-        self.viewport.yaw = (self.viewport.yaw + self.controls.take_yaw() as f32 * 0.001) % TAU;
+        let yaw = self.controls.take_axis(Action::LookYaw, dt.as_secs_f64());
+        let pitch = self.controls.take_axis(Action::LookPitch, dt.as_secs_f64());
+        let zoom = self.controls.take_axis(Action::Zoom, dt.as_secs_f64());
 
-        // Moving forward/back doesn't change altitude
-        // let global_movement =
-        //     Quat::from_rotation_z(self.viewport.yaw) * self.controls.net_movement();
+        if self.map_mode {
+            if self.controls.dragging() {
+                self.rotate_camera(yaw, 0.0);
+            }
+            let focus = self.orbit_camera.target();
+            self.map_view.zoom(&self.world, focus, zoom);
+            if let Some(pose) = self.map_view.pose(&self.world, focus) {
+                self.viewport.set_pose(pose);
+            }
+        } else {
+            match self.camera_mode {
+                CameraMode::FreeFly => {
+                    self.viewport.frame = Quat::IDENTITY;
+                    self.viewport.z_near = viewport::Z_NEAR;
+                    self.viewport.z_far = viewport::Z_FAR;
+                    if !self.cursor_free() || self.controls.dragging() {
+                        self.rotate_camera(yaw, pitch);
+                    }
+
+                    // Moving forward/back doesn't change altitude
+                    // let global_movement =
+                    //     Quat::from_rotation_z(self.viewport.yaw) * self.controls.net_movement();
 
-        // Moving forward/back does change altitude
-        let global_movement = self.viewport.camera_orientation()
-            * self.controls.net_movement().xy().extend(0.0)
-            + Vec3::new(0.0, 0.0, self.controls.net_movement().z);
+                    // Moving forward/back does change altitude
+                    let global_movement = self.viewport.camera_orientation()
+                        * self.controls.net_movement().xy().extend(0.0)
+                        + Vec3::new(0.0, 0.0, self.controls.net_movement().z);
 
-        self.viewport.camera_position += dt.as_secs_f32() * 5.0 * global_movement;
+                    self.viewport.camera_position += dt.as_secs_f32() * 5.0 * global_movement;
+                }
+                CameraMode::Orbit => {
+                    if self.controls.dragging() {
+                        self.rotate_camera(yaw, pitch);
+                    }
+                    self.orbit_camera.zoom(&self.world, zoom);
+                    self.orbit_camera
+                        .update(&self.world, &mut self.viewport, dt.as_secs_f64());
+                }
+            }
+        }
+
+        let markers = hud::orbits::markers(&self.world);
+        hud::orbits::draw(
+            &self.world,
+            self.orbit_camera.target(),
+            self.selection.as_ref(),
+            &markers,
+            self.hud.as_mut(),
+        );
+        hud::craft::draw(&self.world, self.hud.as_mut());
+        let clicked = self.controls.take_click();
+        if self.cursor_free() {
+            self.pick(&markers, clicked);
+        }
+        self.hud.text(
+            TELEMETRY_POSITION,
+            &telemetry::panel(&self.world, self.orbit_camera.target()),
+            TELEMETRY_COLOR,
+        );
+        if let Some((message, remaining)) = &mut self.status {
+            let height = (self.gfx.size().height as f64 / self.gfx.scale_factor()) as f32;
+            let position = Vec2::new(STATUS_OFFSET.x, height - STATUS_OFFSET.y);
+            self.hud.text(position, message, STATUS_COLOR);
+            // Timed by frames rather than the clock, so that replays match.
+            *remaining = remaining.saturating_sub(dt);
+            if remaining.is_zero() {
+                self.status = None;
+            }
+        }
 
         self.last_update = now;
         self.viewport.update();
-        self.scene.update(&self.viewport);
-        // self.hud.orbit = self.scene.orbit;
-        // self.hud.state = self.scene.state;
-
-        // self.world.update();
-        // for (id, tag) in self.world.body_tags.iter().enumerate() {
-        //     self.scene.instances[id].model = self.world.body(tag).model_matrix().to_cols_array_2d();
-        //     self.scene.instances[id].albedo = [0.3, 0.6, 0.9];
-        // }
+        self.scene.update(&self.viewport, &self.world);
+        Ok(())
+    }
+
+    /// Switches the craft's SAS and RCS, and turns it with the rotation
+    /// controls. The SAS target is the selected body.
+    fn update_craft(&mut self, dt: Duration) {
+        let craft = match self.world.craft_mut() {
+            Some(craft) => craft,
+            None => return,
+        };
+        if self.controls.take_pressed(Action::ToggleSas) {
+            craft.sas = match craft.sas {
+                Some(_) => None,
+                None => Some(SasMode::Stability),
+            };
+        }
+        if self.controls.take_pressed(Action::ToggleRcs) {
+            craft.rcs = !craft.rcs;
+        }
+        for (action, mode) in [
+            (Action::SasStability, SasMode::Stability),
+            (Action::SasPrograde, SasMode::Prograde),
+            (Action::SasRetrograde, SasMode::Retrograde),
+            (Action::SasNormal, SasMode::Normal),
+            (Action::SasRadial, SasMode::Radial),
+            (Action::SasTarget, SasMode::Target),
+        ] {
+            if self.controls.take_pressed(action) {
+                craft.sas = Some(mode);
+            }
+        }
+        craft.target = self.selection;
+        self.world
+            .update_craft(self.controls.net_rotation().as_dvec3(), dt);
+    }
+
+    /// Whether a replay has run out of frames.
+    fn replay_finished(&self) -> bool {
+        matches!(&self.replay, Some(replay) if replay.remaining() == 0)
+    }
+
+    /// The cursor is free to point at things on the map, or after being
+    /// released with Escape. Otherwise it is grabbed for looking around.
+    fn cursor_free(&self) -> bool {
+        self.map_mode || self.cursor_released
+    }
+
+    /// Handles what is under the cursor: the marker under it shows a tooltip,
+    /// clicking on a body selects it and moves the camera to it, and clicking
+    /// on an orbit plans a maneuver there.
+    fn pick(&mut self, markers: &[Marker], clicked: bool) {
+        let size = self.gfx.size();
+        let view = View::new(&self.viewport, UVec2::new(size.width, size.height));
+        let cursor = self.controls.cursor_position();
+
+        if let Some(marker) = picking::pick_marker(markers, &view, cursor, PICK_RADIUS) {
+            hud::orbits::draw_tooltip(&self.world, marker, self.hud.as_mut());
+            return;
+        }
+        if clicked {
+            let ray = self.viewport.pixel_ray(cursor);
+            if let Some(tag) = picking::pick_body(&self.world, &ray) {
+                self.selection = Some(tag);
+                self.orbit_camera.set_target(&self.world, tag);
+                self.camera_mode = CameraMode::Orbit;
+                return;
+            }
+        }
+        if let Some(pick) = picking::pick_orbit(&self.world, &view, cursor, PICK_RADIUS) {
+            if clicked {
+                self.world.add_maneuver_node(&pick.body, pick.angle);
+            } else {
+                hud::orbits::draw_pick(&self.world, &pick, self.hud.as_mut());
+            }
+        } else if clicked {
+            self.selection = None;
+        }
     }
 
+    fn grab_cursor(&self, grab: bool) {
+        // Not all platforms support grabbing, and the app is still usable
+        // without it.
+        if let Some(window) = &self.gfx.window {
+            window.set_cursor_grab(grab).ok();
+            window.set_cursor_visible(!grab);
+        }
+    }
+
+    /// Turns the camera by the given angles (radians).
+    fn rotate_camera(&mut self, yaw: f64, pitch: f64) {
+        self.viewport.pitch =
+            (self.viewport.pitch + pitch as f32).clamp(-TAU / 4.0 + 0.001, TAU / 4.0 - 0.001);
+        self.viewport.yaw = (self.viewport.yaw + yaw as f32) % TAU;
+    }
+
+    /// Renders a frame, saves it if asked to, and presents it to the
+    /// window if there is one.
     fn redraw(&mut self) -> anyhow::Result<()> {
+        self.render();
+        if std::mem::take(&mut self.screenshot) {
+            let path = capture::save_screenshot(&self.target.read(&self.gfx)?)?;
+            let message = format!("Saved screenshot to {}", path.display());
+            self.status = Some((message, STATUS_DURATION));
+        }
+        if let Some(export) = &mut self.export {
+            export.save(&self.target.read(&self.gfx)?)?;
+        }
+        self.present()
+    }
+
+    /// Draws the scene and HUD into the render target, resizing it to the
+    /// current frame size first.
+    fn render(&mut self) {
+        let size = self.gfx.size();
+        // Targets are at least a pixel across, even for minimized windows.
+        if (self.target.width, self.target.height) != (size.width.max(1), size.height.max(1)) {
+            self.target = RenderTarget::new(&self.gfx, size.width, size.height);
+        }
+
+        let mut encoder = self.gfx.device.create_command_encoder(&Default::default());
+        self.scene.draw(
+            &mut encoder,
+            &self.target.view,
+            &self.target.depth_view,
+            &self.viewport,
+        );
+        self.hud.draw(
+            &mut encoder,
+            &self.target.view,
+            &self.target.depth_view,
+            &self.viewport,
+        );
+        self.gfx.queue.submit([encoder.finish()]);
+    }
+
+    /// Copies the render target to the window.
+    fn present(&mut self) -> anyhow::Result<()> {
+        let surface = match &self.gfx.surface {
+            Some(surface) => surface,
+            None => return Ok(()),
+        };
         let frame = loop {
-            match self.gfx.surface.get_current_texture() {
+            match surface.get_current_texture() {
                 Ok(frame) => {
                     if frame.suboptimal {
                         self.gfx.reconfigure();
@@ -187,29 +720,9 @@ impl App {
             }
         };
 
-        let size = self.gfx.window.inner_size();
-
-        let depth_texture = self.gfx.device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("depth_texture"),
-            size: wgpu::Extent3d {
-                width: size.width,
-                height: size.height,
-                ..Default::default()
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: self.gfx.depth_format,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-        });
-
         let frame_view = frame.texture.create_view(&Default::default());
-        let depth_view = depth_texture.create_view(&Default::default());
         let mut encoder = self.gfx.device.create_command_encoder(&Default::default());
-        self.scene
-            .draw(&mut encoder, &frame_view, &depth_view, &self.viewport);
-        self.hud.draw(&mut encoder, &frame_view, &self.viewport);
-
+        self.presenter.draw(&mut encoder, &self.target, &frame_view);
         self.gfx.queue.submit([encoder.finish()]);
         frame.present();
 
@@ -218,29 +731,47 @@ impl App {
 
     fn window_resized(&mut self) {
         self.gfx.reconfigure();
-        // self.hud.resized();
+        self.hud.resized();
     }
 }
 
 fn main() -> anyhow::Result<()> {
     env_logger::init();
 
+    let options = Options::from_args()?;
+    if options.headless {
+        let (width, height) = WINDOW_SIZE;
+        let gfx = Arc::new(block_on(GraphicsContextInner::offscreen(width, height))?);
+        let mut app = App::new(gfx, &options)?;
+        while !app.replay_finished() {
+            app.update()?;
+            app.redraw()?;
+        }
+        return Ok(());
+    }
+
     let event_loop = EventLoop::with_user_event();
+    let (width, height) = WINDOW_SIZE;
     let window = WindowBuilder::new()
-        .with_inner_size(LogicalSize::<i32>::new(1280, 720))
+        .with_inner_size(LogicalSize::new(width, height))
         .with_title("Exspherement")
         .build(&event_loop)?;
     window.set_cursor_grab(true).context("cannot grab cursor")?;
     window.set_cursor_visible(false);
 
-    let mut app = block_on(App::new(window))?;
+    let gfx = Arc::new(block_on(GraphicsContextInner::new(window))?);
+    gfx.reconfigure();
+    let mut app = App::new(gfx, &options)?;
 
     event_loop.run(move |event, _, control_flow| {
-        app.event(&event);
+        app.event(&event).unwrap();
         match event {
             Event::RedrawRequested(..) => {
-                app.update();
+                app.update().unwrap();
                 app.redraw().unwrap();
+                if app.replay_finished() {
+                    *control_flow = ControlFlow::Exit;
+                }
             }
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => {
@@ -252,7 +783,9 @@ fn main() -> anyhow::Result<()> {
                 _ => {}
             },
             Event::MainEventsCleared => {
-                app.gfx.window.request_redraw();
+                if let Some(window) = &app.gfx.window {
+                    window.request_redraw();
+                }
             }
             _ => {}
         }
@@ -0,0 +1,241 @@
+//! Top-down overview of a planetary system.
+
+use glam::DVec3;
+use valet::Tag;
+
+use crate::{
+    viewport::{self, CameraPose},
+    world::{Body, World},
+};
+
+/// Zoom factor applied per line of mouse wheel scrolling.
+const ZOOM_PER_LINE: f64 = 1.15;
+
+/// Margin around the framed system, as a multiple of its extent.
+const MARGIN: f64 = 1.2;
+
+/// Lowest height above the center, in multiples of its radius.
+const MIN_HEIGHT: f64 = 1.05;
+
+/// Highest height above the center (meters).
+const MAX_HEIGHT: f64 = 1.0e14;
+
+/// Map mode: looks straight down on the system around the focused body.
+pub struct MapView {
+    zoom: f64,
+    /// Pose of the camera from before entering the map.
+    saved: Option<CameraPose>,
+}
+
+impl MapView {
+    pub fn new() -> Self {
+        Self {
+            zoom: 1.0,
+            saved: None,
+        }
+    }
+
+    /// Switch over to the map, remembering the current camera `pose`.
+    pub fn enter(&mut self, pose: CameraPose) {
+        self.saved = Some(pose);
+        self.zoom = 1.0;
+    }
+
+    /// Leave the map, returning the camera pose from before entering it.
+    pub fn exit(&mut self) -> Option<CameraPose> {
+        self.saved.take()
+    }
+
+    /// Zoom in by the given number of scroll wheel lines (out, if negative).
+    pub fn zoom(&mut self, world: &World, focus: Option<&Tag<Body>>, lines: f64) {
+        self.zoom *= ZOOM_PER_LINE.powf(lines);
+        // Stop at the height limits, so that zooming back responds at once.
+        if let Some(center) = Self::center(world, focus) {
+            self.zoom = Self::full_height(world, &center) / self.height(world, &center);
+        }
+    }
+
+    /// The body whose system is shown when `focus` is selected.
+    ///
+    /// This is the focus itself if anything orbits it, otherwise its parent.
+    pub fn center(world: &World, focus: Option<&Tag<Body>>) -> Option<Tag<Body>> {
+        let focus = match focus {
+            Some(focus) => focus,
+            None => return world.root().copied(),
+        };
+        let body = world.body(focus);
+        if body.satellites().is_empty() {
+            body.parent().or_else(|| world.root()).copied()
+        } else {
+            Some(*focus)
+        }
+    }
+
+    /// Distance from the center to the edge of the framed system (meters).
+    ///
+    /// Frames every bound satellite orbit. Escape trajectories are framed up
+    /// to the edge of the sphere of influence, or to their periapsis around
+    /// the root, whose sphere of influence is infinite.
+    fn extent(world: &World, center: &Tag<Body>) -> f64 {
+        let soi = world.soi_radius(center);
+        let body = world.body(center);
+        body.satellites()
+            .iter()
+            .filter_map(|tag| {
+                let shape = world.body(tag).orbit()?.shape();
+                Some(if shape.is_elliptic() {
+                    shape.ra()
+                } else {
+                    soi.unwrap_or_else(|| shape.rp())
+                })
+            })
+            .fold(body.radius(), f64::max)
+    }
+
+    /// Height above `center` at which the framed system fills the view.
+    fn full_height(world: &World, center: &Tag<Body>) -> f64 {
+        MARGIN * Self::extent(world, center) / (0.5 * viewport::FOV as f64).tan()
+    }
+
+    /// Height above `center` after zooming (meters).
+    fn height(&self, world: &World, center: &Tag<Body>) -> f64 {
+        let min_height = world.body(center).radius() * MIN_HEIGHT;
+        (Self::full_height(world, center) / self.zoom).clamp(min_height, MAX_HEIGHT.max(min_height))
+    }
+
+    /// Camera pose looking down on the system around `focus`.
+    pub fn pose(&self, world: &World, focus: Option<&Tag<Body>>) -> Option<CameraPose> {
+        let center = Self::center(world, focus)?;
+        let height = self.height(world, &center);
+        Some(CameraPose {
+            position: (world.body(&center).position() + height * DVec3::Z).as_vec3(),
+            frame: Default::default(),
+            pitch: -std::f32::consts::TAU / 4.0,
+            z_near: (height * 1.0e-3) as f32,
+            z_far: (height * 10.0) as f32,
+        })
+    }
+}
+
+impl Default for MapView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Quat, Vec3};
+
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= 1e-9 * expected.abs(),
+            "{} isn't {}",
+            actual,
+            expected
+        );
+    }
+
+    fn find(world: &World, name: &str) -> Tag<Body> {
+        *world
+            .body_tags
+            .iter()
+            .find(|tag| world.body(tag).name() == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn centers_on_the_focus_or_its_parent() {
+        let world = World::new();
+        let sun = find(&world, "Sun");
+        let earth = find(&world, "Earth");
+        let moon = find(&world, "Moon");
+        let craft = find(&world, "Craft");
+
+        assert_eq!(MapView::center(&world, None), Some(sun));
+        assert_eq!(MapView::center(&world, Some(&sun)), Some(sun));
+        assert_eq!(MapView::center(&world, Some(&earth)), Some(earth));
+        assert_eq!(MapView::center(&world, Some(&moon)), Some(moon));
+        assert_eq!(MapView::center(&world, Some(&craft)), Some(moon));
+    }
+
+    #[test]
+    fn extent_encloses_every_orbit() {
+        let world = World::new();
+        for center in &world.body_tags {
+            let extent = MapView::extent(&world, center);
+            for tag in world.body(center).satellites() {
+                let shape = world.body(tag).orbit().unwrap().shape();
+                assert!(
+                    shape.ra() <= extent,
+                    "{} reaches {} from {}, past {}",
+                    world.body(tag).name(),
+                    shape.ra(),
+                    world.body(center).name(),
+                    extent
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn view_fits_the_extent_and_zooms() {
+        let world = World::new();
+        let earth = find(&world, "Earth");
+        let mut map = MapView::new();
+        let height = map.height(&world, &earth);
+        let half_fov = 0.5 * viewport::FOV as f64;
+        let visible = height * half_fov.tan();
+        assert!(visible >= MapView::extent(&world, &earth));
+
+        map.zoom(&world, Some(&earth), 1.0);
+        assert_close(map.height(&world, &earth), height / ZOOM_PER_LINE);
+    }
+
+    #[test]
+    fn zoom_is_clamped_to_the_surface_and_the_maximum() {
+        let world = World::new();
+        let earth = find(&world, "Earth");
+        let mut map = MapView::new();
+
+        map.zoom(&world, Some(&earth), 1000.0);
+        let radius = world.body(&earth).radius();
+        assert_close(map.height(&world, &earth), MIN_HEIGHT * radius);
+        let pose = map.pose(&world, Some(&earth)).unwrap();
+        assert!(pose.z_near > 0.0 && pose.z_far.is_finite());
+
+        // Zooming back out starts right away from the limit.
+        map.zoom(&world, Some(&earth), -1.0);
+        assert_close(
+            map.height(&world, &earth),
+            MIN_HEIGHT * radius * ZOOM_PER_LINE,
+        );
+
+        map.zoom(&world, Some(&earth), -1000.0);
+        assert_close(map.height(&world, &earth), MAX_HEIGHT);
+        let pose = map.pose(&world, Some(&earth)).unwrap();
+        assert!(pose.z_near > 0.0 && pose.z_far.is_finite());
+    }
+
+    #[test]
+    fn leaving_restores_the_camera() {
+        let world = World::new();
+        let earth = find(&world, "Earth");
+        let before = CameraPose {
+            position: Vec3::new(1.0, 2.0, 3.0),
+            frame: Quat::from_rotation_x(0.5),
+            pitch: 0.25,
+            z_near: viewport::Z_NEAR,
+            z_far: viewport::Z_FAR,
+        };
+        let mut map = MapView::new();
+        map.enter(before);
+        map.zoom(&world, Some(&earth), 3.0);
+        let pose = map.pose(&world, Some(&earth)).unwrap();
+        assert_ne!(pose, before);
+        assert_eq!(map.exit(), Some(before));
+        assert_eq!(map.exit(), None);
+    }
+}
@@ -0,0 +1,234 @@
+//! Texture maps for the scene shader.
+//!
+//! Every draw in the scene binds a [`Material`] with an albedo map, which
+//! multiplies the vertex and instance colors, and a tangent-space normal map
+//! in the OpenGL convention, with green pointing up the texture. Meshes
+//! without textures use [`Materials::default`], which changes nothing.
+
+use std::path::{Path, PathBuf};
+
+use crate::{
+    texture::{Image, Texture},
+    GraphicsContext,
+};
+
+/// Normal map texel for a normal straight out of the surface.
+const FLAT_NORMAL: [u8; 4] = [128, 128, 255, 255];
+
+/// Image files to texture a body with, relative to the working directory.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextureMaps {
+    /// Equirectangular map of the colors of the surface, with the body
+    /// frame's +X axis in the middle and +Z at the top.
+    pub albedo: Option<PathBuf>,
+    /// Equirectangular normal map with the same layout.
+    pub normal: Option<PathBuf>,
+}
+
+impl TextureMaps {
+    /// The maps in `dir` for the body called `name`, e.g.
+    /// `earth_albedo.png` and `earth_normal.png` for Earth, or `None` if
+    /// neither file exists.
+    pub fn find(dir: &Path, name: &str) -> Option<Self> {
+        let map = |kind| {
+            let path = dir.join(format!("{}_{}.png", name.to_lowercase(), kind));
+            path.is_file().then_some(path)
+        };
+        let maps = Self {
+            albedo: map("albedo"),
+            normal: map("normal"),
+        };
+        (maps != Self::default()).then_some(maps)
+    }
+}
+
+pub struct Material {
+    bind_group: wgpu::BindGroup,
+    has_albedo_map: bool,
+}
+
+impl Material {
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    /// Whether the albedo map replaces colors that the mesh would otherwise
+    /// get from its vertices.
+    pub fn has_albedo_map(&self) -> bool {
+        self.has_albedo_map
+    }
+}
+
+/// Shared state for creating materials, and the default material.
+pub struct Materials {
+    gfx: GraphicsContext,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    white: Texture,
+    flat: Texture,
+    default: Material,
+}
+
+impl Materials {
+    pub fn new(gfx: &GraphicsContext) -> Self {
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let bind_group_layout =
+            gfx.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Materials::bind_group_layout"),
+                    entries: &[
+                        texture_entry(0),
+                        texture_entry(1),
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 2,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                            count: None,
+                        },
+                    ],
+                });
+
+        let sampler = gfx.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Materials::sampler"),
+            // Texture coordinates may go past the edges around the seam of
+            // equirectangular maps.
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let white = Texture::new(gfx, Some("Materials::white"), &Image::solid([u8::MAX; 4]))
+            .expect("failed to create the default albedo map");
+        let flat = Texture::new(gfx, Some("Materials::flat"), &Image::solid(FLAT_NORMAL))
+            .expect("failed to create the default normal map");
+        let default = Self::create(gfx, &bind_group_layout, &sampler, &white, &flat, false);
+
+        Self {
+            gfx: gfx.clone(),
+            bind_group_layout,
+            sampler,
+            white,
+            flat,
+            default,
+        }
+    }
+
+    fn create(
+        gfx: &GraphicsContext,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        albedo: &Texture,
+        normal: &Texture,
+        has_albedo_map: bool,
+    ) -> Material {
+        let bind_group = gfx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Material::bind_group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&albedo.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&normal.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        });
+        Material {
+            bind_group,
+            has_albedo_map,
+        }
+    }
+
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    /// A white material with flat normals.
+    pub fn default(&self) -> &Material {
+        &self.default
+    }
+
+    /// Loads the maps from PNG files. Missing files are not an error, and
+    /// are replaced by the default maps; if both are missing, there's no
+    /// material at all.
+    pub fn load(&self, maps: &TextureMaps) -> anyhow::Result<Option<Material>> {
+        let load = |path: &Option<PathBuf>, label| -> anyhow::Result<Option<Texture>> {
+            match path {
+                Some(path) => Image::load_png(path)?
+                    .map(|image| Texture::new(&self.gfx, label, &image))
+                    .transpose(),
+                None => Ok(None),
+            }
+        };
+        let albedo = load(&maps.albedo, Some("Material::albedo"))?;
+        let normal = load(&maps.normal, Some("Material::normal"))?;
+        if albedo.is_none() && normal.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Self::create(
+            &self.gfx,
+            &self.bind_group_layout,
+            &self.sampler,
+            albedo.as_ref().unwrap_or(&self.white),
+            normal.as_ref().unwrap_or(&self.flat),
+            albedo.is_some(),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::texture::Image;
+
+    #[test]
+    fn maps_are_found_by_body_name() {
+        let dir = std::env::temp_dir().join(format!("material-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let albedo = dir.join("earth_albedo.png");
+        Image::solid([0, 0, 255, 255]).save_png(&albedo).unwrap();
+        let normal = dir.join("moon_normal.png");
+        Image::solid(FLAT_NORMAL).save_png(&normal).unwrap();
+
+        let earth = TextureMaps::find(&dir, "Earth");
+        let moon = TextureMaps::find(&dir, "Moon");
+        let sun = TextureMaps::find(&dir, "Sun");
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            earth,
+            Some(TextureMaps {
+                albedo: Some(albedo),
+                normal: None,
+            })
+        );
+        assert_eq!(
+            moon,
+            Some(TextureMaps {
+                albedo: None,
+                normal: Some(normal),
+            })
+        );
+        assert_eq!(sun, None);
+    }
+}
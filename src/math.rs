@@ -1,6 +1,126 @@
-use glam::Vec3;
+use glam::{DVec3, Mat4, Vec2};
 
-pub fn slerp(a: Vec3, b: Vec3, t: f32) -> Vec3 {
-    let theta = a.angle_between(b);
-    (((1.0 - t) * theta).sin() * a + (t * theta).sin() * b) / theta.sin()
+/// Half-line starting at `origin`, going along the unit vector `direction`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: DVec3,
+    pub direction: DVec3,
+}
+
+impl Ray {
+    /// Ray from the near plane through a point on the screen, given in
+    /// normalized device coordinates, for a camera with the given
+    /// view-projection matrix.
+    ///
+    /// The inverse is taken in double precision, so that rays stay accurate
+    /// far away from the origin.
+    pub fn unproject(view_proj: Mat4, ndc: Vec2) -> Self {
+        let inverse = view_proj.as_dmat4().inverse();
+        let near = inverse.project_point3(ndc.as_dvec2().extend(0.0));
+        let far = inverse.project_point3(ndc.as_dvec2().extend(1.0));
+        Self {
+            origin: near,
+            direction: (far - near).normalize(),
+        }
+    }
+
+    /// Distance along the ray to where it enters the sphere, or `None` if it
+    /// misses. Zero if the ray starts inside the sphere.
+    pub fn intersect_sphere(&self, center: DVec3, radius: f64) -> Option<f64> {
+        let offset = self.origin - center;
+        let b = offset.dot(self.direction);
+        let c = offset.length_squared() - radius * radius;
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let far = -b + discriminant.sqrt();
+        if far < 0.0 {
+            return None;
+        }
+        Some((-b - discriminant.sqrt()).max(0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{DVec2, Vec3};
+
+    use super::*;
+
+    fn view_proj() -> Mat4 {
+        let projection = Mat4::perspective_rh(1.2, 1.5, 0.1, 1000.0);
+        let camera = Mat4::look_at_rh(Vec3::new(3.0, -5.0, 2.0), Vec3::new(0.5, 1.0, 0.0), Vec3::Z);
+        projection * camera
+    }
+
+    fn assert_close(a: DVec3, b: DVec3, tolerance: f64) {
+        assert!((a - b).length() < tolerance, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn unprojected_rays_pass_through_projected_points() {
+        let view_proj = view_proj();
+        let eye = DVec3::new(3.0, -5.0, 2.0);
+        for point in [
+            DVec3::new(0.5, 1.0, 0.0),
+            DVec3::new(-2.0, 4.0, 3.0),
+            DVec3::new(10.0, 30.0, -5.0),
+        ] {
+            let clip = view_proj.as_dmat4() * point.extend(1.0);
+            let ndc = (DVec2::new(clip.x, clip.y) / clip.w).as_vec2();
+            let ray = Ray::unproject(view_proj, ndc);
+
+            // The ray starts on the near plane and points away from the eye.
+            let near = view_proj.as_dmat4() * ray.origin.extend(1.0);
+            assert!((near.z / near.w).abs() < 1e-6);
+            assert!((ray.direction.length() - 1.0).abs() < 1e-9);
+            assert!(ray.direction.dot(ray.origin - eye) > 0.0);
+
+            let t = (point - ray.origin).dot(ray.direction);
+            assert!(t > 0.0);
+            let closest = ray.origin + ray.direction * t;
+            assert_close(closest, point, 1e-4 * (point - eye).length());
+        }
+    }
+
+    #[test]
+    fn center_of_the_screen_looks_forward() {
+        let ray = Ray::unproject(view_proj(), Vec2::ZERO);
+        let forward = (DVec3::new(0.5, 1.0, 0.0) - DVec3::new(3.0, -5.0, 2.0)).normalize();
+        assert_close(ray.direction, forward, 1e-6);
+    }
+
+    #[test]
+    fn rays_hit_spheres_in_front() {
+        let ray = Ray {
+            origin: DVec3::new(0.0, -10.0, 0.0),
+            direction: DVec3::Y,
+        };
+        assert_eq!(ray.intersect_sphere(DVec3::ZERO, 2.0), Some(8.0));
+        // Grazing the edge.
+        let hit = ray
+            .intersect_sphere(DVec3::new(2.0, 0.0, 0.0), 2.0)
+            .unwrap();
+        assert!((hit - 10.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rays_miss_spheres_beside_and_behind() {
+        let ray = Ray {
+            origin: DVec3::new(0.0, -10.0, 0.0),
+            direction: DVec3::Y,
+        };
+        assert_eq!(ray.intersect_sphere(DVec3::new(3.0, 0.0, 0.0), 2.0), None);
+        assert_eq!(ray.intersect_sphere(DVec3::new(0.0, -20.0, 0.0), 2.0), None);
+    }
+
+    #[test]
+    fn rays_inside_spheres_hit_immediately() {
+        let ray = Ray {
+            origin: DVec3::new(0.5, 0.0, 0.0),
+            direction: DVec3::new(0.0, 0.6, 0.8),
+        };
+        assert_eq!(ray.intersect_sphere(DVec3::ZERO, 2.0), Some(0.0));
+    }
 }
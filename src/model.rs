@@ -1,31 +1,50 @@
-use std::ops::Range;
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
 
 use bytemuck::{Pod, Zeroable};
-use glam::Vec3;
+use glam::{Vec2, Vec3};
 use once_cell::sync::Lazy;
 use wgpu::util::DeviceExt;
 
 use crate::GraphicsContext;
 
-pub static VERTEX_ATTRIBUTES: Lazy<[wgpu::VertexAttribute; 2]> = Lazy::new(|| {
+pub static VERTEX_ATTRIBUTES: Lazy<[wgpu::VertexAttribute; 4]> = Lazy::new(|| {
     wgpu::vertex_attr_array![
         0 => Float32x3,
         1 => Float32x3,
+        2 => Float32x3,
+        3 => Float32x2,
     ]
 });
 
-pub const INDEX_FORMAT: wgpu::IndexFormat = wgpu::IndexFormat::Uint16;
+/// Index format for a mesh with this many vertices: 16-bit indices when
+/// they can address every vertex, since they take half the memory, and
+/// 32-bit indices otherwise.
+pub fn index_format(vertex_count: usize) -> wgpu::IndexFormat {
+    if vertex_count <= u16::MAX as usize + 1 {
+        wgpu::IndexFormat::Uint16
+    } else {
+        wgpu::IndexFormat::Uint32
+    }
+}
 
 #[derive(Default, Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
 pub struct Vertex {
     pub position: [f32; 3],
     pub normal: [f32; 3],
+    /// Multiplied with the albedo of the instance.
+    pub color: [f32; 3],
+    /// Texture coordinates, with the origin at the top left.
+    pub uv: [f32; 2],
 }
 
 pub struct Model {
     pub vertex_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
+    pub index_format: wgpu::IndexFormat,
     pub index_range: Range<u32>,
 }
 
@@ -34,7 +53,7 @@ impl Model {
         gfx: &GraphicsContext,
         label: Option<&'static str>,
         vertices: &[Vertex],
-        tris: &[u16],
+        tris: &[u32],
     ) -> Self {
         let vertex_buffer = gfx
             .device
@@ -43,11 +62,20 @@ impl Model {
                 contents: bytemuck::cast_slice(vertices),
                 usage: wgpu::BufferUsages::VERTEX,
             });
+        let index_format = index_format(vertices.len());
+        let short_tris: Vec<u16>;
+        let index_data = match index_format {
+            wgpu::IndexFormat::Uint16 => {
+                short_tris = tris.iter().map(|&index| index as u16).collect();
+                bytemuck::cast_slice(&short_tris)
+            }
+            wgpu::IndexFormat::Uint32 => bytemuck::cast_slice(tris),
+        };
         let index_buffer = gfx
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label,
-                contents: bytemuck::cast_slice(tris),
+                contents: index_data,
                 usage: wgpu::BufferUsages::INDEX,
             });
 
@@ -56,31 +84,184 @@ impl Model {
         Self {
             vertex_buffer,
             index_buffer,
+            index_format,
             index_range,
         }
     }
 
+    /// Uploads a mesh built on the CPU.
+    pub fn from_mesh(gfx: &GraphicsContext, label: Option<&'static str>, mesh: &MeshData) -> Self {
+        Self::new(gfx, label, &mesh.vertices(), &mesh.indices)
+    }
+
     pub fn with_computed_normals(
         gfx: &GraphicsContext,
         label: Option<&'static str>,
         vertices: &[Vec3],
-        tris: &[u16],
+        tris: &[u32],
     ) -> Self {
-        let mut vertex_data = vec![Vertex::default(); vertices.len()];
-        for (i, &vec) in vertices.iter().enumerate() {
-            vertex_data[i].position = vec.into();
-        }
-        for tri in tris.chunks_exact(3) {
-            let a = vertices[tri[0] as usize];
-            let b = vertices[tri[1] as usize];
-            let c = vertices[tri[2] as usize];
+        let mesh = MeshData::with_computed_normals(vertices.to_vec(), tris.to_vec());
+        Self::from_mesh(gfx, label, &mesh)
+    }
+}
+
+/// A triangle mesh on the CPU, which can be built and checked without a
+/// device, then uploaded as a [`Model`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshData {
+    pub positions: Vec<Vec3>,
+    /// Unit normal of each vertex, in the same order as `positions`.
+    pub normals: Vec<Vec3>,
+    /// Color of each vertex, in the same order as `positions`.
+    pub colors: Vec<Vec3>,
+    /// Texture coordinates of each vertex, in the same order as `positions`.
+    pub uvs: Vec<Vec2>,
+    /// Vertex indices, three per triangle, wound clockwise seen from the
+    /// front.
+    pub indices: Vec<u32>,
+}
+
+impl MeshData {
+    /// A white mesh whose vertex normals are the average of the normals of
+    /// the triangles around them, so that shared vertices shade smoothly.
+    pub fn with_computed_normals(positions: Vec<Vec3>, indices: Vec<u32>) -> Self {
+        let mut mesh = Self {
+            normals: Vec::new(),
+            colors: vec![Vec3::ONE; positions.len()],
+            uvs: vec![Vec2::ZERO; positions.len()],
+            positions,
+            indices,
+        };
+        mesh.compute_normals();
+        mesh
+    }
+
+    /// Replaces the normals with the average of the normals of the triangles
+    /// around each vertex.
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![Vec3::ZERO; self.positions.len()];
+        for tri in self.indices.chunks_exact(3) {
+            let a = self.positions[tri[0] as usize];
+            let b = self.positions[tri[1] as usize];
+            let c = self.positions[tri[2] as usize];
+            // Triangles are wound clockwise seen from the front.
             //TODO should larger faces have larger weight (unnormalize here)?
-            let normal = (b - a).cross(c - a).normalize();
+            let normal = (c - a).cross(b - a).normalize_or_zero();
             for &i in tri {
-                let vertex = &mut vertex_data[i as usize];
-                vertex.normal = (Vec3::from(vertex.normal) + normal).into();
+                normals[i as usize] += normal;
             }
         }
-        Self::new(gfx, label, &vertex_data, tris)
+        for normal in &mut normals {
+            *normal = normal.normalize_or_zero();
+        }
+        self.normals = normals;
+    }
+
+    pub fn vertices(&self) -> Vec<Vertex> {
+        self.positions
+            .iter()
+            .zip(&self.normals)
+            .zip(&self.colors)
+            .zip(&self.uvs)
+            .map(|(((&position, &normal), &color), &uv)| Vertex {
+                position: position.into(),
+                normal: normal.into(),
+                color: color.into(),
+                uv: uv.into(),
+            })
+            .collect()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Number of distinct edges between vertices.
+    pub fn edge_count(&self) -> usize {
+        self.edges()
+            .map(|(a, b)| (a.min(b), a.max(b)))
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Vertices minus edges plus triangles, which is 2 for a closed mesh
+    /// shaped like a sphere.
+    pub fn euler_characteristic(&self) -> i64 {
+        self.positions.len() as i64 - self.edge_count() as i64 + self.triangle_count() as i64
+    }
+
+    /// Whether every edge is shared by exactly two triangles, wound in
+    /// opposite directions along it, so that the mesh has no holes or
+    /// seams.
+    pub fn is_watertight(&self) -> bool {
+        let mut edges = HashMap::new();
+        for edge in self.edges() {
+            *edges.entry(edge).or_insert(0) += 1;
+        }
+        edges
+            .iter()
+            .all(|(&(a, b), &count)| count == 1 && edges.get(&(b, a)) == Some(&1))
+    }
+
+    /// Directed edges of every triangle, following the winding.
+    fn edges(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.indices
+            .chunks_exact(3)
+            .flat_map(|tri| [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tetrahedron wound clockwise seen from outside.
+    fn tetrahedron() -> MeshData {
+        MeshData::with_computed_normals(
+            vec![
+                Vec3::new(1.0, 1.0, 1.0),
+                Vec3::new(1.0, -1.0, -1.0),
+                Vec3::new(-1.0, 1.0, -1.0),
+                Vec3::new(-1.0, -1.0, 1.0),
+            ],
+            vec![0, 2, 1, 0, 1, 3, 0, 3, 2, 1, 2, 3],
+        )
+    }
+
+    #[test]
+    fn closed_meshes_are_watertight() {
+        let mesh = tetrahedron();
+        assert_eq!(mesh.triangle_count(), 4);
+        assert_eq!(mesh.edge_count(), 6);
+        assert_eq!(mesh.euler_characteristic(), 2);
+        assert!(mesh.is_watertight());
+    }
+
+    #[test]
+    fn holes_and_flipped_triangles_leak() {
+        let mut open = tetrahedron();
+        open.indices.truncate(9);
+        assert!(!open.is_watertight());
+
+        let mut flipped = tetrahedron();
+        flipped.indices.swap(0, 1);
+        assert!(!flipped.is_watertight());
+    }
+
+    #[test]
+    fn normals_point_out_of_the_front() {
+        let mesh = tetrahedron();
+        for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+            assert!((normal.length() - 1.0).abs() < 1e-6);
+            assert!((*normal - position.normalize()).length() < 1e-6);
+        }
+        assert_eq!(mesh.vertices().len(), 4);
+    }
+
+    #[test]
+    fn index_format_fits_the_vertices() {
+        assert_eq!(index_format(3), wgpu::IndexFormat::Uint16);
+        assert_eq!(index_format(65536), wgpu::IndexFormat::Uint16);
+        assert_eq!(index_format(65537), wgpu::IndexFormat::Uint32);
     }
 }
@@ -71,14 +71,95 @@ impl Orbit2D {
     }
 
     /// Semi-minor axis
+    ///
+    /// For hyperbolas, this is the conjugate semi-axis, which is still
+    /// positive.
     pub fn b(&self) -> f64 {
-        self.p / (1.0 - self.e.powi(2)).sqrt()
+        self.p / (1.0 - self.e.powi(2)).abs().sqrt()
     }
 
     pub fn radius_at(&self, angle: f64) -> f64 {
         self.p / (1.0 + self.e * angle.cos())
     }
 
+    /// Non-negative true anomaly at which the orbit reaches the given
+    /// radius, or `None` if it never does.
+    pub fn angle_at_radius(&self, radius: f64) -> Option<f64> {
+        let cos = (self.p / radius - 1.0) / self.e;
+        if (-1.0..=1.0).contains(&cos) {
+            Some(cos.acos())
+        } else {
+            None
+        }
+    }
+
+    /// True anomaly of the outbound asymptote, which an open trajectory
+    /// approaches as it goes to infinity. `None` for closed orbits.
+    pub fn asymptote_angle(&self) -> Option<f64> {
+        if self.is_elliptic() {
+            None
+        } else {
+            Some((-1.0 / self.e).acos())
+        }
+    }
+
+    /// Hyperbolic excess velocity: the speed an open trajectory approaches
+    /// at infinity. `None` for closed orbits.
+    pub fn v_inf(&self) -> Option<f64> {
+        if self.is_elliptic() {
+            None
+        } else {
+            Some((-self.grav * self.alpha()).max(0.0).sqrt())
+        }
+    }
+
+    /// Orbital speed at the given true anomaly, from the vis-viva equation.
+    pub fn speed_at(&self, angle: f64) -> f64 {
+        (self.grav * (2.0 / self.radius_at(angle) - self.alpha())).sqrt()
+    }
+
+    /// Time from periapsis to the given true anomaly (seconds), which is
+    /// negative for points before periapsis. `angle` should be within
+    /// `-pi..pi`.
+    pub fn time_since_periapsis(&self, angle: f64) -> f64 {
+        let e = self.e;
+        let half_tan = (angle / 2.0).tan();
+        match e.partial_cmp(&1.0) {
+            Some(Ordering::Less) => {
+                // Elliptical
+                let ea = 2.0 * (((1.0 - e) / (1.0 + e)).sqrt() * half_tan).atan();
+                let ma = ea - e * ea.sin();
+                ma * (self.a().powi(3) / self.grav).sqrt()
+            }
+            Some(Ordering::Greater) => {
+                // Hyperbolic
+                let fa = 2.0 * (((e - 1.0) / (e + 1.0)).sqrt() * half_tan).atanh();
+                let ma = e * fa.sinh() - fa;
+                ma * (-self.a().powi(3) / self.grav).sqrt()
+            }
+            _ => {
+                // Parabolic
+                let ma = half_tan + half_tan.powi(3) / 3.0;
+                0.5 * ma * (self.p.powi(3) / self.grav).sqrt()
+            }
+        }
+    }
+
+    /// First time at or after `now` that the orbit passes through the given
+    /// true anomaly, or `None` if an open trajectory has already passed it.
+    pub fn next_time_at(&self, angle: f64, now: SimInstant) -> Option<SimInstant> {
+        let angle = (angle + TAU / 2.0).rem_euclid(TAU) - TAU / 2.0;
+        let time = self.t0 + SimDuration::from_secs_f64(self.time_since_periapsis(angle));
+        let until = (time - now).as_secs_f64();
+        match self.period() {
+            Some(period) => {
+                Some(now + SimDuration::from_secs_f64(until.rem_euclid(period.as_secs_f64())))
+            }
+            None if until >= 0.0 => Some(time),
+            None => None,
+        }
+    }
+
     /// Reciprocal of semi-major axis
     fn alpha(&self) -> f64 {
         (1.0 - self.e.powi(2)) / self.p
@@ -305,7 +386,26 @@ impl Orbit3D {
         self.orientation() * DVec3::new(r * angle.cos(), r * angle.sin(), 0.0)
     }
 
-    fn orientation(&self) -> DQuat {
+    /// Center of the conic, relative to the focus. For parabolas, this is at
+    /// infinity.
+    pub fn center(&self) -> DVec3 {
+        -self.shape.e() * self.a_vector()
+    }
+
+    /// True anomaly of the outbound asymptote. See
+    /// [`Orbit2D::asymptote_angle`].
+    pub fn asymptote_angle(&self) -> Option<f64> {
+        self.shape.asymptote_angle()
+    }
+
+    /// Hyperbolic excess velocity. See [`Orbit2D::v_inf`].
+    pub fn v_inf(&self) -> Option<f64> {
+        self.shape.v_inf()
+    }
+
+    /// Rotation from the orbital plane (periapsis along +X, angular momentum
+    /// along +Z) to the reference frame.
+    pub fn orientation(&self) -> DQuat {
         return DQuat::from_rotation_z(self.lan)
             * DQuat::from_rotation_x(self.inc)
             * DQuat::from_rotation_z(self.arg_pe);
@@ -354,3 +454,163 @@ impl State3D {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9 * expected.abs().max(1.0),
+            "{} isn't {}",
+            actual,
+            expected
+        );
+    }
+
+    fn ellipse() -> Orbit2D {
+        Orbit2D::from_apsides(3.0, 1.0, SimInstant::epoch(), 4.0)
+    }
+
+    fn hyperbola() -> Orbit2D {
+        Orbit2D::new(2.0, 3.0, SimInstant::epoch(), 5.0)
+    }
+
+    #[test]
+    fn hyperbolas_have_negative_semi_major_axes() {
+        let orbit = hyperbola();
+        assert!(orbit.is_hyperbolic());
+        assert_close(orbit.a(), -1.0);
+        assert_close(orbit.rp(), 1.0);
+        // b² = a²(e² - 1)
+        assert_close(orbit.b(), 3f64.sqrt());
+        assert_close(ellipse().b(), 3f64.sqrt());
+    }
+
+    #[test]
+    fn excess_velocity_squared_is_grav_over_a() {
+        let orbit = hyperbola();
+        assert_close(
+            orbit.v_inf().unwrap().powi(2),
+            orbit.grav() / orbit.a().abs(),
+        );
+        // It is also the limit of the orbital speed.
+        let far = orbit.angle_at_radius(1e12).unwrap();
+        assert!((orbit.speed_at(far) - orbit.v_inf().unwrap()).abs() < 1e-5);
+        assert_eq!(ellipse().v_inf(), None);
+    }
+
+    #[test]
+    fn asymptotes_are_where_the_radius_diverges() {
+        let orbit = hyperbola();
+        let angle = orbit.asymptote_angle().unwrap();
+        assert_close(angle, (-1.0 / orbit.e()).acos());
+        assert_close(angle, TAU / 3.0);
+        assert!(orbit.radius_at(angle - 1e-6) > 1e6);
+        assert!(orbit.radius_at(angle + 1e-6) < 0.0);
+        assert_eq!(ellipse().asymptote_angle(), None);
+    }
+
+    #[test]
+    fn periapsis_is_at_angle_zero() {
+        for orbit in [ellipse(), hyperbola()] {
+            assert_close(orbit.angle_at_radius(orbit.rp()).unwrap(), 0.0);
+            assert_eq!(orbit.angle_at_radius(0.9 * orbit.rp()), None);
+        }
+    }
+
+    #[test]
+    fn angle_at_radius_inverts_radius_at() {
+        for orbit in [ellipse(), hyperbola()] {
+            for radius in [1.5, 2.0, 2.9] {
+                let angle = orbit.angle_at_radius(radius).unwrap();
+                assert!((0.0..=TAU / 2.0).contains(&angle));
+                assert_close(orbit.radius_at(angle), radius);
+            }
+        }
+    }
+
+    #[test]
+    fn ellipses_never_go_beyond_apoapsis() {
+        let orbit = ellipse();
+        assert_close(orbit.angle_at_radius(orbit.ra()).unwrap(), TAU / 2.0);
+        assert_eq!(orbit.angle_at_radius(1.1 * orbit.ra()), None);
+        // Open trajectories go out forever.
+        assert!(hyperbola().angle_at_radius(1e9).is_some());
+    }
+
+    #[test]
+    fn conic_center_is_a_semi_major_axis_from_periapsis() {
+        for shape in [ellipse(), hyperbola()] {
+            let orbit = Orbit3D::new(shape, 0.3, 0.7, 1.1);
+            let periapsis = orbit.position_at(0.0);
+            assert_close((periapsis - orbit.center()).length(), shape.a().abs());
+            // Both lie on the line through the focus and periapsis.
+            assert_close(orbit.center().cross(periapsis).length(), 0.0);
+            assert_eq!(orbit.asymptote_angle(), shape.asymptote_angle());
+            assert_eq!(orbit.v_inf(), shape.v_inf());
+        }
+        // The center of a hyperbola is beyond periapsis, and an ellipse's is
+        // on the other side of the focus.
+        let hyperbola = Orbit3D::new(hyperbola(), 0.3, 0.7, 1.1);
+        assert!(hyperbola.center().dot(hyperbola.position_at(0.0)) > 0.0);
+        let ellipse = Orbit3D::new(ellipse(), 0.3, 0.7, 1.1);
+        assert!(ellipse.center().dot(ellipse.position_at(0.0)) < 0.0);
+    }
+
+    /// True anomaly of the orbit's position at the given time.
+    fn angle_at_time(orbit: &Orbit2D, time: SimInstant) -> f64 {
+        let position = orbit.current_state(time).position;
+        position.y.atan2(position.x)
+    }
+
+    fn assert_angle(actual: f64, expected: f64) {
+        let difference = (actual - expected + TAU / 2.0).rem_euclid(TAU) - TAU / 2.0;
+        assert!(difference.abs() < 1e-5, "{} isn't {}", actual, expected);
+    }
+
+    #[test]
+    fn time_since_periapsis_round_trips() {
+        let parabola = Orbit2D::new(1.0, 2.0, SimInstant::epoch(), 4.0);
+        for orbit in [ellipse(), hyperbola(), parabola] {
+            for angle in [-2.0, -0.5, 0.0, 0.3, 1.0, 2.0] {
+                let time = orbit.time_since_periapsis(angle);
+                // Points before periapsis are reached before it.
+                assert_eq!(time < 0.0, angle < 0.0);
+                let at = orbit.t0() + SimDuration::from_secs_f64(time);
+                assert_angle(angle_at_time(&orbit, at), angle);
+            }
+        }
+    }
+
+    #[test]
+    fn next_time_at_is_never_in_the_past() {
+        let orbit = ellipse();
+        let period = orbit.period().unwrap();
+        for now in [-20.0, -3.0, 0.0, 1.5, 4.0, 100.0] {
+            let now = SimInstant::epoch() + SimDuration::from_secs_f64(now);
+            for angle in [-3.0, -1.0, 0.0, 0.5, 2.0, 3.0, 5.0] {
+                let next = orbit.next_time_at(angle, now).unwrap();
+                assert!(next >= now);
+                assert!(next - now < period);
+                assert_angle(angle_at_time(&orbit, next), angle);
+            }
+        }
+    }
+
+    #[test]
+    fn open_trajectories_only_pass_points_once() {
+        let orbit = hyperbola();
+        let now = SimInstant::epoch() + SimDuration::from_secs_f64(0.5);
+        let current = angle_at_time(&orbit, now);
+        let asymptote = orbit.asymptote_angle().unwrap();
+        for angle in [current + 0.01, (current + asymptote) / 2.0] {
+            let next = orbit.next_time_at(angle, now).unwrap();
+            assert!(next >= now);
+            assert_angle(angle_at_time(&orbit, next), angle);
+        }
+        for angle in [current - 0.01, 0.0, -1.0] {
+            assert_eq!(orbit.next_time_at(angle, now), None);
+        }
+    }
+}
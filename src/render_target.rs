@@ -0,0 +1,304 @@
+//! Textures that frames are rendered into, before being presented to the
+//! window or read back to be saved.
+
+use std::num::NonZeroU32;
+
+use anyhow::Context;
+use pollster::block_on;
+use wgpu::include_wgsl;
+
+use crate::{texture::Image, GraphicsContext};
+
+/// Color and depth textures for rendering a frame offscreen.
+pub struct RenderTarget {
+    texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub depth_view: wgpu::TextureView,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl RenderTarget {
+    pub fn new(gfx: &GraphicsContext, width: u32, height: u32) -> Self {
+        let size = wgpu::Extent3d {
+            width: width.max(1),
+            height: height.max(1),
+            depth_or_array_layers: 1,
+        };
+        let texture = gfx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("RenderTarget::texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: gfx.render_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+        });
+        let depth_texture = gfx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("RenderTarget::depth_texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: gfx.depth_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        });
+        Self {
+            view: texture.create_view(&Default::default()),
+            depth_view: depth_texture.create_view(&Default::default()),
+            texture,
+            width: size.width,
+            height: size.height,
+        }
+    }
+
+    /// Copies the rendered frame back from the GPU, waiting for it to
+    /// finish. The frame is opaque, so alpha is dropped.
+    pub fn read(&self, gfx: &GraphicsContext) -> anyhow::Result<Image> {
+        let bytes_per_row = padded_bytes_per_row(self.width);
+        let buffer = gfx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("RenderTarget::read_buffer"),
+            size: (bytes_per_row * self.height) as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = gfx.device.create_command_encoder(&Default::default());
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        gfx.queue.submit([encoder.finish()]);
+
+        let slice = buffer.slice(..);
+        let mapping = slice.map_async(wgpu::MapMode::Read);
+        gfx.device.poll(wgpu::Maintain::Wait);
+        block_on(mapping).context("failed to read the frame")?;
+        let data = slice.get_mapped_range();
+        Ok(Image {
+            width: self.width,
+            height: self.height,
+            pixels: strip_row_padding(&data, self.width),
+        })
+    }
+}
+
+/// Bytes per row of a buffer that a texture of the given width is copied
+/// into, since rows of the copy have to be aligned.
+fn padded_bytes_per_row(width: u32) -> u32 {
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    (4 * width).div_ceil(align) * align
+}
+
+/// Opaque pixels of a frame copied with [`padded_bytes_per_row`], without
+/// the padding at the end of each row.
+fn strip_row_padding(data: &[u8], width: u32) -> Vec<[u8; 4]> {
+    data.chunks_exact(padded_bytes_per_row(width) as usize)
+        .flat_map(|row| row[..4 * width as usize].chunks_exact(4))
+        .map(|pixel| [pixel[0], pixel[1], pixel[2], u8::MAX])
+        .collect()
+}
+
+/// Copies render targets to the window's frames.
+pub struct Presenter {
+    gfx: GraphicsContext,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl Presenter {
+    pub fn new(gfx: &GraphicsContext) -> Self {
+        let bind_group_layout =
+            gfx.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Presenter::bind_group_layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    }],
+                });
+
+        let pipeline_layout = gfx
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Presenter::pipeline_layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let shader_module = gfx.device.create_shader_module(&include_wgsl!("blit.wgsl"));
+
+        let pipeline = gfx
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Presenter::pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                primitive: Default::default(),
+                depth_stencil: None,
+                multisample: Default::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: "fs_main",
+                    targets: &[gfx.render_format.into()],
+                }),
+                multiview: None,
+            });
+
+        Self {
+            gfx: gfx.clone(),
+            bind_group_layout,
+            pipeline,
+        }
+    }
+
+    /// Draws `source` over `frame_view`, stretching it if they aren't the
+    /// same size.
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &RenderTarget,
+        frame_view: &wgpu::TextureView,
+    ) {
+        let bind_group = self
+            .gfx
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Presenter::bind_group"),
+                layout: &self.bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&source.view),
+                }],
+            });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Presenter::render_pass"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: frame_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, sync::Arc};
+
+    use super::*;
+    use crate::GraphicsContextInner;
+
+    const MAGENTA: [u8; 4] = [255, 0, 255, 255];
+
+    fn clear(gfx: &GraphicsContext, target: &RenderTarget) {
+        let mut encoder = gfx.device.create_command_encoder(&Default::default());
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: &target.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: 1.0,
+                        g: 0.0,
+                        b: 1.0,
+                        a: 0.5,
+                    }),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        gfx.queue.submit([encoder.finish()]);
+    }
+
+    #[test]
+    fn rows_are_padded_to_the_copy_alignment() {
+        assert_eq!(padded_bytes_per_row(1), 256);
+        assert_eq!(padded_bytes_per_row(64), 256);
+        assert_eq!(padded_bytes_per_row(65), 512);
+    }
+
+    #[test]
+    fn padding_is_stripped_from_read_rows() {
+        // Two rows of three pixels, each padded with junk to 256 bytes.
+        let mut data = Vec::new();
+        for row in 0..2u8 {
+            for x in 0..3u8 {
+                data.extend([row, x, 7, 0]);
+            }
+            data.resize(256 * (row as usize + 1), 0xcd);
+        }
+        assert_eq!(
+            strip_row_padding(&data, 3),
+            [
+                [0, 0, 7, 255],
+                [0, 1, 7, 255],
+                [0, 2, 7, 255],
+                [1, 0, 7, 255],
+                [1, 1, 7, 255],
+                [1, 2, 7, 255],
+            ]
+        );
+    }
+
+    #[test]
+    #[ignore = "needs a GPU adapter"]
+    fn renders_offscreen_and_saves_frames() {
+        let gfx = Arc::new(block_on(GraphicsContextInner::offscreen(40, 30)).unwrap());
+        assert_eq!((gfx.size().width, gfx.size().height), (40, 30));
+        assert!(gfx.window.is_none() && gfx.surface.is_none());
+
+        let target = RenderTarget::new(&gfx, 40, 30);
+        clear(&gfx, &target);
+        let image = target.read(&gfx).unwrap();
+        assert_eq!((image.width, image.height), (40, 30));
+        assert!(image.pixels.iter().all(|&pixel| pixel == MAGENTA));
+
+        // Presenting stretches the frame over the whole destination.
+        let stretched = RenderTarget::new(&gfx, 57, 11);
+        let mut encoder = gfx.device.create_command_encoder(&Default::default());
+        Presenter::new(&gfx).draw(&mut encoder, &target, &stretched.view);
+        gfx.queue.submit([encoder.finish()]);
+        let stretched = stretched.read(&gfx).unwrap();
+        assert!(stretched.pixels.iter().all(|&pixel| pixel == MAGENTA));
+
+        let path = std::env::temp_dir().join(format!("frame-{}.png", std::process::id()));
+        image.save_png(&path).unwrap();
+        let saved = Image::load_png(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(saved, Some(image));
+    }
+}
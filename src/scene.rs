@@ -1,33 +1,56 @@
 use std::{f32::consts as f32, f64::consts as f64, ops::Range, time::Instant};
 
+use anyhow::Context;
 use bytemuck::{Pod, Zeroable};
 use glam::{Mat4, Quat, Vec3};
 use once_cell::sync::Lazy;
 use wgpu::{include_wgsl, util::DeviceExt};
 
 use crate::{
+    atmosphere::AtmosphereRenderer,
     geometry::{Geodesic, Square, Triangle},
+    import,
+    lighting::Lighting,
+    material::Materials,
     model::{self, Model},
     orbit::{Orbit2D, Orbit3D, State3D},
+    starfield::Starfield,
+    terrain::Terrain,
     time::{SimDuration, SimInstant},
     viewport::Viewport,
+    world::World,
     GraphicsContext,
 };
 
+/// Number of instances used by the demo objects.
+const DEMO_INSTANCES: usize = 3;
+/// Instance of the craft, which comes after the demo objects.
+const CRAFT_INSTANCE: usize = DEMO_INSTANCES;
+
+/// Mesh of the craft, drawn instead of terrain for its body.
+const CRAFT_MODEL: &str = include_str!("craft.obj");
+
+/// Albedo of the root body, which is drawn as an emissive star.
+const STAR_COLOR: Vec3 = glam::const_vec3!([1.0, 0.9, 0.6]);
+
 #[derive(Default, Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
 pub struct Instance {
     pub model: [[f32; 4]; 4],
     pub albedo: [f32; 3],
+    /// How much the instance glows with its own albedo, from 0 for lit
+    /// surfaces to 1 for stars.
+    pub emission: f32,
 }
 
-static INSTANCE_ATTRIBUTES: Lazy<[wgpu::VertexAttribute; 5]> = Lazy::new(|| {
+static INSTANCE_ATTRIBUTES: Lazy<[wgpu::VertexAttribute; 6]> = Lazy::new(|| {
     wgpu::vertex_attr_array![
-        2 => Float32x4,
-        3 => Float32x4,
         4 => Float32x4,
         5 => Float32x4,
-        6 => Float32x3,
+        6 => Float32x4,
+        7 => Float32x4,
+        8 => Float32x3,
+        9 => Float32,
     ]
 });
 
@@ -36,7 +59,15 @@ pub struct Scene {
     triangle: Triangle,
     geodesic: Geodesic,
     square: Square,
+    craft: Model,
+    /// Whether the world has a craft to draw.
+    show_craft: bool,
     pipeline: wgpu::RenderPipeline,
+    lighting: Lighting,
+    materials: Materials,
+    starfield: Starfield,
+    terrain: Terrain,
+    atmosphere: AtmosphereRenderer,
     instance_buffer: wgpu::Buffer,
     pub instances: Vec<Instance>,
     animation_start: Instant,
@@ -48,7 +79,9 @@ impl Scene {
     pub fn new(gfx: &GraphicsContext, viewport: &Viewport) -> Self {
         let triangle = Triangle::new(gfx);
         let square = Square::new(gfx);
-        let geodesic = Geodesic::with_slerp(gfx, 4);
+        let geodesic = Geodesic::new(gfx, 4);
+        let craft_mesh = import::parse_obj(CRAFT_MODEL).expect("invalid craft model");
+        let craft = Model::from_mesh(gfx, Some("Scene::craft"), &craft_mesh);
         let orbit = Orbit3D::new(
             Orbit2D::new(
                 0.5,
@@ -61,7 +94,12 @@ impl Scene {
             0.0,
         );
 
-        let instances = vec![Default::default(); 3];
+        let lighting = Lighting::new(gfx);
+        let materials = Materials::new(gfx);
+        let starfield = Starfield::new(gfx, viewport);
+        let terrain = Terrain::new(gfx);
+        let atmosphere = AtmosphereRenderer::new(gfx, viewport);
+        let instances = vec![Default::default(); DEMO_INSTANCES + 1];
 
         let instance_buffer = gfx
             .device
@@ -75,7 +113,11 @@ impl Scene {
             .device
             .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Scene::pipeline_layout"),
-                bind_group_layouts: &[viewport.bind_group_layout()],
+                bind_group_layouts: &[
+                    viewport.bind_group_layout(),
+                    lighting.bind_group_layout(),
+                    materials.bind_group_layout(),
+                ],
                 push_constant_ranges: &[],
             });
 
@@ -136,7 +178,14 @@ impl Scene {
             triangle,
             square,
             geodesic,
+            craft,
+            show_craft: false,
             pipeline,
+            lighting,
+            materials,
+            starfield,
+            terrain,
+            atmosphere,
             instance_buffer,
             instances,
             animation_start: Instant::now(),
@@ -145,7 +194,26 @@ impl Scene {
         }
     }
 
-    pub fn update(&mut self, viewport: &Viewport) {
+    /// Loads the texture maps of every body that has them.
+    pub fn load_textures(&mut self, world: &World) -> anyhow::Result<()> {
+        for tag in &world.body_tags {
+            let body = world.body(tag);
+            let maps = match body.textures() {
+                Some(maps) => maps,
+                None => continue,
+            };
+            let material = self
+                .materials
+                .load(maps)
+                .with_context(|| format!("failed to load the textures of {}", body.name()))?;
+            if let Some(material) = material {
+                self.terrain.set_material(*tag, material);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn update(&mut self, viewport: &Viewport, world: &World) {
         let t = self.animation_start.elapsed();
 
         // Square
@@ -177,6 +245,44 @@ impl Scene {
         )
         .to_cols_array_2d();
         self.instances[2].albedo = Vec3::new(0.9, 0.1, 0.2).into();
+
+        // The craft, colored by its mesh
+        let craft_body = world.craft().map(|craft| craft.body);
+        self.show_craft = craft_body.is_some();
+        if let Some(tag) = craft_body {
+            self.instances[CRAFT_INSTANCE] = Instance {
+                model: world.body(&tag).model_matrix().to_cols_array_2d(),
+                albedo: Vec3::ONE.into(),
+                emission: 0.0,
+            };
+        }
+
+        // World bodies, lit by the root body
+        let bodies: Vec<_> = world
+            .body_tags
+            .iter()
+            .filter(|&&tag| Some(tag) != craft_body)
+            .map(|tag| {
+                // Bodies with terrain get their colors from it.
+                let (albedo, emission) = if world.root() == Some(tag) {
+                    (STAR_COLOR, 1.0)
+                } else if world.body(tag).surface().is_some() {
+                    (Vec3::ONE, 0.0)
+                } else {
+                    (Vec3::new(0.3, 0.6, 0.9), 0.0)
+                };
+                let instance = Instance {
+                    model: world.body(tag).model_matrix().to_cols_array_2d(),
+                    albedo: albedo.into(),
+                    emission,
+                };
+                (*tag, instance)
+            })
+            .collect();
+        self.terrain.update(world, viewport.camera_pos(), &bodies);
+        self.lighting.update(world, viewport.camera_pos());
+        self.starfield.update();
+        self.atmosphere.update(world, viewport, &self.lighting);
     }
 
     pub fn draw(
@@ -192,6 +298,8 @@ impl Scene {
             bytemuck::cast_slice(&self.instances),
         );
 
+        self.starfield.draw(encoder, frame_view, viewport);
+
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
@@ -199,7 +307,7 @@ impl Scene {
                     view: frame_view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        load: wgpu::LoadOp::Load,
                         store: true,
                     },
                 }],
@@ -215,22 +323,33 @@ impl Scene {
 
             render_pass.set_pipeline(&self.pipeline);
             render_pass.set_bind_group(0, viewport.bind_group(), &[]);
+            render_pass.set_bind_group(1, self.lighting.bind_group(), &[]);
+            render_pass.set_bind_group(2, self.materials.default().bind_group(), &[]);
             render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
             render_pass.draw_model(&self.square.model, 0..1);
             render_pass.draw_model(&self.geodesic.model, 1..2);
             render_pass.draw_model(&self.triangle.model, 2..3);
+            if self.show_craft {
+                let craft = CRAFT_INSTANCE as u32;
+                render_pass.draw_model(&self.craft, craft..craft + 1);
+            }
+            self.terrain
+                .draw(&mut render_pass, self.materials.default());
         }
+
+        self.atmosphere
+            .draw(encoder, frame_view, depth_view, viewport);
     }
 }
 
-trait RenderPassExt<'a> {
+pub(crate) trait RenderPassExt<'a> {
     fn draw_model(&mut self, model: &'a Model, instances: Range<u32>);
 }
 
 impl<'a> RenderPassExt<'a> for wgpu::RenderPass<'a> {
     fn draw_model(&mut self, model: &'a Model, instances: Range<u32>) {
         self.set_vertex_buffer(0, model.vertex_buffer.slice(..));
-        self.set_index_buffer(model.index_buffer.slice(..), model::INDEX_FORMAT);
+        self.set_index_buffer(model.index_buffer.slice(..), model.index_format);
         self.draw_indexed(model.index_range.clone(), 0, instances)
     }
 }
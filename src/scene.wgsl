@@ -1,14 +1,17 @@
 struct VertexInput {
     [[location(0)]] position: vec3<f32>;
     [[location(1)]] normal: vec3<f32>;
+    [[location(2)]] color: vec3<f32>;
+    [[location(3)]] uv: vec2<f32>;
 };
 
 struct InstanceInput {
-    [[location(2)]] model_0: vec4<f32>;
-    [[location(3)]] model_1: vec4<f32>;
-    [[location(4)]] model_2: vec4<f32>;
-    [[location(5)]] model_3: vec4<f32>;
-    [[location(6)]] albedo: vec3<f32>;
+    [[location(4)]] model_0: vec4<f32>;
+    [[location(5)]] model_1: vec4<f32>;
+    [[location(6)]] model_2: vec4<f32>;
+    [[location(7)]] model_3: vec4<f32>;
+    [[location(8)]] albedo: vec3<f32>;
+    [[location(9)]] emission: f32;
 };
 
 struct VertexOutput {
@@ -16,6 +19,8 @@ struct VertexOutput {
     [[location(0)]] position: vec3<f32>;
     [[location(1)]] normal: vec3<f32>;
     [[location(2)]] albedo: vec3<f32>;
+    [[location(3)]] emission: f32;
+    [[location(4)]] uv: vec2<f32>;
 };
 
 struct Viewport {
@@ -25,9 +30,31 @@ struct Viewport {
     up_yfov: vec4<f32>;
 };
 
+struct Light {
+    position_radius: vec4<f32>;
+    color_ambient: vec4<f32>;
+    occluder_count: u32;
+    // Center and radius of each body casting shadows.
+    occluders: array<vec4<f32>, 8>;
+};
+
 [[group(0), binding(0)]]
 var<uniform> viewport: Viewport;
 
+[[group(1), binding(0)]]
+var<uniform> light: Light;
+
+[[group(2), binding(0)]]
+var albedo_map: texture_2d<f32>;
+[[group(2), binding(1)]]
+var normal_map: texture_2d<f32>;
+[[group(2), binding(2)]]
+var material_sampler: sampler;
+
+let SPECULAR: f32 = 0.25;
+let SHININESS: f32 = 32.0;
+let PI: f32 = 3.14159265;
+
 [[stage(vertex)]]
 fn vs_main(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
     let model = mat4x4<f32>(
@@ -42,19 +69,109 @@ fn vs_main(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
     out.position = position.xyz;
     out.normal = normalize((model * vec4<f32>(vertex.normal, 0.0)).xyz);
     out.clip_position = viewport.view_proj * position;
-    out.albedo = instance.albedo;
+    out.albedo = instance.albedo * vertex.color;
+    out.emission = instance.emission;
+    out.uv = vertex.uv;
 
     return out;
 }
 
+// Bends the normal by a tangent-space normal map sample. The tangent frame
+// comes from how the position and texture coordinates change across the
+// screen, so that meshes don't need tangents. Without texture coordinates
+// there is no frame, and the normal is kept.
+fn perturb_normal(
+    normal: vec3<f32>,
+    sample: vec3<f32>,
+    dp_dx: vec3<f32>,
+    dp_dy: vec3<f32>,
+    duv_dx: vec2<f32>,
+    duv_dy: vec2<f32>,
+) -> vec3<f32> {
+    let dp_dy_perp = cross(dp_dy, normal);
+    let dp_dx_perp = cross(normal, dp_dx);
+    let tangent = dp_dy_perp * duv_dx.x + dp_dx_perp * duv_dy.x;
+    let bitangent = dp_dy_perp * duv_dx.y + dp_dx_perp * duv_dy.y;
+    let length_squared = max(dot(tangent, tangent), dot(bitangent, bitangent));
+    if (length_squared <= 0.0) {
+        return normal;
+    }
+    // Framebuffer y goes down the screen, which flips the frame, and normal
+    // maps point green up the image, against the texture coordinates.
+    let scale = inverseSqrt(length_squared);
+    let frame = mat3x3<f32>(-tangent * scale, bitangent * scale, normal);
+    return normalize(frame * (sample * 2.0 - 1.0));
+}
+
+// Angle from the center of a sphere's disc to its edge, seen from `distance`
+// away, or a right angle from inside it.
+fn angular_radius(radius: f32, distance: f32) -> f32 {
+    return asin(min(radius / distance, 1.0));
+}
+
+// Fraction of a disc of angular radius `light` covered by one of radius
+// `occluder` with their centers `separation` apart, as in `eclipse.rs`.
+fn covered_fraction(light: f32, occluder: f32, separation: f32) -> f32 {
+    if (light <= 0.0 || separation >= light + occluder) {
+        return 0.0;
+    }
+    if (separation <= abs(light - occluder)) {
+        let ratio = min(occluder / light, 1.0);
+        return ratio * ratio;
+    }
+    let a = light;
+    let b = occluder;
+    let c = separation;
+    let light_angle = acos(clamp((c * c + a * a - b * b) / (2.0 * c * a), -1.0, 1.0));
+    let occluder_angle = acos(clamp((c * c + b * b - a * a) / (2.0 * c * b), -1.0, 1.0));
+    let kite = sqrt(max((-c + a + b) * (c + a - b) * (c - a + b) * (c + a + b), 0.0));
+    let overlap = a * a * light_angle + b * b * occluder_angle - 0.5 * kite;
+    return clamp(overlap / (PI * a * a), 0.0, 1.0);
+}
+
+// Fraction of the light's disc that no body covers, from 0 in the umbra to 1
+// in full light.
+fn illumination(point: vec3<f32>) -> f32 {
+    let to_light = light.position_radius.xyz - point;
+    let light_radius = angular_radius(light.position_radius.w, length(to_light));
+    var visible = 1.0;
+    for (var i = 0u; i < light.occluder_count; i = i + 1u) {
+        let occluder = light.occluders[i];
+        let to_occluder = occluder.xyz - point;
+        let occluder_radius = angular_radius(occluder.w, length(to_occluder));
+        // More precise than the arccosine for the small angles involved.
+        let separation = atan2(length(cross(to_light, to_occluder)), dot(to_light, to_occluder));
+        visible = visible * (1.0 - covered_fraction(light_radius, occluder_radius, separation));
+    }
+    return visible;
+}
+
+// Blinn-Phong shading from a spherical light, dimmed where other bodies
+// eclipse it, with the interpolated vertex normals for smooth surfaces, bent
+// by the normal map.
 [[stage(fragment)]]
 fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
-    let dx = dpdx(in.position);
-    // dy in window coordinates, positive dpdy means down
-    let dy = -dpdy(in.position);
-    let normal = normalize(cross(dx, dy));
-
-    let ray = normalize(viewport.eye - in.position);
-    let coef = dot(ray, normal);
-    return vec4<f32>(in.albedo * coef, 1.0);
-}
\ No newline at end of file
+    let albedo = in.albedo * textureSample(albedo_map, material_sampler, in.uv).rgb;
+    let normal = perturb_normal(
+        normalize(in.normal),
+        textureSample(normal_map, material_sampler, in.uv).rgb,
+        dpdx(in.position),
+        dpdy(in.position),
+        dpdx(in.uv),
+        dpdy(in.uv),
+    );
+    let to_light = normalize(light.position_radius.xyz - in.position);
+    let to_eye = normalize(viewport.eye - in.position);
+
+    let shadow = illumination(in.position);
+    let diffuse = shadow * max(dot(normal, to_light), 0.0);
+    var specular = 0.0;
+    if (diffuse > 0.0) {
+        let halfway = normalize(to_light + to_eye);
+        specular = shadow * SPECULAR * pow(max(dot(normal, halfway), 0.0), SHININESS);
+    }
+
+    let lit = albedo * (light.color_ambient.w + diffuse * light.color_ambient.rgb)
+        + specular * light.color_ambient.rgb;
+    return vec4<f32>(mix(lit, albedo, in.emission), 1.0);
+}
@@ -0,0 +1,411 @@
+//! Background stars from a bundled catalog of the brightest stars.
+//!
+//! Stars are at infinity, so they only turn with the camera and never move
+//! with it. The world frame is taken to be the equatorial frame of the
+//! catalog: +Z points to the north celestial pole and +X to the March
+//! equinox.
+
+use std::f64::consts::TAU;
+
+use anyhow::{anyhow, bail, Context};
+use bytemuck::{Pod, Zeroable};
+use glam::{DVec3, Vec3};
+use once_cell::sync::Lazy;
+use wgpu::{include_wgsl, util::DeviceExt};
+
+use crate::{viewport::Viewport, GraphicsContext};
+
+/// Catalog of stars, with one star per line:
+///
+/// ```text
+/// # right ascension (hours), declination (degrees), magnitude, B-V, name
+///  6.7525  -16.716 -1.46  0.00 Sirius
+/// ```
+const CATALOG: &str = include_str!("bright_stars.txt");
+
+/// Magnitude of stars drawn at full brightness. Brighter stars are drawn
+/// larger instead.
+const FULL_MAGNITUDE: f64 = 1.0;
+/// Least brightness of a star, so that the faintest ones stay visible.
+const MIN_BRIGHTNESS: f64 = 0.2;
+/// Radius of the sprite of a magnitude 0 star (pixels).
+const RADIUS: f64 = 1.5;
+const MAX_RADIUS: f64 = 4.0;
+
+/// A star in the catalog.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Star {
+    /// Unit vector towards the star in the world frame.
+    pub direction: DVec3,
+    /// Apparent visual magnitude, which is lower for brighter stars.
+    pub magnitude: f64,
+    /// B-V color index, which is higher for redder stars.
+    pub color_index: f64,
+}
+
+/// Parses a catalog with a star per line, ignoring anything after `#`.
+pub fn parse_catalog(text: &str) -> anyhow::Result<Vec<Star>> {
+    let mut stars = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        if line.trim().is_empty() {
+            continue;
+        }
+        let parse_line = || -> anyhow::Result<Star> {
+            let mut words = line.split_whitespace();
+            let mut number = |name| -> anyhow::Result<f64> {
+                let word = words.next().ok_or_else(|| anyhow!("missing {}", name))?;
+                word.parse()
+                    .with_context(|| format!("invalid {} `{}`", name, word))
+            };
+            let right_ascension = number("right ascension")?;
+            let declination = number("declination")?;
+            if !(0.0..24.0).contains(&right_ascension) {
+                bail!("right ascension {} is out of range", right_ascension);
+            }
+            if !(-90.0..=90.0).contains(&declination) {
+                bail!("declination {} is out of range", declination);
+            }
+            Ok(Star {
+                direction: equatorial_direction(
+                    right_ascension / 24.0 * TAU,
+                    declination.to_radians(),
+                ),
+                magnitude: number("magnitude")?,
+                color_index: number("color index")?,
+            })
+        };
+        stars.push(parse_line().with_context(|| format!("line {}", index + 1))?);
+    }
+    Ok(stars)
+}
+
+/// Unit vector towards a right ascension and declination (radians) in the
+/// world frame.
+pub fn equatorial_direction(right_ascension: f64, declination: f64) -> DVec3 {
+    let (sin_ra, cos_ra) = right_ascension.sin_cos();
+    let (sin_dec, cos_dec) = declination.sin_cos();
+    DVec3::new(cos_dec * cos_ra, cos_dec * sin_ra, sin_dec)
+}
+
+/// Effective temperature of a star from its B-V color index (K), after
+/// Ballesteros (2012).
+pub fn color_temperature(color_index: f64) -> f64 {
+    4600.0 * (1.0 / (0.92 * color_index + 1.7) + 1.0 / (0.92 * color_index + 0.62))
+}
+
+/// Color of a black body at a temperature (K), with the brightest channel
+/// at 1. Fitted to blackbody colors between 1000 K and 40000 K.
+pub fn blackbody_color(temperature: f64) -> Vec3 {
+    let t = temperature / 100.0;
+    let red = if t <= 66.0 {
+        255.0
+    } else {
+        329.698727446 * (t - 60.0).powf(-0.1332047592)
+    };
+    let green = if t <= 66.0 {
+        99.4708025861 * t.ln() - 161.1195681661
+    } else {
+        288.1221695283 * (t - 60.0).powf(-0.0755148492)
+    };
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.5177312231 * (t - 10.0).ln() - 305.0447927307
+    };
+    (DVec3::new(red, green, blue).clamp(DVec3::ZERO, DVec3::splat(255.0)) / 255.0).as_vec3()
+}
+
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct Instance {
+    direction: [f32; 3],
+    /// Radius of the sprite (pixels).
+    radius: f32,
+    color: [f32; 3],
+}
+
+static INSTANCE_ATTRIBUTES: Lazy<[wgpu::VertexAttribute; 3]> = Lazy::new(|| {
+    wgpu::vertex_attr_array![
+        0 => Float32x3,
+        1 => Float32,
+        2 => Float32x3,
+    ]
+});
+
+impl Instance {
+    fn new(star: &Star) -> Self {
+        let relative = 10f64.powf(-0.4 * (star.magnitude - FULL_MAGNITUDE));
+        let brightness = relative.clamp(MIN_BRIGHTNESS, 1.0);
+        // Brightness grows with the area of the sprite.
+        let radius = (RADIUS * 10f64.powf(-0.2 * star.magnitude)).clamp(1.0, MAX_RADIUS);
+        let color = blackbody_color(color_temperature(star.color_index)) * brightness as f32;
+        Self {
+            direction: star.direction.as_vec3().into(),
+            radius: radius as f32,
+            color: color.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct Uniforms {
+    /// Size of a pixel in normalized device coordinates.
+    pixel_size: [f32; 2],
+    _padding: [u8; 8],
+}
+
+pub struct Starfield {
+    gfx: GraphicsContext,
+    bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+    instance_buffer: wgpu::Buffer,
+    star_count: u32,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl Starfield {
+    pub fn new(gfx: &GraphicsContext, viewport: &Viewport) -> Self {
+        let stars = parse_catalog(CATALOG).expect("invalid star catalog");
+        let instances: Vec<_> = stars.iter().map(Instance::new).collect();
+        let instance_buffer = gfx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Starfield::instance_buffer"),
+                contents: bytemuck::cast_slice(&instances),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+
+        let uniform_buffer = gfx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Starfield::uniform_buffer"),
+                contents: bytemuck::bytes_of(&Uniforms::zeroed()),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+
+        let bind_group_layout =
+            gfx.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Starfield::bind_group_layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    }],
+                });
+
+        let bind_group = gfx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Starfield::bind_group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let pipeline_layout = gfx
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Starfield::pipeline_layout"),
+                bind_group_layouts: &[viewport.bind_group_layout(), &bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let shader_module = gfx
+            .device
+            .create_shader_module(&include_wgsl!("starfield.wgsl"));
+
+        let pipeline = gfx
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Starfield::pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: "vs_main",
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<Instance>() as _,
+                        step_mode: wgpu::VertexStepMode::Instance,
+                        attributes: &*INSTANCE_ATTRIBUTES,
+                    }],
+                },
+                primitive: Default::default(),
+                depth_stencil: None,
+                multisample: Default::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: "fs_main",
+                    targets: &[wgpu::ColorTargetState {
+                        format: gfx.render_format,
+                        // Overlapping stars add up.
+                        blend: Some(wgpu::BlendState {
+                            color: wgpu::BlendComponent {
+                                src_factor: wgpu::BlendFactor::One,
+                                dst_factor: wgpu::BlendFactor::One,
+                                operation: wgpu::BlendOperation::Add,
+                            },
+                            alpha: wgpu::BlendComponent::REPLACE,
+                        }),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }],
+                }),
+                multiview: None,
+            });
+
+        Self {
+            gfx: gfx.clone(),
+            bind_group,
+            uniform_buffer,
+            instance_buffer,
+            star_count: instances.len() as u32,
+            pipeline,
+        }
+    }
+
+    pub fn update(&self) {
+        let size = self.gfx.size();
+        let uniforms = Uniforms {
+            pixel_size: [
+                2.0 / size.width.max(1) as f32,
+                2.0 / size.height.max(1) as f32,
+            ],
+            _padding: Default::default(),
+        };
+        self.gfx
+            .queue
+            .write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+    }
+
+    /// Clears `frame_view` to black and draws the stars on it, before
+    /// anything else is drawn.
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        frame_view: &wgpu::TextureView,
+        viewport: &Viewport,
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Starfield::render_pass"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: frame_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, viewport.bind_group(), &[]);
+        render_pass.set_bind_group(1, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.star_count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn error(text: &str) -> String {
+        format!("{:#}", parse_catalog(text).unwrap_err())
+    }
+
+    #[test]
+    fn parses_catalogs() {
+        let stars = parse_catalog(
+            "# right ascension, declination, magnitude, B-V, name\n\
+             \n\
+             \x20 6.0  0.0 -1.46  0.00 Sirius # not where it really is\n\
+             12.0 90.0  2.5  1.2\n",
+        )
+        .unwrap();
+        assert_eq!(stars.len(), 2);
+        assert!(stars[0].direction.distance(DVec3::Y) < 1e-12);
+        assert_eq!(stars[0].magnitude, -1.46);
+        assert_eq!(stars[0].color_index, 0.0);
+        assert!(stars[1].direction.distance(DVec3::Z) < 1e-12);
+        assert_eq!(stars[1].magnitude, 2.5);
+        assert_eq!(stars[1].color_index, 1.2);
+    }
+
+    #[test]
+    fn the_bundled_catalog_parses() {
+        let stars = parse_catalog(CATALOG).unwrap();
+        assert!(stars.len() > 100);
+        for star in &stars {
+            assert!((star.direction.length() - 1.0).abs() < 1e-12);
+            assert!((-2.0..7.0).contains(&star.magnitude));
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        assert_eq!(error("1 2 3 4\n5 6\n"), "line 2: missing magnitude");
+        assert_eq!(error("\n1 2 3"), "line 2: missing color index");
+        assert_eq!(
+            error("1 north 3 4"),
+            "line 1: invalid declination `north`: invalid float literal"
+        );
+        assert_eq!(
+            error("24 0 1 0"),
+            "line 1: right ascension 24 is out of range"
+        );
+        assert_eq!(
+            error("-1 0 1 0"),
+            "line 1: right ascension -1 is out of range"
+        );
+        assert_eq!(
+            error("0 90.5 1 0"),
+            "line 1: declination 90.5 is out of range"
+        );
+    }
+
+    #[test]
+    fn equatorial_directions() {
+        let cases = [
+            (0.0, 0.0, DVec3::X),
+            (FRAC_PI_2, 0.0, DVec3::Y),
+            (TAU / 2.0, 0.0, -DVec3::X),
+            (1.0, FRAC_PI_2, DVec3::Z),
+            (2.0, -FRAC_PI_2, -DVec3::Z),
+        ];
+        for (right_ascension, declination, expected) in cases {
+            let direction = equatorial_direction(right_ascension, declination);
+            assert!(direction.distance(expected) < 1e-12, "{}", direction);
+        }
+        for i in 0..20 {
+            let direction = equatorial_direction(i as f64 * 0.7, i as f64 * 0.3 - 3.0);
+            assert!((direction.length() - 1.0).abs() < 1e-12);
+        }
+        // Declination is the angle from the equator.
+        let direction = equatorial_direction(0.3, 0.5);
+        assert!((direction.z.asin() - 0.5).abs() < 1e-12);
+        assert!((direction.y.atan2(direction.x) - 0.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn star_colors() {
+        // The Sun has a B-V of about 0.65.
+        assert!((color_temperature(0.65) - 5800.0).abs() < 200.0);
+        assert_eq!(blackbody_color(6600.0), Vec3::ONE);
+        let red = blackbody_color(3000.0);
+        let blue = blackbody_color(20000.0);
+        assert_eq!(red.x, 1.0);
+        assert!(red.z < red.y && red.y < 1.0);
+        assert_eq!(blue.z, 1.0);
+        assert!(blue.x < blue.y && blue.y < 1.0);
+    }
+}
@@ -0,0 +1,65 @@
+struct Viewport {
+    view_proj: mat4x4<f32>;
+    eye: vec3<f32>;
+    forward_xfov: vec4<f32>;
+    up_yfov: vec4<f32>;
+};
+
+struct Uniforms {
+    pixel_size: vec2<f32>;
+};
+
+struct InstanceInput {
+    [[location(0)]] direction: vec3<f32>;
+    [[location(1)]] radius: f32;
+    [[location(2)]] color: vec3<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    // Position in the sprite, from -1 to 1 across it.
+    [[location(0)]] offset: vec2<f32>;
+    [[location(1)]] color: vec3<f32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> viewport: Viewport;
+
+[[group(1), binding(0)]]
+var<uniform> uniforms: Uniforms;
+
+// A square sprite facing the camera, as two triangles.
+[[stage(vertex)]]
+fn vs_main(
+    [[builtin(vertex_index)]] index: u32,
+    star: InstanceInput,
+) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+    let corner = corners[index];
+
+    // A direction has no position, so the star stays at infinity wherever
+    // the camera is.
+    var clip = viewport.view_proj * vec4<f32>(star.direction, 0.0);
+    let offset = corner * star.radius * uniforms.pixel_size;
+    clip = vec4<f32>(clip.xy + offset * clip.w, 0.0, clip.w);
+
+    var out: VertexOutput;
+    out.clip_position = clip;
+    out.offset = corner;
+    out.color = star.color;
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let distance_squared = dot(in.offset, in.offset);
+    let falloff = exp(-4.0 * distance_squared) * step(distance_squared, 1.0);
+    return vec4<f32>(in.color * falloff, 1.0);
+}
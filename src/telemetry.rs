@@ -0,0 +1,120 @@
+//! Text readouts of the simulation state.
+
+use std::fmt::Write;
+
+use valet::Tag;
+
+use crate::{
+    craft::SasMode,
+    hud::orbits::{self, Marker},
+    time::SimInstant,
+    world::{Body, World},
+};
+
+/// Formats a length with an SI prefix, e.g. `147.10 Gm`.
+pub fn format_distance(meters: f64) -> String {
+    const PREFIXES: [&str; 5] = ["", "k", "M", "G", "T"];
+    let mut value = meters;
+    let mut prefix = 0;
+    while value.abs() >= 1000.0 && prefix < PREFIXES.len() - 1 {
+        value /= 1000.0;
+        prefix += 1;
+    }
+    format!("{:.2} {}m", value, PREFIXES[prefix])
+}
+
+/// Formats a speed with an SI prefix, e.g. `29.78 km/s`.
+pub fn format_speed(meters_per_second: f64) -> String {
+    format!("{}/s", format_distance(meters_per_second))
+}
+
+/// Formats a time since the epoch as days, hours, minutes and seconds, e.g.
+/// `T+ 12d 03:04:05`.
+pub fn format_time(seconds: f64) -> String {
+    let sign = if seconds < 0.0 { '-' } else { '+' };
+    let total = seconds.abs() as u64;
+    format!(
+        "T{} {}d {:02}:{:02}:{:02}",
+        sign,
+        total / 86400,
+        total / 3600 % 24,
+        total / 60 % 60,
+        total % 60
+    )
+}
+
+/// The telemetry panel: simulation time and warp, and the state and orbit
+/// of the focused body relative to its parent.
+pub fn panel(world: &World, focus: Option<&Tag<Body>>) -> String {
+    let mut text = String::new();
+    let time = (world.time() - SimInstant::epoch()).as_secs_f64();
+    writeln!(text, "{}", format_time(time)).unwrap();
+    writeln!(text, "Warp {}x", world.warp()).unwrap();
+
+    let focus = match focus {
+        Some(focus) => focus,
+        None => return text,
+    };
+    let body = world.body(focus);
+    let (parent, orbit) = match (body.parent(), body.orbit()) {
+        (Some(parent), Some(orbit)) => (world.body(parent), orbit),
+        _ => {
+            writeln!(text, "\n{}", body.name()).unwrap();
+            return text;
+        }
+    };
+
+    let state = world.relative_state(focus);
+    let shape = orbit.shape();
+    let altitude = |radius: f64| format_distance(radius - parent.radius());
+
+    writeln!(text, "\n{} orbiting {}", body.name(), parent.name()).unwrap();
+    writeln!(text, "Vel  {}", format_speed(state.velocity.length())).unwrap();
+    writeln!(text, "Alt  {}", altitude(state.position.length())).unwrap();
+    match orbit.v_inf() {
+        Some(v_inf) => writeln!(text, "Vinf {}", format_speed(v_inf)).unwrap(),
+        None => writeln!(text, "Ap   {}", altitude(shape.ra())).unwrap(),
+    }
+    writeln!(text, "Pe   {}", altitude(shape.rp())).unwrap();
+    writeln!(text, "Inc  {:.2} deg", orbit.inc().to_degrees()).unwrap();
+    writeln!(text, "e    {:.4}", shape.e()).unwrap();
+    if let Some(craft) = world.craft().filter(|craft| craft.body == *focus) {
+        writeln!(text, "SAS  {}", craft.sas.map_or("Off", SasMode::name)).unwrap();
+        writeln!(text, "RCS  {}", if craft.rcs { "On" } else { "Off" }).unwrap();
+        let sunlight = craft.sunlight(world);
+        let eclipse = if craft.in_eclipse(world) {
+            " (eclipse)"
+        } else {
+            ""
+        };
+        writeln!(text, "Sun  {:.0}%{}", sunlight * 100.0, eclipse).unwrap();
+    }
+    text
+}
+
+/// Details of an orbit marker: altitude, time until the body gets there and
+/// speed at that point.
+pub fn marker_tooltip(world: &World, marker: &Marker) -> String {
+    let mut text = String::new();
+    writeln!(text, "{}", marker.kind.name()).unwrap();
+    writeln!(
+        text,
+        "Alt  {}",
+        format_distance(orbits::altitude(world, marker))
+    )
+    .unwrap();
+
+    let shape = match world.body(&marker.body).orbit() {
+        Some(orbit) => orbit.shape(),
+        None => return text,
+    };
+    match shape.next_time_at(marker.angle, world.time()) {
+        Some(time) => {
+            let until = (time - world.time()).as_secs_f64();
+            writeln!(text, "In   {}", format_time(-until)).unwrap();
+        }
+        None => writeln!(text, "In   ---").unwrap(),
+    }
+    writeln!(text, "Vel  {}", format_speed(shape.speed_at(marker.angle))).unwrap();
+    text
+}
@@ -0,0 +1,227 @@
+//! Meshes for terrain chunks, built on the CPU.
+
+use std::f64::consts::{PI, TAU};
+
+use glam::{DVec3, Vec2, Vec3};
+
+use super::{quadtree::ChunkId, surface::Heightmap};
+use crate::model::MeshData;
+
+/// Number of quads along each edge of a chunk.
+pub const CHUNK_RESOLUTION: u32 = 16;
+
+/// How far the skirts hang down below the edges of a chunk, as a fraction of
+/// its size.
+const SKIRT_DEPTH: f64 = 0.05;
+
+/// The mesh of a chunk of a unit sphere, displaced by the heightmap if there
+/// is one, and colored by it too unless `vertex_colors` is false. Positions
+/// are relative to [`ChunkId::center`] so that they stay precise for small
+/// chunks. Texture coordinates are equirectangular, see
+/// [`equirectangular_uv`].
+///
+/// Neighbouring chunks at different levels don't have matching vertices
+/// along their shared edge, so there are skirts around the chunk that hang
+/// down into the sphere and hide the cracks between them.
+pub fn mesh_chunk(chunk: ChunkId, heightmap: Option<&Heightmap>, vertex_colors: bool) -> MeshData {
+    let center = chunk.center();
+    let center_u = equirectangular_uv(center.normalize(), 0.5).x as f64;
+    let row = CHUNK_RESOLUTION + 1;
+    let index = |x: u32, y: u32| y * row + x;
+    // Normals are taken from the heightmap rather than the triangles, so
+    // that they match along the edges of neighbouring chunks.
+    let step = 0.5 * chunk.size() / CHUNK_RESOLUTION as f64;
+
+    let mut mesh = MeshData::default();
+    let add_vertex = |mesh: &mut MeshData, point: DVec3, normal: DVec3, color: Vec3, uv: Vec2| {
+        mesh.positions.push((point - center).as_vec3());
+        mesh.normals.push(normal.as_vec3());
+        mesh.colors.push(color);
+        mesh.uvs.push(uv);
+    };
+
+    let mut points = Vec::with_capacity((row * row) as usize);
+    for y in 0..row {
+        for x in 0..row {
+            let direction = chunk
+                .point(
+                    x as f64 / CHUNK_RESOLUTION as f64,
+                    y as f64 / CHUNK_RESOLUTION as f64,
+                )
+                .normalize();
+            let (point, normal) = match heightmap {
+                Some(heightmap) => (
+                    heightmap.point(direction),
+                    surface_normal(heightmap, direction, step),
+                ),
+                None => (direction, direction),
+            };
+            let color = match heightmap {
+                Some(heightmap) if vertex_colors => heightmap.color(direction),
+                _ => Vec3::ONE,
+            };
+            let uv = equirectangular_uv(direction, center_u);
+            add_vertex(&mut mesh, point, normal, color, uv);
+            points.push(point);
+        }
+    }
+    for y in 0..CHUNK_RESOLUTION {
+        for x in 0..CHUNK_RESOLUTION {
+            let (p00, p10) = (index(x, y), index(x + 1, y));
+            let (p01, p11) = (index(x, y + 1), index(x + 1, y + 1));
+            mesh.indices.extend([p00, p01, p11, p00, p11, p10]);
+        }
+    }
+
+    // Go around the edge counterclockwise seen from outside, adding a skirt
+    // vertex below each edge vertex and a quad between each pair.
+    let n = CHUNK_RESOLUTION;
+    let border: Vec<u32> = (0..n)
+        .map(|x| index(x, 0))
+        .chain((0..n).map(|y| index(n, y)))
+        .chain((0..n).map(|x| index(n - x, n)))
+        .chain((0..n).map(|y| index(0, n - y)))
+        .collect();
+    let depth = 1.0 - SKIRT_DEPTH * chunk.size();
+    let skirt_start = mesh.positions.len() as u32;
+    for &top in &border {
+        let top = top as usize;
+        let (normal, color, uv) = (
+            mesh.normals[top].as_dvec3(),
+            mesh.colors[top],
+            mesh.uvs[top],
+        );
+        add_vertex(&mut mesh, points[top] * depth, normal, color, uv);
+    }
+    for i in 0..border.len() {
+        let j = (i + 1) % border.len();
+        let (a, b) = (border[i], border[j]);
+        let (a_skirt, b_skirt) = (skirt_start + i as u32, skirt_start + j as u32);
+        mesh.indices.extend([a, b, b_skirt, a, b_skirt, a_skirt]);
+    }
+
+    mesh
+}
+
+/// Equirectangular texture coordinates of a direction in the body frame,
+/// with +X in the middle of the texture and +Z at the top. `u` is moved by
+/// whole turns to within half a turn of `near_u`, so that the triangles of a
+/// chunk that crosses the seam don't stretch back across the whole texture.
+pub fn equirectangular_uv(direction: DVec3, near_u: f64) -> Vec2 {
+    let u = direction.y.atan2(direction.x) / TAU + 0.5;
+    let u = u - (u - near_u).round();
+    let v = direction.z.clamp(-1.0, 1.0).acos() / PI;
+    Vec2::new(u as f32, v as f32)
+}
+
+/// Normal of the displaced surface, from central differences `step` apart
+/// along two tangents.
+fn surface_normal(heightmap: &Heightmap, direction: DVec3, step: f64) -> DVec3 {
+    let helper = if direction.x.abs() < 0.9 {
+        DVec3::X
+    } else {
+        DVec3::Y
+    };
+    let tangent = helper.cross(direction).normalize();
+    let bitangent = direction.cross(tangent);
+    let point = |offset: DVec3| heightmap.point((direction + offset).normalize());
+    let du = point(tangent * step) - point(-tangent * step);
+    let dv = point(bitangent * step) - point(-bitangent * step);
+    du.cross(dv).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::{
+        quadtree::Face,
+        surface::{Biome, Surface},
+    };
+
+    fn chunk() -> ChunkId {
+        ChunkId {
+            face: Face::PosY,
+            level: 2,
+            x: 1,
+            y: 3,
+        }
+    }
+
+    fn heightmap() -> Heightmap {
+        Heightmap::new(Surface {
+            seed: 7,
+            relief: 0.02,
+            frequency: 4.0,
+            ridges: 0.5,
+            craters: 20,
+            max_crater_radius: 0.3,
+            biome: Biome::Terran { sea_level: 0.0 },
+        })
+    }
+
+    #[test]
+    fn meshes_are_deterministic() {
+        assert_eq!(
+            mesh_chunk(chunk(), None, true),
+            mesh_chunk(chunk(), None, true)
+        );
+        // Separately built heightmaps from the same surface give the same
+        // mesh.
+        assert_eq!(
+            mesh_chunk(chunk(), Some(&heightmap()), true),
+            mesh_chunk(chunk(), Some(&heightmap()), true)
+        );
+    }
+
+    #[test]
+    fn meshes_have_a_grid_and_a_skirt() {
+        let n = CHUNK_RESOLUTION as usize;
+        let mesh = mesh_chunk(chunk(), None, false);
+        assert_eq!(mesh.positions.len(), (n + 1) * (n + 1) + 4 * n);
+        assert_eq!(mesh.triangle_count(), 2 * n * n + 2 * 4 * n);
+        assert_eq!(mesh.normals.len(), mesh.positions.len());
+        assert_eq!(mesh.uvs.len(), mesh.positions.len());
+        assert!(mesh.colors.iter().all(|&color| color == Vec3::ONE));
+
+        let center = chunk().center();
+        let grid = (n + 1) * (n + 1);
+        for &position in &mesh.positions[..grid] {
+            let point = position.as_dvec3() + center;
+            assert!((point.length() - 1.0).abs() < 1e-6);
+        }
+        for &position in &mesh.positions[grid..] {
+            let point = position.as_dvec3() + center;
+            assert!(point.length() < 1.0);
+        }
+    }
+
+    #[test]
+    fn heightmaps_displace_and_color_the_grid() {
+        let heightmap = heightmap();
+        let mesh = mesh_chunk(chunk(), Some(&heightmap), true);
+        let center = chunk().center();
+        let corner = chunk().point(0.0, 0.0).normalize();
+        assert!((mesh.positions[0].as_dvec3() + center).distance(heightmap.point(corner)) < 1e-6);
+        assert_eq!(mesh.colors[0], heightmap.color(corner));
+        assert!(mesh
+            .normals
+            .iter()
+            .all(|normal| (normal.length() - 1.0).abs() < 1e-5));
+    }
+
+    #[test]
+    fn equirectangular_uv_wraps_near_the_chunk() {
+        assert!((equirectangular_uv(DVec3::X, 0.5) - Vec2::new(0.5, 0.5)).length() < 1e-6);
+        assert!(equirectangular_uv(DVec3::Z, 0.5).y.abs() < 1e-6);
+        assert!((equirectangular_uv(-DVec3::Z, 0.5).y - 1.0).abs() < 1e-6);
+        // Just either side of the seam at -X.
+        let below = DVec3::new(-1.0, -0.01, 0.0).normalize();
+        let above = DVec3::new(-1.0, 0.01, 0.0).normalize();
+        let (below, above) = (
+            equirectangular_uv(below, 1.0),
+            equirectangular_uv(above, 1.0),
+        );
+        assert!((below.x - above.x).abs() < 0.01);
+        assert!((below.x - 1.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,219 @@
+//! Level-of-detail terrain for the bodies of the world.
+//!
+//! The chunks to draw are chosen on the main thread by
+//! [`quadtree::select_chunks`], and their meshes are built by
+//! [`mesh::mesh_chunk`] on a worker thread, so that moving the camera
+//! doesn't stall rendering. Until every chunk of a body's new selection is
+//! ready, its previous selection keeps being drawn. Bodies with a
+//! [`Surface`](surface::Surface) get procedural terrain from its
+//! [`Heightmap`], and bodies with a [`Material`] are drawn with it.
+
+pub mod mesh;
+pub mod quadtree;
+pub mod surface;
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread,
+};
+
+use glam::{Mat4, Vec3};
+use valet::Tag;
+use wgpu::util::DeviceExt;
+
+use self::{
+    mesh::mesh_chunk,
+    quadtree::{ChunkId, Face},
+    surface::Heightmap,
+};
+use crate::{
+    material::Material,
+    model::{MeshData, Model},
+    scene::{Instance, RenderPassExt},
+    world::{Body, World},
+    GraphicsContext,
+};
+
+/// Number of chunk instances that the instance buffer starts with room for.
+const INITIAL_CAPACITY: usize = 256;
+
+type ChunkKey = (Tag<Body>, ChunkId);
+
+/// A chunk for the worker to mesh, with the body's heightmap and whether
+/// the heightmap colors the vertices.
+type Request = (ChunkKey, Option<Arc<Heightmap>>, bool);
+
+pub struct Terrain {
+    gfx: GraphicsContext,
+    /// Meshes of the chunks that are drawn or about to be.
+    models: HashMap<ChunkKey, Model>,
+    /// Chunks sent to the worker that haven't come back yet.
+    pending: HashSet<ChunkKey>,
+    /// Chunks currently drawn for each body.
+    displayed: HashMap<Tag<Body>, Vec<ChunkId>>,
+    /// Each body's heightmap, built the first time the body is drawn.
+    heightmaps: HashMap<Tag<Body>, Option<Arc<Heightmap>>>,
+    materials: HashMap<Tag<Body>, Material>,
+    requests: Sender<Request>,
+    results: Receiver<(ChunkKey, MeshData)>,
+    /// The chunk drawn with each instance.
+    draws: Vec<ChunkKey>,
+    instances: Vec<Instance>,
+    instance_buffer: wgpu::Buffer,
+    instance_capacity: usize,
+}
+
+impl Terrain {
+    pub fn new(gfx: &GraphicsContext) -> Self {
+        let (requests, worker_requests) = mpsc::channel::<Request>();
+        let (worker_results, results) = mpsc::channel();
+        thread::Builder::new()
+            .name("terrain mesher".to_owned())
+            .spawn(move || {
+                // Stops once the terrain, and with it the sender, is dropped.
+                for (key, heightmap, vertex_colors) in worker_requests {
+                    let mesh = mesh_chunk(key.1, heightmap.as_deref(), vertex_colors);
+                    if worker_results.send((key, mesh)).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("failed to start the terrain mesher");
+
+        Self {
+            gfx: gfx.clone(),
+            models: HashMap::new(),
+            pending: HashSet::new(),
+            displayed: HashMap::new(),
+            heightmaps: HashMap::new(),
+            materials: HashMap::new(),
+            requests,
+            results,
+            draws: Vec::new(),
+            instances: Vec::new(),
+            instance_buffer: Self::create_instance_buffer(gfx, INITIAL_CAPACITY),
+            instance_capacity: INITIAL_CAPACITY,
+        }
+    }
+
+    fn create_instance_buffer(gfx: &GraphicsContext, capacity: usize) -> wgpu::Buffer {
+        gfx.device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Terrain::instance_buffer"),
+                contents: bytemuck::cast_slice(&vec![Instance::default(); capacity]),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            })
+    }
+
+    /// Draws the body with a material instead of the default one. If the
+    /// material has an albedo map, it replaces the colors of the heightmap.
+    /// Chunks that are already meshed keep their colors, so this should be
+    /// set before the body is first drawn.
+    pub fn set_material(&mut self, body: Tag<Body>, material: Material) {
+        self.materials.insert(body, material);
+    }
+
+    /// Chooses the chunks of each body to draw for a camera at `camera`,
+    /// with each body's instance giving its transform and material.
+    pub fn update(&mut self, world: &World, camera: Vec3, bodies: &[(Tag<Body>, Instance)]) {
+        for (key, mesh) in self.results.try_iter() {
+            self.pending.remove(&key);
+            let model = Model::from_mesh(&self.gfx, Some("Terrain::chunk"), &mesh);
+            self.models.insert(key, model);
+        }
+
+        let mut keep = HashSet::new();
+        self.draws.clear();
+        self.instances.clear();
+        for &(tag, instance) in bodies {
+            let body = world.body(&tag);
+            let heightmap = self
+                .heightmaps
+                .entry(tag)
+                .or_insert_with(|| body.surface().cloned().map(Heightmap::new).map(Arc::new))
+                .clone();
+            let vertex_colors = !self
+                .materials
+                .get(&tag)
+                .is_some_and(Material::has_albedo_map);
+
+            // Start out with the root chunks, meshed right away, so that
+            // there is always something to draw.
+            if !self.displayed.contains_key(&tag) {
+                let roots: Vec<_> = Face::ALL.iter().map(|&face| ChunkId::root(face)).collect();
+                for &chunk in &roots {
+                    let gfx = &self.gfx;
+                    self.models.entry((tag, chunk)).or_insert_with(|| {
+                        let mesh = mesh_chunk(chunk, heightmap.as_deref(), vertex_colors);
+                        Model::from_mesh(gfx, Some("Terrain::chunk"), &mesh)
+                    });
+                }
+                self.displayed.insert(tag, roots);
+            }
+
+            let local = body.orientation().inverse()
+                * ((camera.as_dvec3() - body.position()) / body.radius());
+            let wanted = quadtree::select_chunks(local);
+            let mut ready = true;
+            for &chunk in &wanted {
+                let key = (tag, chunk);
+                keep.insert(key);
+                if !self.models.contains_key(&key) {
+                    ready = false;
+                    if self.pending.insert(key) {
+                        self.requests
+                            .send((key, heightmap.clone(), vertex_colors))
+                            .ok();
+                    }
+                }
+            }
+            if ready {
+                self.displayed.insert(tag, wanted);
+            }
+
+            let body_model = Mat4::from_cols_array_2d(&instance.model);
+            for &chunk in &self.displayed[&tag] {
+                keep.insert((tag, chunk));
+                self.draws.push((tag, chunk));
+                self.instances.push(Instance {
+                    model: (body_model * Mat4::from_translation(chunk.center().as_vec3()))
+                        .to_cols_array_2d(),
+                    ..instance
+                });
+            }
+        }
+        self.models.retain(|key, _| keep.contains(key));
+
+        if self.instances.len() > self.instance_capacity {
+            self.instance_capacity = self.instances.len().next_power_of_two();
+            self.instance_buffer = Self::create_instance_buffer(&self.gfx, self.instance_capacity);
+        }
+        self.gfx.queue.write_buffer(
+            &self.instance_buffer,
+            0,
+            bytemuck::cast_slice(&self.instances),
+        );
+    }
+
+    /// Draws the chunks chosen by the last update, with `default` for bodies
+    /// without a material of their own. Uses the instance buffer slot and
+    /// the material bind group, so it replaces whatever was bound there
+    /// before.
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, default: &'a Material) {
+        render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        let mut bound = None;
+        for (i, key @ (tag, _)) in self.draws.iter().enumerate() {
+            if bound != Some(tag) {
+                let material = self.materials.get(tag).unwrap_or(default);
+                render_pass.set_bind_group(2, material.bind_group(), &[]);
+                bound = Some(tag);
+            }
+            let i = i as u32;
+            render_pass.draw_model(&self.models[key], i..i + 1);
+        }
+    }
+}
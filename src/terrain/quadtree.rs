@@ -0,0 +1,277 @@
+//! Chunks of a body's surface, and choosing which of them to draw.
+//!
+//! The surface is a cube projected onto the unit sphere. Each face of the
+//! cube is the root of a quadtree, and a chunk is split into four children
+//! when the camera gets close to it compared to its size.
+
+use glam::DVec3;
+
+/// Chunks are split when the camera is closer to them than this many times
+/// their size.
+const SPLIT_DISTANCE: f64 = 2.0;
+
+/// Deepest level of the quadtree. Chunks at this level are about
+/// `2^-MAX_LEVEL` of a body's radius across.
+pub const MAX_LEVEL: u8 = 16;
+
+/// One of the six faces of the cube that is projected onto the sphere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Face {
+    PosX,
+    NegX,
+    PosY,
+    NegY,
+    PosZ,
+    NegZ,
+}
+
+impl Face {
+    pub const ALL: [Face; 6] = [
+        Self::PosX,
+        Self::NegX,
+        Self::PosY,
+        Self::NegY,
+        Self::PosZ,
+        Self::NegZ,
+    ];
+
+    /// The outward normal of the face, and the directions that chunk `x`
+    /// and `y` grow along. Seen from outside, `x` goes right and `y` goes
+    /// up.
+    pub fn axes(self) -> (DVec3, DVec3, DVec3) {
+        match self {
+            Self::PosX => (DVec3::X, DVec3::Y, DVec3::Z),
+            Self::NegX => (-DVec3::X, DVec3::Z, DVec3::Y),
+            Self::PosY => (DVec3::Y, DVec3::Z, DVec3::X),
+            Self::NegY => (-DVec3::Y, DVec3::X, DVec3::Z),
+            Self::PosZ => (DVec3::Z, DVec3::X, DVec3::Y),
+            Self::NegZ => (-DVec3::Z, DVec3::Y, DVec3::X),
+        }
+    }
+}
+
+/// A square part of one face, `2^-level` of the face across.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChunkId {
+    pub face: Face,
+    pub level: u8,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl ChunkId {
+    /// The chunk covering the whole face.
+    pub fn root(face: Face) -> Self {
+        Self {
+            face,
+            level: 0,
+            x: 0,
+            y: 0,
+        }
+    }
+
+    /// The four quarters of the chunk, in a fixed order.
+    pub fn children(self) -> [Self; 4] {
+        let child = |dx, dy| Self {
+            face: self.face,
+            level: self.level + 1,
+            x: self.x * 2 + dx,
+            y: self.y * 2 + dy,
+        };
+        [child(0, 0), child(1, 0), child(0, 1), child(1, 1)]
+    }
+
+    pub fn parent(self) -> Option<Self> {
+        Some(Self {
+            face: self.face,
+            level: self.level.checked_sub(1)?,
+            x: self.x / 2,
+            y: self.y / 2,
+        })
+    }
+
+    /// Point on the unit sphere at the given fraction of the way across the
+    /// chunk, from 0 to 1 along `x` and `y`.
+    pub fn point(self, s: f64, t: f64) -> DVec3 {
+        let (normal, u, v) = self.face.axes();
+        let scale = 0.5_f64.powi(self.level as i32);
+        let s = 2.0 * (self.x as f64 + s) * scale - 1.0;
+        let t = 2.0 * (self.y as f64 + t) * scale - 1.0;
+        cube_to_sphere(normal + s * u + t * v)
+    }
+
+    pub fn center(self) -> DVec3 {
+        self.point(0.5, 0.5)
+    }
+
+    /// Approximate length of the chunk's edges on the unit sphere.
+    pub fn size(self) -> f64 {
+        self.point(0.0, 0.5).distance(self.point(1.0, 0.5))
+    }
+
+    /// Radius of a sphere around [`ChunkId::center`] that contains the
+    /// whole chunk.
+    pub fn bounding_radius(self) -> f64 {
+        [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)]
+            .iter()
+            .map(|&(s, t)| self.point(s, t).distance(self.center()))
+            .fold(0.0, f64::max)
+    }
+
+    /// Whether the chunk should be replaced by its children for a camera at
+    /// the given position, relative to the center of a unit sphere.
+    pub fn should_split(self, camera: DVec3) -> bool {
+        let distance = (camera.distance(self.center()) - self.bounding_radius()).max(0.0);
+        self.level < MAX_LEVEL && distance < SPLIT_DISTANCE * self.size()
+    }
+}
+
+/// The chunks to draw for a camera at the given position, relative to the
+/// center of a unit sphere. They cover the sphere exactly once, and are
+/// always returned in the same order for the same position.
+pub fn select_chunks(camera: DVec3) -> Vec<ChunkId> {
+    let mut chunks = Vec::new();
+    let mut pending: Vec<ChunkId> = Face::ALL
+        .iter()
+        .rev()
+        .map(|&face| ChunkId::root(face))
+        .collect();
+    while let Some(chunk) = pending.pop() {
+        if chunk.should_split(camera) {
+            pending.extend(chunk.children().iter().rev());
+        } else {
+            chunks.push(chunk);
+        }
+    }
+    chunks
+}
+
+/// Maps a point on the surface of the cube from -1 to 1 onto the unit
+/// sphere, spreading the points more evenly than normalizing would.
+fn cube_to_sphere(p: DVec3) -> DVec3 {
+    let square = p * p;
+    DVec3::new(
+        p.x * (1.0 - square.y / 2.0 - square.z / 2.0 + square.y * square.z / 3.0).sqrt(),
+        p.y * (1.0 - square.z / 2.0 - square.x / 2.0 + square.z * square.x / 3.0).sqrt(),
+        p.z * (1.0 - square.x / 2.0 - square.y / 2.0 + square.x * square.y / 3.0).sqrt(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fraction of its face that a chunk covers.
+    fn area(chunk: ChunkId) -> f64 {
+        0.25_f64.powi(chunk.level as i32)
+    }
+
+    fn is_ancestor(ancestor: ChunkId, mut chunk: ChunkId) -> bool {
+        while let Some(parent) = chunk.parent() {
+            if parent == ancestor {
+                return true;
+            }
+            chunk = parent;
+        }
+        false
+    }
+
+    fn assert_covers_sphere(chunks: &[ChunkId]) {
+        let total: f64 = chunks.iter().copied().map(area).sum();
+        assert!((total - 6.0).abs() < 1e-12, "chunks cover {} faces", total);
+        for &a in chunks {
+            for &b in chunks {
+                assert!(!is_ancestor(a, b), "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn children_split_their_parent() {
+        let chunk = ChunkId {
+            face: Face::NegY,
+            level: 3,
+            x: 5,
+            y: 2,
+        };
+        let children = chunk.children();
+        for child in children {
+            assert_eq!(child.parent(), Some(chunk));
+        }
+        assert_eq!(ChunkId::root(Face::PosZ).parent(), None);
+        // The children meet in the middle of the parent.
+        assert!(children[0].point(1.0, 1.0).distance(chunk.center()) < 1e-12);
+        assert!(children[3].point(0.0, 0.0).distance(chunk.center()) < 1e-12);
+        assert!(children[0].point(0.0, 0.0).distance(chunk.point(0.0, 0.0)) < 1e-12);
+        assert!(children[3].point(1.0, 1.0).distance(chunk.point(1.0, 1.0)) < 1e-12);
+    }
+
+    #[test]
+    fn points_are_on_the_unit_sphere() {
+        for face in Face::ALL {
+            let chunk = ChunkId::root(face);
+            assert!(chunk.center().distance(face.axes().0) < 1e-12);
+            for (s, t) in [(0.0, 0.0), (0.3, 0.9), (1.0, 0.5), (1.0, 1.0)] {
+                assert!((chunk.point(s, t).length() - 1.0).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn far_cameras_draw_the_faces() {
+        let chunks = select_chunks(DVec3::new(0.0, 0.0, 100.0));
+        let roots: Vec<_> = Face::ALL.iter().map(|&face| ChunkId::root(face)).collect();
+        assert_eq!(chunks, roots);
+    }
+
+    #[test]
+    fn chunks_split_towards_the_camera() {
+        let camera = DVec3::new(0.3, -0.2, 1.0).normalize() * 1.001;
+        let chunks = select_chunks(camera);
+        assert_covers_sphere(&chunks);
+        let deepest = chunks.iter().max_by_key(|chunk| chunk.level).unwrap();
+        assert!(deepest.level >= 10);
+        assert!(deepest.center().distance(camera.normalize()) < 1e-2);
+        // The far side of the sphere stays coarse.
+        assert!(chunks
+            .iter()
+            .filter(|chunk| chunk.face == Face::NegZ)
+            .all(|chunk| chunk.level <= 2));
+    }
+
+    #[test]
+    fn chunks_merge_as_the_camera_leaves() {
+        let near = select_chunks(DVec3::new(1.01, 0.0, 0.0));
+        let middle = select_chunks(DVec3::new(3.0, 0.0, 0.0));
+        let far = select_chunks(DVec3::new(30.0, 0.0, 0.0));
+        assert_covers_sphere(&near);
+        assert_covers_sphere(&middle);
+        assert!(near.len() > middle.len());
+        assert!(middle.len() > far.len());
+        assert_eq!(far.len(), 6);
+        // Every chunk of the coarser selection is made of chunks of the
+        // finer one.
+        for chunk in &middle {
+            assert!(near
+                .iter()
+                .any(|&fine| fine == *chunk || is_ancestor(*chunk, fine)));
+        }
+    }
+
+    #[test]
+    fn selection_is_deterministic() {
+        let camera = DVec3::new(-0.4, 0.7, 0.6);
+        assert_eq!(select_chunks(camera), select_chunks(camera));
+    }
+
+    #[test]
+    fn splitting_stops_at_the_deepest_level() {
+        let chunk = ChunkId {
+            face: Face::PosX,
+            level: MAX_LEVEL,
+            x: 0,
+            y: 0,
+        };
+        assert!(!chunk.should_split(chunk.center()));
+    }
+}
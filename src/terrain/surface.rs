@@ -0,0 +1,387 @@
+//! Procedural elevation and coloring of a body's surface.
+//!
+//! Everything is sampled at points on the unit sphere and derived only from
+//! the [`Surface`] parameters, so the same seed always gives the same
+//! terrain.
+
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+use glam::{DVec3, Vec3};
+
+/// Number of octaves of noise summed for rolling terrain and for ridges.
+const OCTAVES: u32 = 8;
+
+/// Depth of a crater below the surrounding terrain, as a fraction of its
+/// radius.
+const CRATER_DEPTH: f64 = 0.1;
+/// Height of a crater's rim, as a fraction of its depth.
+const CRATER_RIM: f64 = 0.3;
+/// Width of a crater's rim, as a fraction of its radius.
+const CRATER_RIM_WIDTH: f64 = 0.25;
+
+/// Parameters for generating a body's surface.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Surface {
+    pub seed: u64,
+    /// Height of the tallest noise features above the mean radius, as a
+    /// fraction of the radius.
+    pub relief: f64,
+    /// Number of the largest noise features around the sphere.
+    pub frequency: f64,
+    /// How much of the relief is ridged mountains rather than rolling
+    /// hills, from 0 to 1.
+    pub ridges: f64,
+    pub craters: u32,
+    /// Radius of the largest crater (radians).
+    pub max_crater_radius: f64,
+    pub biome: Biome,
+}
+
+/// How a surface is colored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Biome {
+    /// Oceans, beaches, vegetation, rock and ice caps. The sea level is a
+    /// fraction of the relief, and the sea is flat.
+    Terran { sea_level: f64 },
+    /// Bare rock, lighter at higher altitudes.
+    Barren,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Crater {
+    center: DVec3,
+    radius: f64,
+}
+
+/// A [`Surface`] ready to be sampled.
+#[derive(Debug, Clone)]
+pub struct Heightmap {
+    surface: Surface,
+    craters: Vec<Crater>,
+}
+
+impl Heightmap {
+    /// Places the surface's craters.
+    pub fn new(surface: Surface) -> Self {
+        let mut rng = Rng::new(surface.seed);
+        let craters = (0..surface.craters)
+            .map(|_| {
+                let z = 2.0 * rng.next_f64() - 1.0;
+                let phi = TAU * rng.next_f64();
+                let r = (1.0 - z * z).sqrt();
+                Crater {
+                    center: DVec3::new(r * phi.cos(), r * phi.sin(), z),
+                    // Small craters are much more common than large ones.
+                    radius: surface.max_crater_radius * rng.next_f64().powi(3),
+                }
+            })
+            .collect();
+        Self { surface, craters }
+    }
+
+    pub fn surface(&self) -> &Surface {
+        &self.surface
+    }
+
+    /// Height of the terrain above the mean radius, as a fraction of the
+    /// radius, at the point on the unit sphere in the given direction. The
+    /// sea, if any, is included.
+    pub fn height(&self, direction: DVec3) -> f64 {
+        let height = self.elevation(direction) * self.surface.relief;
+        match self.surface.biome {
+            Biome::Terran { sea_level } => height.max(sea_level * self.surface.relief),
+            Biome::Barren => height,
+        }
+    }
+
+    /// The point on the surface in the given direction, relative to the
+    /// center of a unit sphere.
+    pub fn point(&self, direction: DVec3) -> DVec3 {
+        direction * (1.0 + self.height(direction))
+    }
+
+    /// Height of the ground as a fraction of the relief, ignoring the sea.
+    /// Mostly from -1 to 1.
+    pub fn elevation(&self, direction: DVec3) -> f64 {
+        let seed = self.surface.seed;
+        let p = direction * self.surface.frequency;
+        let hills = fractal_noise(seed, p);
+        let ridges = 2.0 * ridged_noise(seed ^ 0x5eed_0001, p) - 1.0;
+        let terrain = hills + (ridges - hills) * self.surface.ridges;
+
+        let craters: f64 = self
+            .craters
+            .iter()
+            .map(|crater| crater_profile(crater, direction))
+            .sum();
+        terrain + craters / self.surface.relief
+    }
+
+    /// Color of the surface in the given direction. The body frame's Z axis
+    /// is the pole, so latitude is measured from the XY plane.
+    pub fn color(&self, direction: DVec3) -> Vec3 {
+        let elevation = self.elevation(direction);
+        let latitude = direction.z.clamp(-1.0, 1.0).asin().abs() / FRAC_PI_2;
+        match self.surface.biome {
+            Biome::Terran { sea_level } => {
+                if elevation < sea_level {
+                    let depth = ((sea_level - elevation) * 2.0).min(1.0);
+                    return ramp(&[(0.0, SHALLOW_SEA), (1.0, DEEP_SEA)], depth);
+                }
+                // The snow line comes down towards the poles.
+                let altitude = (elevation - sea_level) / (1.0 - sea_level);
+                let snow_line = 0.8 * (1.0 - latitude.powi(4));
+                let land = ramp(
+                    &[(0.0, BEACH), (0.05, LOWLAND), (0.4, HIGHLAND), (0.7, ROCK)],
+                    altitude,
+                );
+                ramp(&[(-0.05, land), (0.0, SNOW)], altitude - snow_line)
+            }
+            Biome::Barren => ramp(&[(-1.0, DARK_ROCK), (1.0, LIGHT_ROCK)], elevation),
+        }
+    }
+}
+
+const DEEP_SEA: Vec3 = glam::const_vec3!([0.02, 0.06, 0.25]);
+const SHALLOW_SEA: Vec3 = glam::const_vec3!([0.05, 0.2, 0.5]);
+const BEACH: Vec3 = glam::const_vec3!([0.76, 0.7, 0.5]);
+const LOWLAND: Vec3 = glam::const_vec3!([0.2, 0.45, 0.15]);
+const HIGHLAND: Vec3 = glam::const_vec3!([0.4, 0.35, 0.2]);
+const ROCK: Vec3 = glam::const_vec3!([0.45, 0.42, 0.4]);
+const SNOW: Vec3 = glam::const_vec3!([0.95, 0.95, 0.97]);
+const DARK_ROCK: Vec3 = glam::const_vec3!([0.3, 0.3, 0.3]);
+const LIGHT_ROCK: Vec3 = glam::const_vec3!([0.7, 0.7, 0.68]);
+
+/// Interpolates linearly between colors at increasing positions, holding the
+/// first and last colors beyond the ends.
+fn ramp(stops: &[(f64, Vec3)], x: f64) -> Vec3 {
+    let (first, last) = (stops[0], stops[stops.len() - 1]);
+    if x <= first.0 {
+        return first.1;
+    }
+    for pair in stops.windows(2) {
+        let ((x0, c0), (x1, c1)) = (pair[0], pair[1]);
+        if x <= x1 {
+            return c0.lerp(c1, ((x - x0) / (x1 - x0)) as f32);
+        }
+    }
+    last.1
+}
+
+/// Height added by a crater, as a fraction of the radius: a bowl inside,
+/// and a raised rim around the edge.
+fn crater_profile(crater: &Crater, direction: DVec3) -> f64 {
+    let reach = crater.radius * (1.0 + 3.0 * CRATER_RIM_WIDTH);
+    let cos = direction.dot(crater.center);
+    if cos < reach.cos() {
+        return 0.0;
+    }
+    let x = cos.clamp(-1.0, 1.0).acos() / crater.radius;
+    let depth = CRATER_DEPTH * crater.radius;
+    let bowl = if x < 1.0 { x * x - 1.0 } else { 0.0 };
+    let rim = CRATER_RIM * (-((x - 1.0) / CRATER_RIM_WIDTH).powi(2)).exp();
+    depth * (bowl + rim)
+}
+
+/// Sum of octaves of gradient noise, from about -1 to 1.
+fn fractal_noise(seed: u64, p: DVec3) -> f64 {
+    let mut total = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut norm = 0.0;
+    for octave in 0..OCTAVES {
+        total += amplitude * gradient_noise(seed.wrapping_add(octave as u64), p * frequency);
+        norm += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    total / norm
+}
+
+/// Noise with sharp crests where gradient noise crosses zero, from 0 to 1.
+/// Each octave is weighted by the one before, so that detail gathers on the
+/// ridges.
+fn ridged_noise(seed: u64, p: DVec3) -> f64 {
+    let mut total = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut weight = 1.0;
+    let mut norm = 0.0;
+    for octave in 0..OCTAVES {
+        let ridge = 1.0 - gradient_noise(seed.wrapping_add(octave as u64), p * frequency).abs();
+        let ridge = ridge * ridge * weight;
+        weight = ridge.clamp(0.0, 1.0);
+        total += amplitude * ridge;
+        norm += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    total / norm
+}
+
+/// Perlin's gradient noise, from about -1 to 1, with the gradients picked by
+/// hashing the lattice points with the seed.
+fn gradient_noise(seed: u64, p: DVec3) -> f64 {
+    let cell = p.floor();
+    let f = p - cell;
+    let fade = |t: f64| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let (u, v, w) = (fade(f.x), fade(f.y), fade(f.z));
+    let (x, y, z) = (cell.x as i64, cell.y as i64, cell.z as i64);
+
+    let corner = |dx: i64, dy: i64, dz: i64| {
+        let gradient = GRADIENTS[(hash(seed, x + dx, y + dy, z + dz) % 12) as usize];
+        gradient.dot(f - DVec3::new(dx as f64, dy as f64, dz as f64))
+    };
+    let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+    lerp(
+        lerp(
+            lerp(corner(0, 0, 0), corner(1, 0, 0), u),
+            lerp(corner(0, 1, 0), corner(1, 1, 0), u),
+            v,
+        ),
+        lerp(
+            lerp(corner(0, 0, 1), corner(1, 0, 1), u),
+            lerp(corner(0, 1, 1), corner(1, 1, 1), u),
+            v,
+        ),
+        w,
+    )
+}
+
+const GRADIENTS: [DVec3; 12] = [
+    glam::const_dvec3!([1.0, 1.0, 0.0]),
+    glam::const_dvec3!([-1.0, 1.0, 0.0]),
+    glam::const_dvec3!([1.0, -1.0, 0.0]),
+    glam::const_dvec3!([-1.0, -1.0, 0.0]),
+    glam::const_dvec3!([1.0, 0.0, 1.0]),
+    glam::const_dvec3!([-1.0, 0.0, 1.0]),
+    glam::const_dvec3!([1.0, 0.0, -1.0]),
+    glam::const_dvec3!([-1.0, 0.0, -1.0]),
+    glam::const_dvec3!([0.0, 1.0, 1.0]),
+    glam::const_dvec3!([0.0, -1.0, 1.0]),
+    glam::const_dvec3!([0.0, 1.0, -1.0]),
+    glam::const_dvec3!([0.0, -1.0, -1.0]),
+];
+
+fn hash(seed: u64, x: i64, y: i64, z: i64) -> u64 {
+    let mut h = seed;
+    for value in [x, y, z] {
+        h = splitmix64(h ^ value as u64);
+    }
+    h
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// A small deterministic random number generator.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        splitmix64(self.0)
+    }
+
+    /// Uniform in [0, 1).
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn surface(seed: u64, biome: Biome) -> Surface {
+        Surface {
+            seed,
+            relief: 0.02,
+            frequency: 4.0,
+            ridges: 0.5,
+            craters: 40,
+            max_crater_radius: 0.3,
+            biome,
+        }
+    }
+
+    const BIOMES: [Biome; 2] = [Biome::Terran { sea_level: 0.0 }, Biome::Barren];
+
+    /// Directions spread evenly over the sphere.
+    fn directions() -> impl Iterator<Item = DVec3> {
+        let count = 500;
+        let golden_angle = std::f64::consts::PI * (3.0 - 5f64.sqrt());
+        (0..count).map(move |i| {
+            let z = 1.0 - (2 * i + 1) as f64 / count as f64;
+            let r = (1.0 - z * z).sqrt();
+            let phi = golden_angle * i as f64;
+            DVec3::new(r * phi.cos(), r * phi.sin(), z)
+        })
+    }
+
+    #[test]
+    fn same_seed_same_surface() {
+        for biome in BIOMES {
+            let a = Heightmap::new(surface(42, biome));
+            let b = Heightmap::new(surface(42, biome));
+            for direction in directions() {
+                assert_eq!(a.height(direction), b.height(direction));
+                assert_eq!(a.color(direction), b.color(direction));
+            }
+        }
+    }
+
+    #[test]
+    fn different_seeds_different_surfaces() {
+        for biome in BIOMES {
+            let a = Heightmap::new(surface(1, biome));
+            let b = Heightmap::new(surface(2, biome));
+            let different_heights = directions()
+                .filter(|&direction| a.elevation(direction) != b.elevation(direction))
+                .count();
+            let different_colors = directions()
+                .filter(|&direction| a.color(direction) != b.color(direction))
+                .count();
+            assert!(
+                different_heights > 450,
+                "{} heights differ",
+                different_heights
+            );
+            assert!(different_colors > 250, "{} colors differ", different_colors);
+        }
+    }
+
+    #[test]
+    fn the_sea_is_flat() {
+        let sea_level = 0.1;
+        let heightmap = Heightmap::new(surface(3, Biome::Terran { sea_level }));
+        let mut sea = 0;
+        for direction in directions() {
+            let height = heightmap.height(direction);
+            assert!(height >= sea_level * 0.02);
+            if heightmap.elevation(direction) < sea_level {
+                assert_eq!(height, sea_level * 0.02);
+                sea += 1;
+            }
+        }
+        assert!(sea > 0);
+    }
+
+    #[test]
+    fn points_are_displaced_along_the_direction() {
+        let heightmap = Heightmap::new(surface(4, Biome::Barren));
+        for direction in directions() {
+            let point = heightmap.point(direction);
+            assert!(point.normalize().distance(direction) < 1e-12);
+            assert!((point.length() - 1.0).abs() < 0.1);
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Packs the glyphs of the built-in font into a single coverage texture.
+
+use glam::{IVec2, UVec2};
+
+use super::font::{CELL_HEIGHT, CELL_WIDTH, FIRST_CHAR, GLYPHS, MAX_COVERAGE};
+
+/// Width of the atlas texture, in pixels.
+const ATLAS_WIDTH: u32 = 128;
+
+/// Empty space left between glyphs, so that they don't bleed into each other.
+const PADDING: u32 = 1;
+
+/// Places rectangles left to right in rows ("shelves"), starting a new shelf
+/// below the tallest rectangle when the current one is full.
+#[derive(Debug, Clone)]
+pub struct ShelfPacker {
+    width: u32,
+    cursor: UVec2,
+    shelf_height: u32,
+}
+
+impl ShelfPacker {
+    pub fn new(width: u32) -> Self {
+        Self {
+            width,
+            cursor: UVec2::ZERO,
+            shelf_height: 0,
+        }
+    }
+
+    /// Reserves space for a rectangle, returning its top left corner, or
+    /// `None` if it is wider than the packer.
+    pub fn pack(&mut self, size: UVec2) -> Option<UVec2> {
+        if size.x > self.width {
+            return None;
+        }
+        if self.cursor.x + size.x > self.width {
+            self.cursor = UVec2::new(0, self.cursor.y + self.shelf_height + PADDING);
+            self.shelf_height = 0;
+        }
+        let position = self.cursor;
+        self.cursor.x += size.x + PADDING;
+        self.shelf_height = self.shelf_height.max(size.y);
+        Some(position)
+    }
+
+    /// Height of the area used so far.
+    pub fn height(&self) -> u32 {
+        self.cursor.y + self.shelf_height
+    }
+}
+
+/// Where a glyph's bitmap is in the atlas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyph {
+    /// Top left corner in the atlas, in pixels.
+    pub position: UVec2,
+    pub size: UVec2,
+    /// Offset of the bitmap from the top left corner of the glyph's cell.
+    pub offset: IVec2,
+}
+
+/// Single-channel coverage texture containing every glyph of the font.
+#[derive(Debug, Clone)]
+pub struct Atlas {
+    pub size: UVec2,
+    /// Coverage values, row by row.
+    pub pixels: Vec<u8>,
+    glyphs: Vec<Glyph>,
+}
+
+impl Atlas {
+    pub fn new() -> Self {
+        // Trim each glyph to the pixels it actually covers.
+        let bounds: Vec<_> = GLYPHS.iter().map(ink_bounds).collect();
+
+        let mut packer = ShelfPacker::new(ATLAS_WIDTH);
+        let glyphs: Vec<_> = bounds
+            .iter()
+            .map(|&(min, size)| Glyph {
+                position: packer.pack(size).expect("glyph wider than atlas"),
+                size,
+                offset: min.as_ivec2(),
+            })
+            .collect();
+
+        let size = UVec2::new(ATLAS_WIDTH, packer.height().max(1));
+        let mut pixels = vec![0; (size.x * size.y) as usize];
+        for ((cell, glyph), &(min, _)) in GLYPHS.iter().zip(&glyphs).zip(&bounds) {
+            for y in 0..glyph.size.y {
+                for x in 0..glyph.size.x {
+                    let value = coverage(cell, min + UVec2::new(x, y));
+                    let pixel = glyph.position + UVec2::new(x, y);
+                    pixels[(pixel.y * size.x + pixel.x) as usize] =
+                        (value * 255 / MAX_COVERAGE) as u8;
+                }
+            }
+        }
+
+        Self {
+            size,
+            pixels,
+            glyphs,
+        }
+    }
+
+    /// The glyph for a character, falling back to `?` for characters that
+    /// aren't in the font.
+    pub fn glyph(&self, c: char) -> &Glyph {
+        let index = |c: char| (c as u32).checked_sub(FIRST_CHAR as u32);
+        index(c)
+            .and_then(|i| self.glyphs.get(i as usize))
+            .unwrap_or_else(|| &self.glyphs[index('?').unwrap() as usize])
+    }
+}
+
+impl Default for Atlas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Coverage of one pixel of a glyph cell, from 0 to `MAX_COVERAGE`.
+fn coverage(cell: &[u32; CELL_HEIGHT as usize], pixel: UVec2) -> u32 {
+    (cell[pixel.y as usize] >> (4 * (CELL_WIDTH - 1 - pixel.x))) & 0xf
+}
+
+/// Top left corner and size of the covered part of a glyph cell. Blank
+/// glyphs are empty rectangles at the origin.
+fn ink_bounds(cell: &[u32; CELL_HEIGHT as usize]) -> (UVec2, UVec2) {
+    let mut min = UVec2::new(CELL_WIDTH, CELL_HEIGHT);
+    let mut max = UVec2::ZERO;
+    for y in 0..CELL_HEIGHT {
+        for x in 0..CELL_WIDTH {
+            if coverage(cell, UVec2::new(x, y)) > 0 {
+                min = min.min(UVec2::new(x, y));
+                max = max.max(UVec2::new(x + 1, y + 1));
+            }
+        }
+    }
+    if min.x >= max.x {
+        (UVec2::ZERO, UVec2::ZERO)
+    } else {
+        (min, max - min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packer_fills_shelves_left_to_right() {
+        let mut packer = ShelfPacker::new(10);
+        assert_eq!(packer.pack(UVec2::new(4, 3)), Some(UVec2::new(0, 0)));
+        assert_eq!(packer.pack(UVec2::new(4, 5)), Some(UVec2::new(5, 0)));
+        assert_eq!(packer.height(), 5);
+        // Doesn't fit next to the others, so starts a shelf below the
+        // tallest rectangle.
+        assert_eq!(packer.pack(UVec2::new(2, 2)), Some(UVec2::new(0, 6)));
+        assert_eq!(packer.height(), 8);
+        assert_eq!(packer.pack(UVec2::new(11, 1)), None);
+        assert_eq!(packer.pack(UVec2::new(10, 1)), Some(UVec2::new(0, 9)));
+    }
+
+    #[test]
+    fn glyphs_fit_without_overlapping() {
+        let atlas = Atlas::new();
+        assert_eq!(atlas.pixels.len(), (atlas.size.x * atlas.size.y) as usize);
+        let glyphs: Vec<_> = (FIRST_CHAR..='~')
+            .map(|c| *atlas.glyph(c))
+            .filter(|glyph| glyph.size.x > 0)
+            .collect();
+        for (i, a) in glyphs.iter().enumerate() {
+            let a_max = a.position + a.size;
+            assert!(a_max.x <= atlas.size.x && a_max.y <= atlas.size.y);
+            for b in &glyphs[i + 1..] {
+                let b_max = b.position + b.size;
+                let apart = a_max.x + PADDING <= b.position.x
+                    || b_max.x + PADDING <= a.position.x
+                    || a_max.y + PADDING <= b.position.y
+                    || b_max.y + PADDING <= a.position.y;
+                assert!(apart, "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn glyphs_match_the_font() {
+        let atlas = Atlas::new();
+        for (i, cell) in GLYPHS.iter().enumerate() {
+            let c = char::from_u32(FIRST_CHAR as u32 + i as u32).unwrap();
+            let glyph = atlas.glyph(c);
+            for y in 0..CELL_HEIGHT {
+                for x in 0..CELL_WIDTH {
+                    let cell_pixel = UVec2::new(x, y).as_ivec2() - glyph.offset;
+                    let inside = cell_pixel.cmpge(IVec2::ZERO).all()
+                        && cell_pixel.cmplt(glyph.size.as_ivec2()).all();
+                    let expected = coverage(cell, UVec2::new(x, y));
+                    if inside {
+                        let pixel = glyph.position + cell_pixel.as_uvec2();
+                        let value = atlas.pixels[(pixel.y * atlas.size.x + pixel.x) as usize];
+                        assert_eq!(value as u32, expected * 255 / MAX_COVERAGE, "{:?}", c);
+                    } else {
+                        assert_eq!(expected, 0, "{:?} trimmed ink", c);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blank_and_unknown_glyphs() {
+        let atlas = Atlas::new();
+        assert_eq!(atlas.glyph(' ').size, UVec2::ZERO);
+        assert_eq!(atlas.glyph('é'), atlas.glyph('?'));
+        assert_eq!(atlas.glyph('\t'), atlas.glyph('?'));
+        assert_ne!(atlas.glyph('A'), atlas.glyph('?'));
+    }
+}
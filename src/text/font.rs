@@ -0,0 +1,737 @@
+//! Built-in monospace font, rasterized from DejaVu Sans Mono at 13px.
+//!
+//! Each glyph is an 8x16 pixel cell, covering the printable ASCII range. Rows
+//! are stored top to bottom, with one 4-bit coverage value per pixel, leftmost
+//! pixel in the highest nibble.
+
+// The glyphs are derived from DejaVu Sans Mono, whose license follows.
+//
+// Fonts are (c) Bitstream (see below). DejaVu changes are in public domain.
+//
+// Bitstream Vera Fonts Copyright
+// ------------------------------
+//
+// Copyright (c) 2003 by Bitstream, Inc. All Rights Reserved. Bitstream Vera is
+// a trademark of Bitstream, Inc.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of the fonts accompanying this license ("Fonts") and associated
+// documentation files (the "Font Software"), to reproduce and distribute the
+// Font Software, including without limitation the rights to use, copy, merge,
+// publish, distribute, and/or sell copies of the Font Software, and to permit
+// persons to whom the Font Software is furnished to do so, subject to the
+// following conditions:
+//
+// The above copyright and trademark notices and this permission notice shall
+// be included in all copies of one or more of the Font Software typefaces.
+//
+// The Font Software may be modified, altered, or added to, and in particular
+// the designs of glyphs or characters in the Fonts may be modified and
+// additional glyphs or characters may be added to the Fonts, only if the fonts
+// are renamed to names not containing either the words "Bitstream" or the word
+// "Vera".
+//
+// This License becomes null and void to the extent applicable to Fonts or Font
+// Software that has been modified and is distributed under the "Bitstream
+// Vera" names.
+//
+// The Font Software may be sold as part of a larger software package but no
+// copy of one or more of the Font Software typefaces may be sold by itself.
+//
+// THE FONT SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO ANY WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT OF COPYRIGHT, PATENT,
+// TRADEMARK, OR OTHER RIGHT. IN NO EVENT SHALL BITSTREAM OR THE GNOME
+// FOUNDATION BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, INCLUDING
+// ANY GENERAL, SPECIAL, INDIRECT, INCIDENTAL, OR CONSEQUENTIAL DAMAGES,
+// WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF
+// THE USE OR INABILITY TO USE THE FONT SOFTWARE OR FROM OTHER DEALINGS IN THE
+// FONT SOFTWARE.
+//
+// Except as contained in this notice, the names of Gnome, the Gnome
+// Foundation, and Bitstream Inc., shall not be used in advertising or
+// otherwise to promote the sale, use or other dealings in this Font Software
+// without prior written authorization from the Gnome Foundation or Bitstream
+// Inc., respectively. For further information, contact: fonts at gnome dot
+// org.
+
+/// Width of a glyph cell, in pixels.
+pub const CELL_WIDTH: u32 = 8;
+
+/// Height of a glyph cell, in pixels.
+pub const CELL_HEIGHT: u32 = 16;
+
+/// First character in the font.
+pub const FIRST_CHAR: char = ' ';
+
+/// Largest value of a coverage nibble.
+pub const MAX_COVERAGE: u32 = 15;
+
+#[rustfmt::skip]
+pub const GLYPHS: [[u32; CELL_HEIGHT as usize]; 95] = [
+    // ' '
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '!'
+    [
+        0x00000000, 0x00000000, 0x00065000, 0x000b9000,
+        0x000b9000, 0x000b9000, 0x000b9000, 0x000a8000,
+        0x00097000, 0x00000000, 0x00076000, 0x000b9000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '"'
+    [
+        0x00000000, 0x00000000, 0x00723500, 0x00d46900,
+        0x00d46900, 0x00d46900, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '#'
+    [
+        0x00000000, 0x00000000, 0x00042150, 0x000d25b0,
+        0x002d0870, 0x3bcebec8, 0x14b85e43, 0x00d25b00,
+        0x9af9cc91, 0x6ab6e761, 0x0961e000, 0x0d25a000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '$'
+    [
+        0x00000000, 0x00000000, 0x00035000, 0x00289400,
+        0x04dabb70, 0x0b746000, 0x0a946000, 0x02cfc710,
+        0x00059bb0, 0x000462f1, 0x062466d0, 0x06ceeb20,
+        0x00046000, 0x00035000, 0x00000000, 0x00000000,
+    ],
+    // '%'
+    [
+        0x00000000, 0x00000000, 0x00100000, 0x3dc90000,
+        0xb30b3000, 0xa40c3000, 0x2cd828a3, 0x005a8200,
+        0x4a51bcc1, 0x00058068, 0x00059068, 0x0000adb1,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '&'
+    [
+        0x00000000, 0x00000000, 0x00599300, 0x04e66500,
+        0x08a00000, 0x04e10000, 0x05fa0000, 0x3e3d6058,
+        0x8803e378, 0x98007db4, 0x5e300dd0, 0x08ece9d6,
+        0x00121000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '\''
+    [
+        0x00000000, 0x00000000, 0x00054000, 0x00098000,
+        0x00098000, 0x00098000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '('
+    [
+        0x00000000, 0x00000000, 0x00008500, 0x0003e000,
+        0x00098000, 0x000e4000, 0x003f1000, 0x004f0000,
+        0x004f0000, 0x002f1000, 0x000d4000, 0x00089000,
+        0x0002e100, 0x00007500, 0x00000000, 0x00000000,
+    ],
+    // ')'
+    [
+        0x00000000, 0x00000000, 0x00760000, 0x002e1000,
+        0x000a7000, 0x0006c000, 0x0003f000, 0x0002f200,
+        0x0002f200, 0x0004f000, 0x0007b000, 0x000b6000,
+        0x003e0000, 0x00750000, 0x00000000, 0x00000000,
+    ],
+    // '*'
+    [
+        0x00000000, 0x00000000, 0x00052000, 0x04084140,
+        0x05bb9a40, 0x004ed200, 0x09885970, 0x00084000,
+        0x00021000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '+'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00043000, 0x00098000, 0x00098000, 0x599dc992,
+        0x488cb882, 0x00098000, 0x00098000, 0x00022000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // ','
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x000ba000, 0x000da000,
+        0x001f3000, 0x00490000, 0x00000000, 0x00000000,
+    ],
+    // '-'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00122100,
+        0x00bff900, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '.'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x000ba000, 0x000db000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '/'
+    [
+        0x00000000, 0x00000000, 0x00000360, 0x00000c60,
+        0x00004e00, 0x0000b700, 0x0003e100, 0x000a8000,
+        0x002f1000, 0x00990000, 0x01f20000, 0x08a00000,
+        0x1e300000, 0x13000000, 0x00000000, 0x00000000,
+    ],
+    // '0'
+    [
+        0x00000000, 0x00000000, 0x00499300, 0x04e78e20,
+        0x0b800b90, 0x0f4006d0, 0x2f2755f0, 0x2f2ca4f0,
+        0x1f3106e0, 0x0e5008b0, 0x09b01d70, 0x01ceea00,
+        0x00021000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '1'
+    [
+        0x00000000, 0x00000000, 0x00378000, 0x08ecf000,
+        0x0104f000, 0x0004f000, 0x0004f000, 0x0004f000,
+        0x0004f000, 0x0004f000, 0x0025f220, 0x04fffff0,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '2'
+    [
+        0x00000000, 0x00000000, 0x03898200, 0x0e97ae30,
+        0x01000c90, 0x00000a90, 0x00002f40, 0x0001c800,
+        0x000ba000, 0x00aa0000, 0x0ad22210, 0x0fffffb0,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '3'
+    [
+        0x00000000, 0x00000000, 0x04898300, 0x0a879f30,
+        0x00000b90, 0x00000b80, 0x0059ca10, 0x0048ad20,
+        0x000009b0, 0x000007d0, 0x14002c90, 0x1efdfb10,
+        0x00121000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '4'
+    [
+        0x00000000, 0x00000000, 0x00005800, 0x0003ff00,
+        0x000b9f00, 0x00796f00, 0x02d16f00, 0x0b606f00,
+        0x4d448f41, 0x4bbbdfb3, 0x00006f00, 0x00006f00,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '5'
+    [
+        0x00000000, 0x00000000, 0x05888810, 0x09c99910,
+        0x09800000, 0x09942000, 0x09edfb10, 0x01002e70,
+        0x000008b0, 0x000008b0, 0x14003e70, 0x1eedf900,
+        0x00221000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '6'
+    [
+        0x00000000, 0x00000000, 0x00289810, 0x03ea7930,
+        0x0b900000, 0x0f334000, 0x2f9cce30, 0x2fa009b0,
+        0x2f4004f0, 0x0e4005e0, 0x09a00ab0, 0x01ceed20,
+        0x00022000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '7'
+    [
+        0x00000000, 0x00000000, 0x18888870, 0x19999da0,
+        0x00000e40, 0x00005e00, 0x0000b800, 0x0002f300,
+        0x0008c000, 0x000d6000, 0x004f1000, 0x00aa0000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '8'
+    [
+        0x00000000, 0x00000000, 0x00599400, 0x08d67e60,
+        0x0e6008b0, 0x0c7009a0, 0x04c89b20, 0x05d89d40,
+        0x1f4007c0, 0x2f2004f0, 0x0e8009c0, 0x05eded30,
+        0x00022000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '9'
+    [
+        0x00000000, 0x00000000, 0x00699300, 0x09d68e30,
+        0x1f400a90, 0x3f2008c0, 0x1f3009d0, 0x0bb46ee0,
+        0x019ba6d0, 0x000008a0, 0x02004e40, 0x07fef600,
+        0x00121000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // ':'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x000a8000, 0x000db000, 0x00021000,
+        0x00000000, 0x00000000, 0x000ba000, 0x000db000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // ';'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x000a8000, 0x000db000, 0x00021000,
+        0x00000000, 0x00000000, 0x000ba000, 0x000da000,
+        0x001f3000, 0x00490000, 0x00000000, 0x00000000,
+    ],
+    // '<'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000001, 0x000028e4, 0x006ce830, 0x5eb50000,
+        0x4cc72000, 0x0039eb50, 0x000016c4, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '='
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x7dddddd3, 0x12222220,
+        0x36666661, 0x6bbbbbb3, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '>'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x10000000, 0x7d720000, 0x049eb400, 0x00016cd2,
+        0x00028db2, 0x16ce8200, 0x8b500000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '?'
+    [
+        0x00000000, 0x00000000, 0x00699400, 0x08b68f40,
+        0x01000b90, 0x00001e50, 0x0001d800, 0x000a9000,
+        0x000d6000, 0x00073000, 0x00084000, 0x000d6000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '@'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x005bdb30,
+        0x08c314e1, 0x3d101186, 0x8609dcc8, 0xb33d00a8,
+        0xd2690068, 0xb34c00a8, 0x960acbd8, 0x3d103311,
+        0x08c20000, 0x005cee70, 0x00000000, 0x00000000,
+    ],
+    // 'A'
+    [
+        0x00000000, 0x00000000, 0x00076000, 0x002fe000,
+        0x007ad400, 0x00b69900, 0x01f24e00, 0x06d01f30,
+        0x0ac88e80, 0x0f988ac0, 0x5e0002f2, 0x9b0000d7,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'B'
+    [
+        0x00000000, 0x00000000, 0x08887300, 0x0fb9af60,
+        0x0f6007d0, 0x0f6008c0, 0x0fb9ad40, 0x0fa88d60,
+        0x0f6003f1, 0x0f6002f4, 0x0f6028e1, 0x0ffffb30,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'C'
+    [
+        0x00000000, 0x00000000, 0x00179940, 0x01db68a0,
+        0x09b00000, 0x0e600000, 0x1f400000, 0x2f400000,
+        0x0f400000, 0x0c800000, 0x05e30040, 0x007fdea0,
+        0x00002100, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'D'
+    [
+        0x00000000, 0x00000000, 0x18874000, 0x2faaeb10,
+        0x2f201d80, 0x2f2008c0, 0x2f2006f0, 0x2f2006f0,
+        0x2f2006e0, 0x2f200ab0, 0x2f227f40, 0x2fffb400,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'E'
+    [
+        0x00000000, 0x00000000, 0x06888870, 0x0bc99980,
+        0x0b800000, 0x0b800000, 0x0bc99960, 0x0bb88850,
+        0x0b800000, 0x0b800000, 0x0b822220, 0x0bfffff0,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'F'
+    [
+        0x00000000, 0x00000000, 0x04888880, 0x08e99990,
+        0x08b00000, 0x08b00000, 0x08e99960, 0x08d88850,
+        0x08b00000, 0x08b00000, 0x08b00000, 0x08b00000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'G'
+    [
+        0x00000000, 0x00000000, 0x00289820, 0x03e96990,
+        0x0c800010, 0x2f300000, 0x5f000000, 0x6f008dd0,
+        0x4f1027f0, 0x1f4004f0, 0x09c104f0, 0x01afdf90,
+        0x00012100, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'H'
+    [
+        0x00000000, 0x00000000, 0x18100380, 0x2f2006f0,
+        0x2f2006f0, 0x2f2006f0, 0x2fa99bf0, 0x2f888af0,
+        0x2f2006f0, 0x2f2006f0, 0x2f2006f0, 0x2f2006f0,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'I'
+    [
+        0x00000000, 0x00000000, 0x06888840, 0x079ec950,
+        0x000b8000, 0x000b8000, 0x000b8000, 0x000b8000,
+        0x000b8000, 0x000b8000, 0x012c8210, 0x0bffff80,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'J'
+    [
+        0x00000000, 0x00000000, 0x00588810, 0x0069bf20,
+        0x00004f20, 0x00004f20, 0x00004f20, 0x00004f20,
+        0x00004f20, 0x00004f00, 0x35008d00, 0x3eeee500,
+        0x00220000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'K'
+    [
+        0x00000000, 0x00000000, 0x18100173, 0x2f200bb0,
+        0x2f20ab10, 0x2f2ab100, 0x2fbf2000, 0x2fdbb000,
+        0x2f32e600, 0x2f206e20, 0x2f200bb0, 0x2f2002f7,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'L'
+    [
+        0x00000000, 0x00000000, 0x05500000, 0x09900000,
+        0x09900000, 0x09900000, 0x09900000, 0x09900000,
+        0x09900000, 0x09900000, 0x09a22220, 0x09fffff4,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'M'
+    [
+        0x00000000, 0x00000000, 0x48300482, 0x8f900be4,
+        0x8cd02bd4, 0x8ba477d4, 0x8b59c2d4, 0x8b0db0d4,
+        0x8b0650d4, 0x8b0000d4, 0x8b0000d4, 0x8b0000d4,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'N'
+    [
+        0x00000000, 0x00000000, 0x18400280, 0x2fd004f0,
+        0x2fd504f0, 0x2f7b04f0, 0x2f2e24f0, 0x2f2884f0,
+        0x2f22e4f0, 0x2f20b9f0, 0x2f204ff0, 0x2f200df0,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'O'
+    [
+        0x00000000, 0x00000000, 0x00499400, 0x06e78f40,
+        0x0d7009b0, 0x2f3006e0, 0x4f2004f1, 0x4f2004f2,
+        0x3f2004f0, 0x1f4007d0, 0x0ba01c80, 0x02deeb10,
+        0x00022000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'P'
+    [
+        0x00000000, 0x00000000, 0x06887400, 0x0bc9ae90,
+        0x0b8005f2, 0x0b8002f4, 0x0b8008f1, 0x0bedee60,
+        0x0b942000, 0x0b800000, 0x0b800000, 0x0b800000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'Q'
+    [
+        0x00000000, 0x00000000, 0x00499400, 0x06e78f40,
+        0x0d7009b0, 0x2f3006e0, 0x4f2004f1, 0x4f2004f2,
+        0x3f2004f0, 0x1f4007d0, 0x0ba01c80, 0x02deeb10,
+        0x00028d20, 0x00000730, 0x00000000, 0x00000000,
+    ],
+    // 'R'
+    [
+        0x00000000, 0x00000000, 0x18886100, 0x2fb9be30,
+        0x2f400ba0, 0x2f4009b0, 0x2f404e70, 0x2ffff800,
+        0x2f406e10, 0x2f400b80, 0x2f4004f1, 0x2f4000c8,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'S'
+    [
+        0x00000000, 0x00000000, 0x00599710, 0x09d76a50,
+        0x1f300000, 0x1f400000, 0x0be95100, 0x006aee40,
+        0x000009c0, 0x000004f0, 0x07100ac0, 0x0cfded20,
+        0x00122000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'T'
+    [
+        0x00000000, 0x00000000, 0x68888884, 0x799ed995,
+        0x000b9000, 0x000b9000, 0x000b9000, 0x000b9000,
+        0x000b9000, 0x000b9000, 0x000b9000, 0x000b9000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'U'
+    [
+        0x00000000, 0x00000000, 0x18200370, 0x2f4006d0,
+        0x2f4006d0, 0x2f4006d0, 0x2f4006d0, 0x2f4006d0,
+        0x1f4006d0, 0x0f4006d0, 0x0d800aa0, 0x04deec20,
+        0x00022000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'V'
+    [
+        0x00000000, 0x00000000, 0x46000073, 0x5e0002f3,
+        0x1f3006d0, 0x0b700a90, 0x07b00e40, 0x03f03f10,
+        0x00d47b00, 0x0098b600, 0x004ce200, 0x001fd000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'W'
+    [
+        0x00000000, 0x00000000, 0x82000046, 0xd600008a,
+        0xb70000a8, 0x890b90b6, 0x6b1ed0d3, 0x4d4ac1f1,
+        0x1e7796e0, 0x0eb36bc0, 0x0ce02fa0, 0x0ab00e70,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'X'
+    [
+        0x00000000, 0x00000000, 0x28000073, 0x0d7007d0,
+        0x04f21e40, 0x009aa900, 0x001ee100, 0x002ee100,
+        0x00aaa900, 0x04e12e30, 0x1d6008c0, 0x9c0001e6,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'Y'
+    [
+        0x00000000, 0x00000000, 0x56000074, 0x3f2005e1,
+        0x09a00d70, 0x01e46d00, 0x007ce400, 0x000db000,
+        0x000b8000, 0x000b8000, 0x000b8000, 0x000b8000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'Z'
+    [
+        0x00000000, 0x00000000, 0x07888882, 0x08999bf3,
+        0x00000c90, 0x00006d10, 0x0002e400, 0x000b9000,
+        0x006d1000, 0x01e40000, 0x0ab22221, 0x0ffffff6,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '['
+    [
+        0x00000000, 0x00000000, 0x000dd800, 0x000f2000,
+        0x000f2000, 0x000f2000, 0x000f2000, 0x000f2000,
+        0x000f2000, 0x000f2000, 0x000f2000, 0x000f2000,
+        0x000f5200, 0x000bb700, 0x00000000, 0x00000000,
+    ],
+    // '\\'
+    [
+        0x00000000, 0x00000000, 0x27000000, 0x0d500000,
+        0x07c00000, 0x00e40000, 0x008b0000, 0x001f3000,
+        0x0008a000, 0x0001f200, 0x00009900, 0x00002f10,
+        0x00000a80, 0x00000130, 0x00000000, 0x00000000,
+    ],
+    // ']'
+    [
+        0x00000000, 0x00000000, 0x00adb000, 0x0004d000,
+        0x0004d000, 0x0004d000, 0x0004d000, 0x0004d000,
+        0x0004d000, 0x0004d000, 0x0004d000, 0x0004d000,
+        0x0037d000, 0x008ba000, 0x00000000, 0x00000000,
+    ],
+    // '^'
+    [
+        0x00000000, 0x00000000, 0x00065000, 0x007de400,
+        0x04d24e20, 0x2d2005c1, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '_'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x88888887, 0x22222222,
+    ],
+    // '`'
+    [
+        0x00000000, 0x00610000, 0x007a0000, 0x00097000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'a'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00243000, 0x09ebcd20, 0x02000a90, 0x00478bb0,
+        0x0ac76ab0, 0x1f1008b0, 0x1f302eb0, 0x08fcdab0,
+        0x00120000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'b'
+    [
+        0x00000000, 0x00000000, 0x0a500000, 0x0b600000,
+        0x0b624100, 0x0bbdce30, 0x0bc009b0, 0x0b7004f0,
+        0x0b6002f0, 0x0b7004f0, 0x0bc10aa0, 0x0baedd20,
+        0x00012000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'c'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00014300, 0x008ebca0, 0x05e20030, 0x0a900000,
+        0x0b800000, 0x0a900000, 0x04e30030, 0x007ece90,
+        0x00002100, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'd'
+    [
+        0x00000000, 0x00000000, 0x00000780, 0x00000890,
+        0x00142890, 0x04ebdb90, 0x0d601e90, 0x2f100a90,
+        0x4f000890, 0x2f200a90, 0x0d801e90, 0x03eddb90,
+        0x00021000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'e'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00034100, 0x02dcce30, 0x0c8007b0, 0x2f3225f0,
+        0x4fbbbbb0, 0x1f100000, 0x0b900040, 0x01bece90,
+        0x00012100, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'f'
+    [
+        0x00000000, 0x00000000, 0x0003bda0, 0x000b8210,
+        0x012d5210, 0x08bfcb80, 0x000d4000, 0x000d4000,
+        0x000d4000, 0x000d4000, 0x000d4000, 0x000d4000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'g'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00142110, 0x04ebdb90, 0x0d601e90, 0x2f100a90,
+        0x4f000890, 0x1f200a90, 0x0c903e90, 0x02cec990,
+        0x00000a80, 0x03525e30, 0x03abb400, 0x00000000,
+    ],
+    // 'h'
+    [
+        0x00000000, 0x00000000, 0x0a500000, 0x0b600000,
+        0x0b624100, 0x0bacce30, 0x0bb00b80, 0x0b700890,
+        0x0b600890, 0x0b600890, 0x0b600890, 0x0b600890,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'i'
+    [
+        0x00000000, 0x00000000, 0x00078000, 0x00056000,
+        0x01221000, 0x04bd9000, 0x00089000, 0x00089000,
+        0x00089000, 0x00089000, 0x00089000, 0x0bdeedb0,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'j'
+    [
+        0x00000000, 0x00000000, 0x0003d000, 0x00029000,
+        0x00222000, 0x03bcf000, 0x0004f000, 0x0004f000,
+        0x0004f000, 0x0004f000, 0x0004f000, 0x0004f000,
+        0x0004f000, 0x034bb000, 0x0ab91000, 0x00000000,
+    ],
+    // 'k'
+    [
+        0x00000000, 0x00000000, 0x07a00000, 0x08b00000,
+        0x08b00020, 0x08b01b80, 0x08b1d800, 0x08cd9000,
+        0x08f9e200, 0x08b09c00, 0x08b00c80, 0x08b003f4,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'l'
+    [
+        0x00000000, 0x00000000, 0x0fff0000, 0x002f0000,
+        0x002f0000, 0x002f0000, 0x002f0000, 0x002f0000,
+        0x002f0000, 0x002f1000, 0x000e5000, 0x0005ef90,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'm'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x01220410, 0x4ebfbcd0, 0x4d0a80e2, 0x4b0980d4,
+        0x4b0980d4, 0x4b0980d4, 0x4b0980d4, 0x4b0980d4,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'n'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x01124100, 0x0bacce30, 0x0bb00b80, 0x0b700890,
+        0x0b600890, 0x0b600890, 0x0b600890, 0x0b600890,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'o'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00143000, 0x03ecdd20, 0x0c800b90, 0x1f3005d0,
+        0x2f2004f0, 0x1f3006d0, 0x0b900b90, 0x02ddec10,
+        0x00022000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'p'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x01124100, 0x0bbdce20, 0x0bc009b0, 0x0b7004f0,
+        0x0b6004f0, 0x0b7004e0, 0x0bc10aa0, 0x0baedd20,
+        0x0b612000, 0x0b600000, 0x08400000, 0x00000000,
+    ],
+    // 'q'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00142110, 0x03ecdcb0, 0x0c801db0, 0x1f3008b0,
+        0x2f2008b0, 0x1f3008b0, 0x0c801db0, 0x03ecdbb0,
+        0x000318b0, 0x000008b0, 0x00000680, 0x00000000,
+    ],
+    // 'r'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00110430, 0x00b9dce5, 0x00be3001, 0x00b90000,
+        0x00b80000, 0x00b80000, 0x00b80000, 0x00b80000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 's'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00144100, 0x03ecbe20, 0x09a00000, 0x08d41000,
+        0x018cfb10, 0x00001c70, 0x03000c70, 0x08fceb10,
+        0x00121000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 't'
+    [
+        0x00000000, 0x00000000, 0x00020000, 0x004d0000,
+        0x025d2210, 0x1bcfbb60, 0x004d0000, 0x004d0000,
+        0x004d0000, 0x004d0000, 0x003f2000, 0x0009ed70,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'u'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x01100110, 0x0b600890, 0x0b600890, 0x0b600890,
+        0x0b600890, 0x0b600890, 0x09a01d90, 0x03eeda90,
+        0x00120000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'v'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x12000020, 0x2f1004e0, 0x0c600890, 0x07b00e40,
+        0x01f24d00, 0x00b79800, 0x006ce300, 0x001fd000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'w'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x20000012, 0xd400007a, 0x980000a7, 0x6b0b80d3,
+        0x2e1cc2f0, 0x0e78a7b0, 0x0ae36d80, 0x07e02f40,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'x'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x02000020, 0x0c700aa0, 0x02e36d10, 0x005de300,
+        0x001dc000, 0x009ac700, 0x06d12e40, 0x3e3006d1,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // 'y'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x12000020, 0x2f2002f1, 0x0b8008b0, 0x05d00d40,
+        0x00e44e00, 0x008aa800, 0x002ff200, 0x000bb000,
+        0x000c6000, 0x038e0000, 0x0aa30000, 0x00000000,
+    ],
+    // 'z'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x01222210, 0x06bbbe90, 0x00003e30, 0x0001d500,
+        0x000b8000, 0x009b0000, 0x06d10000, 0x0bffff90,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+    // '{'
+    [
+        0x00000000, 0x00000000, 0x0002ad50, 0x0008b100,
+        0x00098000, 0x00098000, 0x000a8000, 0x047e4000,
+        0x06ad2000, 0x000b7000, 0x00098000, 0x00098000,
+        0x0009a000, 0x0003ec40, 0x00000210, 0x00000000,
+    ],
+    // '|'
+    [
+        0x00000000, 0x00000000, 0x00087000, 0x00098000,
+        0x00098000, 0x00098000, 0x00098000, 0x00098000,
+        0x00098000, 0x00098000, 0x00098000, 0x00098000,
+        0x00098000, 0x00098000, 0x00098000, 0x00011000,
+    ],
+    // '}'
+    [
+        0x00000000, 0x00000000, 0x08d90000, 0x001d5000,
+        0x000b7000, 0x000b8000, 0x000b8000, 0x0006d620,
+        0x0003da40, 0x000a8000, 0x000b8000, 0x000b8000,
+        0x000c5000, 0x07dd1000, 0x01100000, 0x00000000,
+    ],
+    // '~'
+    [
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x3beb6373,
+        0x5425bd91, 0x00000000, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x00000000, 0x00000000,
+    ],
+];
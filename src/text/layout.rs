@@ -0,0 +1,167 @@
+//! Turns strings into positioned glyph quads.
+
+use glam::{UVec2, Vec2, Vec3};
+
+use super::{
+    atlas::Atlas,
+    font::{CELL_HEIGHT, CELL_WIDTH},
+};
+use crate::hud::View;
+
+/// Offset of a label from the point it is attached to, in pixels.
+const LABEL_OFFSET: Vec2 = glam::const_vec2!([8.0, -8.0]);
+
+/// Where a piece of text is placed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Anchor {
+    /// Top left corner on the screen, in pixels.
+    Screen(Vec2),
+    /// Point in the world that the text is drawn next to.
+    World(Vec3),
+}
+
+impl Anchor {
+    /// Top left corner of the text on the screen, or `None` if it is
+    /// attached to a point behind the eye.
+    ///
+    /// Screen positions and offsets are multiplied by `scale_factor`, to
+    /// convert them from logical to physical pixels.
+    pub fn resolve(&self, view: &View, scale_factor: f32) -> Option<Vec2> {
+        match *self {
+            Self::Screen(position) => Some(position * scale_factor),
+            Self::World(position) => Some(view.to_pixel(position)? + LABEL_OFFSET * scale_factor),
+        }
+    }
+}
+
+/// A string waiting to be drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Text {
+    pub anchor: Anchor,
+    pub text: String,
+    pub color: [f32; 4],
+}
+
+/// A rectangle of the screen to be filled with part of the atlas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphQuad {
+    /// Top left corner on the screen, in pixels.
+    pub position: Vec2,
+    /// Top left corner in the atlas, in pixels.
+    pub atlas_position: UVec2,
+    pub size: UVec2,
+}
+
+/// Size of the box taken up by the text, in pixels.
+pub fn measure(text: &str) -> Vec2 {
+    let (columns, rows) = text.lines().fold((0, 0), |(columns, rows), line| {
+        (columns.max(line.chars().count()), rows + 1)
+    });
+    Vec2::new(
+        (columns as u32 * CELL_WIDTH) as f32,
+        (rows * CELL_HEIGHT) as f32,
+    )
+}
+
+/// Lays out the text with its top left corner at `origin`, breaking lines at
+/// newlines.
+///
+/// The origin is rounded to whole pixels, so that glyphs are drawn crisply.
+pub fn layout<'a>(
+    atlas: &'a Atlas,
+    text: &'a str,
+    origin: Vec2,
+) -> impl Iterator<Item = GlyphQuad> + 'a {
+    let origin = origin.round();
+    text.lines().enumerate().flat_map(move |(row, line)| {
+        line.chars().enumerate().filter_map(move |(column, c)| {
+            let glyph = atlas.glyph(c);
+            if glyph.size.x == 0 || glyph.size.y == 0 {
+                return None;
+            }
+            let cell = UVec2::new(column as u32 * CELL_WIDTH, row as u32 * CELL_HEIGHT);
+            Some(GlyphQuad {
+                position: origin + cell.as_vec2() + glyph.offset.as_vec2(),
+                atlas_position: glyph.position,
+                size: glyph.size,
+            })
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Mat4;
+
+    use super::*;
+
+    /// Looks along +Y from the origin, with +Z up.
+    fn view() -> View {
+        let dims = UVec2::new(800, 600);
+        let y_fov = 1.0f32;
+        let x_fov = 2.0 * ((y_fov * 0.5).tan() * 800.0 / 600.0).atan();
+        let proj = Mat4::perspective_rh(y_fov, 800.0 / 600.0, 0.1, 100.0);
+        let view = Mat4::look_at_rh(Vec3::ZERO, Vec3::Y, Vec3::Z);
+        View {
+            view_proj: proj * view,
+            eye: Vec3::ZERO,
+            forward: Vec3::Y,
+            up: Vec3::Z,
+            x_fov,
+            y_fov,
+            dims,
+        }
+    }
+
+    #[test]
+    fn measures_the_longest_line() {
+        assert_eq!(measure(""), Vec2::ZERO);
+        assert_eq!(
+            measure("ab\ncde"),
+            Vec2::new(3.0 * CELL_WIDTH as f32, 2.0 * CELL_HEIGHT as f32)
+        );
+    }
+
+    #[test]
+    fn lays_glyphs_out_in_cells() {
+        let atlas = Atlas::new();
+        let quads: Vec<_> = layout(&atlas, "A b\nc", Vec2::new(10.4, 19.6)).collect();
+        // The space has no quad.
+        assert_eq!(quads.len(), 3);
+        let origin = Vec2::new(10.0, 20.0);
+        let expected = [
+            ('A', UVec2::new(0, 0)),
+            ('b', UVec2::new(2 * CELL_WIDTH, 0)),
+            ('c', UVec2::new(0, CELL_HEIGHT)),
+        ];
+        for (quad, (c, cell)) in quads.iter().zip(expected) {
+            let glyph = atlas.glyph(c);
+            assert_eq!(
+                quad.position,
+                origin + cell.as_vec2() + glyph.offset.as_vec2()
+            );
+            assert_eq!(quad.atlas_position, glyph.position);
+            assert_eq!(quad.size, glyph.size);
+        }
+    }
+
+    #[test]
+    fn screen_anchors_scale_with_the_window() {
+        let anchor = Anchor::Screen(Vec2::new(10.0, 20.0));
+        assert_eq!(anchor.resolve(&view(), 1.0), Some(Vec2::new(10.0, 20.0)));
+        assert_eq!(anchor.resolve(&view(), 2.0), Some(Vec2::new(20.0, 40.0)));
+    }
+
+    #[test]
+    fn world_anchors_follow_the_point() {
+        let view = view();
+        let center = Anchor::World(Vec3::new(0.0, 10.0, 0.0))
+            .resolve(&view, 2.0)
+            .unwrap();
+        assert!((center - (Vec2::new(400.0, 300.0) + LABEL_OFFSET * 2.0)).length() < 1e-3);
+        assert_eq!(
+            Anchor::World(Vec3::new(0.0, -10.0, 0.0)).resolve(&view, 1.0),
+            None
+        );
+    }
+}
@@ -0,0 +1,12 @@
+//! Bitmap text for the HUD.
+//!
+//! The built-in font is packed into a glyph [`atlas::Atlas`], and strings are
+//! turned into quads referencing it by [`layout`]. Both happen on the CPU, so
+//! any HUD backend can draw the result.
+
+pub mod atlas;
+pub mod font;
+pub mod layout;
+
+pub use atlas::Atlas;
+pub use layout::{layout, measure, Anchor, GlyphQuad, Text};
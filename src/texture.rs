@@ -0,0 +1,280 @@
+//! Images loaded on the CPU and uploaded as textures with mipmaps.
+
+use std::{fs::File, io, num::NonZeroU32, path::Path};
+
+use anyhow::{bail, Context};
+
+use crate::GraphicsContext;
+
+/// An 8-bit RGBA image, with rows from top to bottom.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<[u8; 4]>,
+}
+
+impl Image {
+    /// A single pixel of the given color.
+    pub fn solid(color: [u8; 4]) -> Self {
+        Self {
+            width: 1,
+            height: 1,
+            pixels: vec![color],
+        }
+    }
+
+    /// Decodes a PNG file of any color type and bit depth. A missing file is
+    /// not an error.
+    pub fn load_png(path: impl AsRef<Path>) -> anyhow::Result<Option<Self>> {
+        let path = path.as_ref();
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        Self::decode_png(io::BufReader::new(file))
+            .with_context(|| format!("failed to load {}", path.display()))
+            .map(Some)
+    }
+
+    /// Encodes the image as an 8-bit RGBA PNG file.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let save = || -> anyhow::Result<()> {
+            let file = io::BufWriter::new(File::create(path)?);
+            let mut encoder = png::Encoder::new(file, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(bytemuck::cast_slice(&self.pixels))?;
+            writer.finish()?;
+            Ok(())
+        };
+        save().with_context(|| format!("failed to save {}", path.display()))
+    }
+
+    fn decode_png(reader: impl io::Read) -> anyhow::Result<Self> {
+        let mut decoder = png::Decoder::new(reader);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        if info.color_type == png::ColorType::Indexed {
+            bail!("indexed colors weren't expanded");
+        }
+        let channels = info.color_type.samples();
+        let pixels = buffer
+            .chunks_exact(info.line_size)
+            .take(info.height as usize)
+            .flat_map(|row| row[..info.width as usize * channels].chunks_exact(channels))
+            .map(|pixel| match *pixel {
+                [l] => [l, l, l, u8::MAX],
+                [l, a] => [l, l, l, a],
+                [r, g, b] => [r, g, b, u8::MAX],
+                [r, g, b, a] => [r, g, b, a],
+                _ => unreachable!(),
+            })
+            .collect();
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+
+    /// The image at half the size, rounded down but at least one pixel, with
+    /// each pixel the average of the ones it covers.
+    pub fn downsample(&self) -> Self {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let pixel = |x: u32, y: u32| {
+            let (x, y) = (x.min(self.width - 1), y.min(self.height - 1));
+            self.pixels[(y * self.width + x) as usize]
+        };
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let corners = [
+                    pixel(2 * x, 2 * y),
+                    pixel(2 * x + 1, 2 * y),
+                    pixel(2 * x, 2 * y + 1),
+                    pixel(2 * x + 1, 2 * y + 1),
+                ];
+                let mut average = [0; 4];
+                for (c, channel) in average.iter_mut().enumerate() {
+                    let sum: u32 = corners.iter().map(|corner| corner[c] as u32).sum();
+                    *channel = ((sum + 2) / 4) as u8;
+                }
+                average
+            })
+            .collect();
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// The image followed by each smaller mip level, down to one pixel.
+    pub fn mip_chain(&self) -> Vec<Self> {
+        let mut levels = vec![self.clone()];
+        while let Some(last) = levels
+            .last()
+            .filter(|last| last.width > 1 || last.height > 1)
+        {
+            let next = last.downsample();
+            levels.push(next);
+        }
+        levels
+    }
+}
+
+/// A 2D texture with a full chain of mipmaps.
+pub struct Texture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+}
+
+impl Texture {
+    pub fn new(
+        gfx: &GraphicsContext,
+        label: Option<&'static str>,
+        image: &Image,
+    ) -> anyhow::Result<Self> {
+        let max_size = gfx.device.limits().max_texture_dimension_2d;
+        if image.width > max_size || image.height > max_size {
+            bail!(
+                "{}x{} texture is larger than the limit of {}",
+                image.width,
+                image.height,
+                max_size
+            );
+        }
+
+        let levels = image.mip_chain();
+        let texture = gfx.device.create_texture(&wgpu::TextureDescriptor {
+            label,
+            size: wgpu::Extent3d {
+                width: image.width,
+                height: image.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: levels.len() as u32,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            // The scene is shaded without converting from sRGB, so colors
+            // are used as they are stored.
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        });
+        for (mip_level, level) in levels.iter().enumerate() {
+            gfx.queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: mip_level as u32,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                bytemuck::cast_slice(&level.pixels),
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(4 * level.width),
+                    rows_per_image: NonZeroU32::new(level.height),
+                },
+                wgpu::Extent3d {
+                    width: level.width,
+                    height: level.height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+        let view = texture.create_view(&Default::default());
+        Ok(Self { texture, view })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}-{}", name, std::process::id()))
+    }
+
+    /// An image where each pixel's red and green are its coordinates.
+    fn gradient(width: u32, height: u32) -> Image {
+        Image {
+            width,
+            height,
+            pixels: (0..height)
+                .flat_map(|y| (0..width).map(move |x| [x as u8, y as u8, 7, 255]))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn downsampling_averages_squares_of_four() {
+        let image = Image {
+            width: 2,
+            height: 2,
+            pixels: vec![
+                [0, 0, 0, 0],
+                [255, 0, 0, 255],
+                [0, 255, 0, 255],
+                [1, 2, 3, 4],
+            ],
+        };
+        let half = image.downsample();
+        assert_eq!((half.width, half.height), (1, 1));
+        // Rounded to the nearest value.
+        assert_eq!(half.pixels, vec![[64, 64, 1, 129]]);
+    }
+
+    #[test]
+    fn downsampling_odd_sizes_rounds_down() {
+        let half = gradient(5, 3).downsample();
+        assert_eq!((half.width, half.height), (2, 1));
+        // The last column and row are left out.
+        assert_eq!(half.pixels, vec![[1, 1, 7, 255], [3, 1, 7, 255]]);
+    }
+
+    #[test]
+    fn downsampling_a_line_keeps_it_one_pixel_thick() {
+        let half = gradient(4, 1).downsample();
+        assert_eq!((half.width, half.height), (2, 1));
+        // The edge pixel is repeated to cover the missing row.
+        assert_eq!(half.pixels, vec![[1, 0, 7, 255], [3, 0, 7, 255]]);
+    }
+
+    #[test]
+    fn mip_chain_goes_down_to_one_pixel() {
+        let levels = gradient(8, 3).mip_chain();
+        let sizes: Vec<_> = levels.iter().map(|l| (l.width, l.height)).collect();
+        assert_eq!(sizes, vec![(8, 3), (4, 1), (2, 1), (1, 1)]);
+        assert_eq!(levels[0], gradient(8, 3));
+
+        assert_eq!(Image::solid([1, 2, 3, 4]).mip_chain().len(), 1);
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let dir = temp_dir("texture-test");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("gradient.png");
+        let mut image = gradient(5, 3);
+        image.pixels[4] = [9, 8, 7, 6];
+        image.save_png(&path).unwrap();
+        let loaded = Image::load_png(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded, Some(image));
+    }
+
+    #[test]
+    fn missing_png_is_not_an_error() {
+        let path = temp_dir("texture-test-missing").join("missing.png");
+        assert_eq!(Image::load_png(path).unwrap(), None);
+    }
+}
@@ -14,6 +14,7 @@ impl SimInstant {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SimDuration {
     micros: i64,
 }
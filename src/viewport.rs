@@ -1,14 +1,14 @@
 use std::f32::consts::TAU;
 
 use bytemuck::{Pod, Zeroable};
-use glam::{EulerRot, Mat4, Quat, Vec3};
+use glam::{EulerRot, Mat4, Quat, UVec2, Vec2, Vec3};
 use wgpu::util::DeviceExt;
 
-use crate::GraphicsContext;
+use crate::{math::Ray, GraphicsContext};
 
-const FOV: f32 = 75.0 / 360.0 * TAU;
-const Z_NEAR: f32 = 0.1;
-const Z_FAR: f32 = 1000.0;
+pub const FOV: f32 = 75.0 / 360.0 * TAU;
+pub const Z_NEAR: f32 = 0.1;
+pub const Z_FAR: f32 = 1000.0;
 
 #[derive(Debug, Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
@@ -38,6 +38,22 @@ pub struct Viewport {
     pub up: Vec3,
     pub pitch: f32,
     pub yaw: f32,
+    /// Reference frame that `pitch` and `yaw` are relative to.
+    pub frame: Quat,
+    pub z_near: f32,
+    pub z_far: f32,
+}
+
+/// Where the camera is and which way it is pitched, along with the depth
+/// range it sees. Each camera mode takes these over, while the user keeps
+/// control of the yaw.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraPose {
+    pub position: Vec3,
+    pub frame: Quat,
+    pub pitch: f32,
+    pub z_near: f32,
+    pub z_far: f32,
 }
 
 impl Viewport {
@@ -86,6 +102,9 @@ impl Viewport {
             up: Vec3::Z,
             pitch: 0.0,
             yaw: 0.0,
+            frame: Quat::IDENTITY,
+            z_near: Z_NEAR,
+            z_far: Z_FAR,
         }
     }
 
@@ -97,6 +116,24 @@ impl Viewport {
         &self.bind_group
     }
 
+    pub fn pose(&self) -> CameraPose {
+        CameraPose {
+            position: self.camera_position,
+            frame: self.frame,
+            pitch: self.pitch,
+            z_near: self.z_near,
+            z_far: self.z_far,
+        }
+    }
+
+    pub fn set_pose(&mut self, pose: CameraPose) {
+        self.camera_position = pose.position;
+        self.frame = pose.frame;
+        self.pitch = pose.pitch;
+        self.z_near = pose.z_near;
+        self.z_far = pose.z_far;
+    }
+
     pub fn camera_pos(&self) -> Vec3 {
         self.camera_position
     }
@@ -109,23 +146,40 @@ impl Viewport {
     }
 
     pub fn camera_orientation(&self) -> Quat {
-        Quat::from_euler(EulerRot::ZXY, self.yaw, self.pitch, 0.0)
+        self.frame * Quat::from_euler(EulerRot::ZXY, self.yaw, self.pitch, 0.0)
     }
 
     pub fn aspect(&self) -> f32 {
-        let size = self.gfx.window.inner_size();
+        let size = self.gfx.size();
         size.width as f32 / size.height as f32
     }
 
+    /// Horizontal field of view (radians).
+    pub fn x_fov(&self) -> f32 {
+        ((FOV * 0.5).tan() * self.aspect()).atan() * 2.0
+    }
+
+    /// Vertical field of view (radians).
+    pub fn y_fov(&self) -> f32 {
+        FOV
+    }
+
     pub fn view_proj(&self) -> Mat4 {
-        let projection = Mat4::perspective_rh(FOV, self.aspect(), Z_NEAR, Z_FAR);
-        let forward = self.camera_orientation() * Vec3::Y;
-        let camera = Mat4::look_at_rh(
+        view_proj(
             self.camera_position,
-            self.camera_position + forward,
-            self.up(),
-        );
-        projection * camera
+            self.camera_orientation(),
+            self.aspect(),
+            self.z_near,
+            self.z_far,
+        )
+    }
+
+    /// Ray from the camera through a pixel of the window, with the origin in
+    /// the top left corner.
+    pub fn pixel_ray(&self, pixel: Vec2) -> Ray {
+        let size = self.gfx.size();
+        let ndc = pixel_to_ndc(pixel, UVec2::new(size.width, size.height));
+        Ray::unproject(self.view_proj(), ndc)
     }
 
     pub fn update(&mut self) {
@@ -137,10 +191,74 @@ impl Viewport {
                 camera: self.camera_position.into(),
                 forward: self.forward().into(),
                 up: self.up().into(),
-                x_fov: ((FOV * 0.5).tan() * self.aspect()).atan() * 2.0,
-                y_fov: FOV,
+                x_fov: self.x_fov(),
+                y_fov: self.y_fov(),
                 _padding: Default::default(),
             }),
         );
     }
 }
+
+/// View-projection matrix of a camera at `position`, looking along +Y and
+/// with +Z up after rotating by `orientation`.
+fn view_proj(position: Vec3, orientation: Quat, aspect: f32, z_near: f32, z_far: f32) -> Mat4 {
+    let projection = Mat4::perspective_rh(FOV, aspect, z_near, z_far);
+    let camera = Mat4::look_at_rh(
+        position,
+        position + orientation * Vec3::Y,
+        orientation * Vec3::Z,
+    );
+    projection * camera
+}
+
+/// Converts pixel coordinates in a window of the given size, with the origin
+/// in the top left corner, to normalized device coordinates.
+fn pixel_to_ndc(pixel: Vec2, size: UVec2) -> Vec2 {
+    Vec2::new(
+        2.0 * pixel.x / size.x as f32 - 1.0,
+        1.0 - 2.0 * pixel.y / size.y as f32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::DVec3;
+
+    use super::*;
+
+    #[test]
+    fn pixels_map_to_the_edges_of_the_screen() {
+        let size = UVec2::new(800, 600);
+        assert_eq!(pixel_to_ndc(Vec2::ZERO, size), Vec2::new(-1.0, 1.0));
+        assert_eq!(pixel_to_ndc(Vec2::new(400.0, 300.0), size), Vec2::ZERO);
+        assert_eq!(
+            pixel_to_ndc(Vec2::new(800.0, 600.0), size),
+            Vec2::new(1.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn pixel_rays_pass_through_the_projected_point() {
+        let size = UVec2::new(800, 600);
+        let eye = Vec3::new(0.0, -5.0, 3.0);
+        // Turned a little to the left and looking down.
+        let orientation = Quat::from_euler(EulerRot::ZXY, 0.2, -0.5, 0.0);
+        let view_proj = view_proj(eye, orientation, 800.0 / 600.0, Z_NEAR, Z_FAR);
+
+        // The middle of the screen is straight ahead.
+        let ahead = Ray::unproject(view_proj, Vec2::ZERO);
+        assert!((ahead.direction - (orientation * Vec3::Y).as_dvec3()).length() < 1e-5);
+
+        let point = Vec3::new(1.0, 2.0, 0.5);
+        let clip = view_proj * point.extend(1.0);
+        let ndc = Vec2::new(clip.x, clip.y) / clip.w;
+        let pixel = Vec2::new(ndc.x + 1.0, 1.0 - ndc.y) * 0.5 * size.as_vec2();
+        let ray = Ray::unproject(view_proj, pixel_to_ndc(pixel, size));
+
+        let to_point = point.as_dvec3() - ray.origin;
+        let miss = to_point - ray.direction * to_point.dot(ray.direction);
+        assert!(miss.length() < 1e-4, "missed by {}", miss);
+        assert!(to_point.dot(ray.direction) > 0.0);
+        assert!(ray.direction.dot(DVec3::Y) > 0.0);
+    }
+}
@@ -1,22 +1,35 @@
-use std::time::Instant;
+use std::{path::Path, time::Duration};
 
-use glam::{DVec3, Mat4, Quat, Vec3};
+use glam::{DMat3, DQuat, DVec3, Mat4, Vec3};
 use valet::{Tag, Valet};
 
 use crate::{
+    atmosphere::Atmosphere,
+    craft::Craft,
+    eclipse::{self, Sphere},
+    material::TextureMaps,
     orbit::{Orbit2D, Orbit3D, State3D},
+    terrain::surface::{Biome, Surface},
     time::{SimDuration, SimInstant},
 };
 
 /// Universal gravitational constant (m^3/kg/s^2)
 const G: f64 = 6.67430e-11;
 
+/// Simulated seconds per real second.
+const WARP: f64 = 100000.0;
+
+/// Fastest allowed time warp.
+const MAX_WARP: f64 = 1.0e7;
+
 pub struct World {
     bodies: Valet<Body>,
     root: Option<Tag<Body>>,
     time: SimInstant,
-    last_update: Instant,
+    warp: f64,
     pub body_tags: Vec<Tag<Body>>,
+    maneuver_nodes: Vec<ManeuverNode>,
+    craft: Option<Craft>,
 }
 
 impl World {
@@ -25,11 +38,14 @@ impl World {
             bodies: Valet::new(),
             root: None,
             time: SimInstant::epoch(),
-            last_update: Instant::now(),
+            warp: WARP,
             body_tags: vec![],
+            maneuver_nodes: vec![],
+            craft: None,
         };
 
         let sun = this.add_body(
+            "Sun",
             &OrbitSpec::Fixed(DVec3::ZERO),
             2.0e30,
             6.957e9,
@@ -37,6 +53,7 @@ impl World {
         );
         this.root = Some(sun);
         let earth = this.add_body(
+            "Earth",
             &OrbitSpec::Apsides {
                 parent: sun,
                 apo: 1.521e11,
@@ -50,7 +67,18 @@ impl World {
             6.957e9,
             // 6.365e6,
         );
-        let _moon = this.add_body(
+        this.bodies[&earth].surface = Some(Surface {
+            seed: 3,
+            relief: 0.01,
+            frequency: 2.0,
+            ridges: 0.4,
+            craters: 0,
+            max_crater_radius: 0.0,
+            biome: Biome::Terran { sea_level: 0.0 },
+        });
+        this.bodies[&earth].atmosphere = Some(Atmosphere::earth_like());
+        let moon = this.add_body(
+            "Moon",
             &OrbitSpec::Apsides {
                 parent: earth,
                 apo: 4.054e8,
@@ -63,11 +91,48 @@ impl World {
             7.342e22,
             1.736e6,
         );
+        this.bodies[&moon].surface = Some(Surface {
+            seed: 7,
+            relief: 0.01,
+            frequency: 1.5,
+            ridges: 0.1,
+            craters: 400,
+            max_crater_radius: 0.15,
+            biome: Biome::Barren,
+        });
+        // Earth is drawn much larger than it really is, past the edge of its
+        // own sphere of influence, so the craft orbits the Moon instead.
+        let craft = this.add_body(
+            "Craft",
+            &OrbitSpec::Apsides {
+                parent: moon,
+                apo: 2.2e6,
+                peri: 1.9e6,
+                t0: SimInstant::epoch(),
+                arg_pe: 0.0,
+                inc: 10.0_f64.to_radians(),
+                lan: 0.0,
+            },
+            1.0e4,
+            5.0,
+        );
+        this.craft = Some(Craft::new(
+            craft,
+            DMat3::from_diagonal(DVec3::new(4.0e4, 1.5e4, 4.0e4)),
+            DVec3::new(5.0e3, 2.5e3, 5.0e3),
+            DVec3::new(1.0e4, 1.0e4, 1.0e4),
+        ));
 
         this
     }
 
-    fn add_body(&mut self, orbit_spec: &OrbitSpec, mass: f64, radius: f64) -> Tag<Body> {
+    fn add_body(
+        &mut self,
+        name: &str,
+        orbit_spec: &OrbitSpec,
+        mass: f64,
+        radius: f64,
+    ) -> Tag<Body> {
         let (m1, parent_state) = orbit_spec
             .parent()
             .map(|tag| {
@@ -83,11 +148,16 @@ impl World {
         let abs_state = state.offset_by(&parent_state);
 
         let tag = self.bodies.insert(Body {
+            name: name.to_owned(),
             trajectory,
             abs_state,
             satellites: vec![],
             mass,
             radius,
+            orientation: DQuat::IDENTITY,
+            surface: None,
+            textures: None,
+            atmosphere: None,
         });
         if let Some(parent) = orbit_spec.parent() {
             self.bodies[&parent].satellites.push(tag);
@@ -114,33 +184,244 @@ impl World {
         }
     }
 
-    pub fn update(&mut self) {
-        let now = Instant::now();
-        let dt = SimDuration::from((now - self.last_update) * 100000);
-        self.time += dt;
-        self.last_update = now;
+    /// Advances the simulation by `dt` of real time, sped up by the warp.
+    pub fn update(&mut self, dt: Duration) {
+        self.advance(SimDuration::from_secs_f64(dt.as_secs_f64() * self.warp));
+    }
+
+    /// Advances the simulation by exactly `duration` of simulated time,
+    /// regardless of the warp.
+    pub fn advance(&mut self, duration: SimDuration) {
+        self.time += duration;
 
         self.update_positions();
+        let time = self.time;
+        self.maneuver_nodes.retain(|node| node.time >= time);
+    }
+
+    /// Turns the craft, if there is one, for `dt` of real time.
+    ///
+    /// The craft's rotation isn't sped up by the warp, so that it can still
+    /// be controlled while time is warped. `input` is passed on to
+    /// [`Craft::update`].
+    pub fn update_craft(&mut self, input: DVec3, dt: Duration) {
+        let direction = match &self.craft {
+            Some(craft) => craft.sas_direction(self),
+            None => return,
+        };
+        let craft = self.craft.as_mut().unwrap();
+        craft.update(input, direction, dt.as_secs_f64());
+        self.bodies[&craft.body].orientation = craft.attitude();
+    }
+
+    /// Textures each body with the maps in `dir` named after it (see
+    /// [`TextureMaps::find`]). Returns how many bodies have maps there.
+    pub fn find_textures(&mut self, dir: &Path) -> usize {
+        let mut found = 0;
+        for tag in &self.body_tags {
+            let body = &mut self.bodies[tag];
+            body.textures = TextureMaps::find(dir, &body.name);
+            found += body.textures.is_some() as usize;
+        }
+        found
+    }
+
+    pub fn craft(&self) -> Option<&Craft> {
+        self.craft.as_ref()
+    }
+
+    pub fn craft_mut(&mut self) -> Option<&mut Craft> {
+        self.craft.as_mut()
     }
 
     pub fn body(&self, tag: &Tag<Body>) -> &Body {
         &self.bodies[tag]
     }
+
+    pub fn time(&self) -> SimInstant {
+        self.time
+    }
+
+    /// Simulated seconds per real second.
+    pub fn warp(&self) -> f64 {
+        self.warp
+    }
+
+    /// Sets the time warp, limited to between real time and [`MAX_WARP`].
+    pub fn set_warp(&mut self, warp: f64) {
+        self.warp = warp.clamp(1.0, MAX_WARP);
+    }
+
+    pub fn root(&self) -> Option<&Tag<Body>> {
+        self.root.as_ref()
+    }
+
+    /// Bodies that cast shadows: every body but the root, which gives the
+    /// light, and the craft.
+    pub fn occluders(&self) -> impl Iterator<Item = Sphere> + '_ {
+        let craft = self.craft.as_ref().map(|craft| craft.body);
+        self.body_tags
+            .iter()
+            .filter(move |&&tag| Some(tag) != self.root && Some(tag) != craft)
+            .map(|tag| self.bodies[tag].sphere())
+    }
+
+    /// Fraction of the root body's light that reaches a point, from 0 in
+    /// the umbra of a body to 1 in full light. See [`eclipse::illumination`].
+    ///
+    /// Without a root body there's no light at all.
+    pub fn illumination(&self, point: DVec3) -> f64 {
+        match self.root {
+            Some(root) => {
+                eclipse::illumination(point, self.bodies[&root].sphere(), self.occluders())
+            }
+            None => 0.0,
+        }
+    }
+
+    /// Radius of the body's sphere of influence, using the Laplace
+    /// approximation `a * (m / M)^(2/5)`.
+    ///
+    /// Returns `None` for bodies that aren't orbiting anything.
+    pub fn soi_radius(&self, tag: &Tag<Body>) -> Option<f64> {
+        let body = &self.bodies[tag];
+        let parent = &self.bodies[body.parent()?];
+        let a = body.orbit()?.shape().a().abs();
+        Some(a * (body.mass / parent.mass).powf(0.4))
+    }
+
+    /// Planned maneuvers that have not happened yet, in the order they were
+    /// added.
+    pub fn maneuver_nodes(&self) -> &[ManeuverNode] {
+        &self.maneuver_nodes
+    }
+
+    /// Plans a maneuver at the next time the body passes the given true
+    /// anomaly on its current orbit.
+    ///
+    /// Returns `None` without adding anything if the body isn't orbiting, or
+    /// is on an open trajectory and has already passed that point.
+    pub fn add_maneuver_node(&mut self, tag: &Tag<Body>, angle: f64) -> Option<&ManeuverNode> {
+        let orbit = self.bodies[tag].orbit()?;
+        let time = orbit.shape().next_time_at(angle, self.time)?;
+        self.maneuver_nodes.push(ManeuverNode {
+            body: *tag,
+            angle,
+            time,
+            delta_v: DVec3::ZERO,
+        });
+        self.maneuver_nodes.last()
+    }
+
+    /// State of the body relative to the body it is orbiting.
+    pub fn relative_state(&self, tag: &Tag<Body>) -> State3D {
+        let body = &self.bodies[tag];
+        match body.parent() {
+            Some(parent) => body.abs_state.relative_to(&self.bodies[parent].abs_state),
+            None => body.abs_state,
+        }
+    }
+}
+
+/// A burn planned at a point on a body's orbit.
+#[derive(Debug, Clone, Copy)]
+pub struct ManeuverNode {
+    pub body: Tag<Body>,
+    /// True anomaly on the orbit at the time the node was placed.
+    pub angle: f64,
+    pub time: SimInstant,
+    /// Change in velocity along the prograde, normal and radial directions
+    /// (m/s).
+    pub delta_v: DVec3,
 }
 
 pub struct Body {
+    name: String,
     trajectory: Trajectory,
     abs_state: State3D,
     satellites: Vec<Tag<Body>>,
     mass: f64,
     radius: f64,
+    orientation: DQuat,
+    surface: Option<Surface>,
+    textures: Option<TextureMaps>,
+    atmosphere: Option<Atmosphere>,
 }
 
 impl Body {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn parent(&self) -> Option<&Tag<Body>> {
+        self.trajectory.parent()
+    }
+
+    pub fn satellites(&self) -> &[Tag<Body>] {
+        &self.satellites
+    }
+
+    /// The body's orbit around its parent, if it is not fixed in place.
+    pub fn orbit(&self) -> Option<&Orbit3D> {
+        match &self.trajectory {
+            Trajectory::Orbiting { orbit, .. } => Some(orbit),
+            _ => None,
+        }
+    }
+
+    /// Absolute state in the world frame.
+    pub fn state(&self) -> &State3D {
+        &self.abs_state
+    }
+
+    pub fn position(&self) -> DVec3 {
+        self.abs_state.position
+    }
+
+    pub fn velocity(&self) -> DVec3 {
+        self.abs_state.velocity
+    }
+
+    pub fn mass(&self) -> f64 {
+        self.mass
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    /// Parameters for the body's procedural terrain, if it has any.
+    pub fn surface(&self) -> Option<&Surface> {
+        self.surface.as_ref()
+    }
+
+    /// Image files to texture the body with, if it has any.
+    pub fn textures(&self) -> Option<&TextureMaps> {
+        self.textures.as_ref()
+    }
+
+    /// The body's atmosphere, if it has one.
+    pub fn atmosphere(&self) -> Option<&Atmosphere> {
+        self.atmosphere.as_ref()
+    }
+
+    /// The sphere the body fills in the world frame.
+    pub fn sphere(&self) -> Sphere {
+        Sphere {
+            center: self.position(),
+            radius: self.radius,
+        }
+    }
+
+    /// Rotation from the body's own frame to the world frame.
+    pub fn orientation(&self) -> DQuat {
+        self.orientation
+    }
+
     pub fn model_matrix(&self) -> Mat4 {
         Mat4::from_scale_rotation_translation(
             Vec3::splat(self.radius as f32),
-            Quat::IDENTITY,
+            self.orientation.as_f32(),
             self.abs_state.position.as_vec3(),
         )
     }